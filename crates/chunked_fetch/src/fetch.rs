@@ -0,0 +1,119 @@
+use jiff::civil::Date;
+use roc_calendar::ChunkSize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ChunkOptions {
+    pub chunk_size: ChunkSize,
+    pub max_concurrency: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: ChunkSize::Month,
+            max_concurrency: 4,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Runs `fetch` for every chunk on at most `max_concurrency` threads and returns the results in
+/// chunk order. The remaining chunks are skipped as soon as one of them fails.
+pub fn fetch_chunks<T, E, F, P>(
+    chunks: &[(Date, Date)],
+    max_concurrency: usize,
+    fetch: F,
+    on_progress: P,
+) -> Result<Vec<T>, E>
+where
+    T: Send,
+    E: Send,
+    F: Fn(&Date, &Date) -> Result<T, E> + Sync,
+    P: Fn(Progress) + Sync,
+{
+    let total = chunks.len();
+    let next_index = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new((0..total).map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..max_concurrency.clamp(1, total.max(1)) {
+            scope.spawn(|| {
+                while !failed.load(Ordering::Relaxed) {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some((start_date, end_date)) = chunks.get(index) else {
+                        break;
+                    };
+
+                    let result = fetch(start_date, end_date);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    } else {
+                        on_progress(Progress {
+                            completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                            total,
+                        });
+                    }
+                    results.lock().expect("results lock poisoned")[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    // Skipped chunks are left as `None`, which only happens after a failure
+    results
+        .into_inner()
+        .expect("results lock poisoned")
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_calendar::split_date_range;
+
+    #[test]
+    fn test_fetch_chunks() {
+        let chunks = split_date_range(
+            &Date::new(2025, 1, 1).unwrap(),
+            &Date::new(2025, 12, 31).unwrap(),
+            ChunkSize::Month,
+        );
+        let progress = Mutex::new(vec![]);
+        let months = fetch_chunks(
+            &chunks,
+            3,
+            |start_date, _| Ok::<_, ()>(start_date.month()),
+            |p| progress.lock().unwrap().push(p),
+        )
+        .unwrap();
+        assert_eq!(months, (1..=12).collect::<Vec<_>>());
+
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(progress.len(), 12);
+        assert!(progress.iter().all(|p| p.total == 12));
+        assert_eq!(progress.iter().map(|p| p.completed).max(), Some(12));
+
+        let result = fetch_chunks(
+            &chunks,
+            3,
+            |start_date, _| match start_date.month() {
+                6 => Err("failed"),
+                month => Ok(month),
+            },
+            |_| {},
+        );
+        assert_eq!(result, Err("failed"));
+    }
+}
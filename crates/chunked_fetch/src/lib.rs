@@ -0,0 +1,4 @@
+mod fetch;
+
+pub use crate::fetch::{ChunkOptions, Progress, fetch_chunks};
+pub use roc_calendar::{ChunkSize, split_date_range};
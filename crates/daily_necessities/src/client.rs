@@ -1,13 +1,25 @@
 use crate::Error;
+use crate::product::ProductCatalog;
 use crate::purchase_list::PurchaseList;
+use crate::station::StationList;
+use crate::supplier::SupplierList;
+use chunked_fetch::{ChunkOptions, Progress, fetch_chunks, split_date_range};
 use jiff::civil::Date;
+use log::{info, warn};
 use scraper::{Html, Selector};
+use serde::de::DeserializeOwned;
+use std::time::Instant;
 use ureq::Agent;
 use ureq::http::StatusCode;
+use ureq::http::header::LOCATION;
 
 const DOMAIN: &str = "http://192.168.41.123:90/";
 const LOGIN_URL: &str = "http://192.168.41.123:90/login";
+const LOGIN_PATH: &str = "/login";
 const PURCHASE_LIST_API_URL: &str = "http://192.168.41.123:90/backstage/purchase/list/search";
+const PRODUCT_LIST_API_URL: &str = "http://192.168.41.123:90/backstage/product/list/search";
+const SUPPLIER_LIST_API_URL: &str = "http://192.168.41.123:90/backstage/supplier/list/search";
+const STATION_LIST_API_URL: &str = "http://192.168.41.123:90/backstage/station/list/search";
 
 pub struct Client {
     agent: Agent,
@@ -31,21 +43,95 @@ impl Client {
         start_date: &Date,
         end_date: &Date,
     ) -> Result<PurchaseList, Error> {
+        self.search(
+            PURCHASE_LIST_API_URL,
+            &[
+                ("startday", &start_date.strftime("%Y%m%d").to_string()),
+                ("endday", &end_date.strftime("%Y%m%d").to_string()),
+            ],
+        )
+    }
+
+    /// Gets the purchase list like [`Client::get_purchase_list`], but splits the date range into
+    /// chunks which are fetched concurrently and merged into one [`PurchaseList`].
+    pub fn get_purchase_list_chunked<P: Fn(Progress) + Sync>(
+        &mut self,
+        start_date: &Date,
+        end_date: &Date,
+        chunk_options: &ChunkOptions,
+        on_progress: P,
+    ) -> Result<PurchaseList, Error> {
+        self.refresh_login_status()?;
+
+        let chunks = split_date_range(start_date, end_date, chunk_options.chunk_size);
+        let purchase_lists = fetch_chunks(
+            &chunks,
+            chunk_options.max_concurrency,
+            |start_date, end_date| {
+                self.post_search(
+                    PURCHASE_LIST_API_URL,
+                    &[
+                        ("startday", &start_date.strftime("%Y%m%d").to_string()),
+                        ("endday", &end_date.strftime("%Y%m%d").to_string()),
+                    ],
+                )
+            },
+            on_progress,
+        )?;
+        Ok(PurchaseList::merge(purchase_lists))
+    }
+
+    pub fn get_product_catalog(&mut self) -> Result<ProductCatalog, Error> {
+        self.search(PRODUCT_LIST_API_URL, &[])
+    }
+
+    pub fn get_supplier_list(&mut self) -> Result<SupplierList, Error> {
+        self.search(SUPPLIER_LIST_API_URL, &[])
+    }
+
+    pub fn get_station_list(&mut self) -> Result<StationList, Error> {
+        self.search(STATION_LIST_API_URL, &[])
+    }
+
+    fn search<T: DeserializeOwned>(
+        &mut self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, Error> {
         self.refresh_login_status()?;
+        self.post_search(url, params)
+    }
 
-        Ok(self
+    fn post_search<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, Error> {
+        let csrf_token = self.csrf_token.as_deref().ok_or(Error::CSRFTokenNotFound)?;
+        let started = Instant::now();
+        let result = self
             .agent
-            .post(PURCHASE_LIST_API_URL)
-            .send_form([
-                (
-                    "_token",
-                    self.csrf_token.as_ref().ok_or(Error::CSRFTokenNotFound)?,
-                ),
-                ("startday", &start_date.strftime("%Y%m%d").to_string()),
-                ("endday", &end_date.strftime("%Y%m%d").to_string()),
-            ])?
-            .body_mut()
-            .read_json()?)
+            .post(url)
+            .send_form(std::iter::once(("_token", csrf_token)).chain(params.iter().copied()))
+            .and_then(|mut response| {
+                let status = response.status().as_u16();
+                response.body_mut().read_json().map(|value| (status, value))
+            });
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok((status, value)) => {
+                info!(url, status, duration_ms; "received response");
+                Ok(value)
+            }
+            Err(error) => {
+                let status = match error {
+                    ureq::Error::StatusCode(status) => Some(status),
+                    _ => None,
+                };
+                warn!(url, status:?, duration_ms, error:%; "request failed");
+                Err(error.into())
+            }
+        }
     }
 
     fn refresh_login_status(&mut self) -> Result<bool, Error> {
@@ -60,7 +146,11 @@ impl Client {
         if is_cookie_expired || self.csrf_token.is_none() {
             // Login expired, reset csrf_token and perform login again
             self.csrf_token = None;
-            self.login()?;
+            if let Err(error) = self.login() {
+                warn!(username = self.username.as_str(), error:%; "login failed");
+                return Err(error);
+            }
+            info!(username = self.username.as_str(); "logged in");
 
             Ok(true)
         } else {
@@ -71,20 +161,37 @@ impl Client {
     fn login(&mut self) -> Result<(), Error> {
         let csrf_token = self.get_login_form_with_csrf_token()?;
 
-        // Submit login form
-        let response = match self.agent.post(LOGIN_URL).send_form([
-            ("_token", csrf_token.clone()),
-            ("user_id", self.username.clone()),
-            ("password", self.password.clone()),
-        ]) {
+        // Submit login form without following the redirect, so that its target can be inspected
+        let response = match self
+            .agent
+            .post(LOGIN_URL)
+            .config()
+            .max_redirects(0)
+            .build()
+            .send_form([
+                ("_token", csrf_token.clone()),
+                ("user_id", self.username.clone()),
+                ("password", self.password.clone()),
+            ]) {
             Ok(response) => response,
             Err(ureq::Error::StatusCode(status_code)) => {
-                return Err(Error::LoginError(status_code));
+                return Err(login_error_from_status_code(status_code));
             }
             Err(e) => return Err(Error::from(e)),
         };
         if response.status() != StatusCode::FOUND {
-            return Err(Error::LoginError(response.status().as_u16()));
+            return Err(login_error_from_status_code(response.status().as_u16()));
+        }
+
+        // Laravel redirects back to the login page with flashed errors when the login fails
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .unwrap_or_default();
+        if is_login_page_location(location) {
+            let messages = parse_login_error_messages(&self.get_login_page()?);
+            return Err(login_error_from_messages(&messages));
         }
 
         // Login success, set csrf_token
@@ -93,10 +200,14 @@ impl Client {
         Ok(())
     }
 
-    fn get_login_form_with_csrf_token(&self) -> Result<String, Error> {
+    fn get_login_page(&self) -> Result<Html, Error> {
         let mut response = self.agent.get(LOGIN_URL).call()?;
         let html = response.body_mut().read_to_string()?;
-        let html = Html::parse_document(&html);
+        Ok(Html::parse_document(&html))
+    }
+
+    fn get_login_form_with_csrf_token(&self) -> Result<String, Error> {
+        let html = self.get_login_page()?;
         let selector = Selector::parse(r#"meta[name="csrf-token"]"#).expect("Invalid selector");
         let csrf_token_meta = html
             .select(&selector)
@@ -108,3 +219,116 @@ impl Client {
             .to_string())
     }
 }
+
+fn login_error_from_status_code(status_code: u16) -> Error {
+    match status_code {
+        // Laravel answers a missing or stale CSRF token with "419 Page Expired"
+        419 => Error::CsrfMismatch,
+        // Login throttling
+        429 => Error::AccountLocked(String::from("Too Many Requests")),
+        500..=599 => Error::ServerError(status_code),
+        _ => Error::LoginError(status_code),
+    }
+}
+
+fn is_login_page_location(location: &str) -> bool {
+    let path = match location.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |index| &rest[index..]),
+        None => location,
+    };
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    path.trim_end_matches('/').ends_with(LOGIN_PATH)
+}
+
+fn parse_login_error_messages(html: &Html) -> Vec<String> {
+    let selector =
+        Selector::parse(".invalid-feedback, .alert-danger, .help-block").expect("Invalid selector");
+    html.select(&selector)
+        .map(|element| element.text().collect::<String>().trim().to_string())
+        .filter(|message| !message.is_empty())
+        .collect()
+}
+
+fn login_error_from_messages(messages: &[String]) -> Error {
+    const ACCOUNT_LOCKED_KEYWORDS: [&str; 6] =
+        ["鎖定", "停用", "次數過多", "locked", "disabled", "too many"];
+
+    messages
+        .iter()
+        .find(|message| {
+            let message = message.to_lowercase();
+            ACCOUNT_LOCKED_KEYWORDS
+                .iter()
+                .any(|keyword| message.contains(keyword))
+        })
+        .map(|message| Error::AccountLocked(message.clone()))
+        .unwrap_or(Error::InvalidCredentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_error_from_status_code() {
+        assert!(matches!(
+            login_error_from_status_code(419),
+            Error::CsrfMismatch
+        ));
+        assert!(matches!(
+            login_error_from_status_code(429),
+            Error::AccountLocked(_)
+        ));
+        assert!(matches!(
+            login_error_from_status_code(503),
+            Error::ServerError(503)
+        ));
+        assert!(matches!(
+            login_error_from_status_code(404),
+            Error::LoginError(404)
+        ));
+    }
+
+    #[test]
+    fn test_is_login_page_location() {
+        assert!(is_login_page_location("http://192.168.41.123:90/login"));
+        assert!(is_login_page_location("http://192.168.41.123:90/login/"));
+        assert!(is_login_page_location("/login?redirect=1"));
+        assert!(!is_login_page_location("http://192.168.41.123:90/home"));
+        assert!(!is_login_page_location("http://192.168.41.123:90/"));
+    }
+
+    #[test]
+    fn test_login_error_from_messages() {
+        let html = Html::parse_document(
+            r#"<form>
+                <input name="user_id" class="is-invalid">
+                <span class="invalid-feedback" role="alert">
+                    <strong>帳號或密碼錯誤</strong>
+                </span>
+            </form>"#,
+        );
+        let messages = parse_login_error_messages(&html);
+        assert_eq!(messages, vec!["帳號或密碼錯誤".to_string()]);
+        assert!(matches!(
+            login_error_from_messages(&messages),
+            Error::InvalidCredentials
+        ));
+
+        let html = Html::parse_document(
+            r#"<div class="alert alert-danger">
+                Too many login attempts. Please try again in 60 seconds.
+            </div>"#,
+        );
+        let messages = parse_login_error_messages(&html);
+        assert!(matches!(
+            login_error_from_messages(&messages),
+            Error::AccountLocked(message) if message.contains("60 seconds")
+        ));
+
+        assert!(matches!(
+            login_error_from_messages(&[]),
+            Error::InvalidCredentials
+        ));
+    }
+}
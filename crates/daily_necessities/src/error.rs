@@ -10,4 +10,16 @@ pub enum Error {
 
     #[error("Login failed with status code {0}")]
     LoginError(u16),
+
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+
+    #[error("Account locked: {0}")]
+    AccountLocked(String),
+
+    #[error("CSRF token mismatch")]
+    CsrfMismatch,
+
+    #[error("Server error with status code {0}")]
+    ServerError(u16),
 }
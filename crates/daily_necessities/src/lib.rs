@@ -1,7 +1,14 @@
 mod client;
 mod error;
+mod product;
 mod purchase_list;
+mod station;
+mod supplier;
 
+pub use chunked_fetch::{ChunkOptions, ChunkSize, Progress};
 pub use client::Client;
 pub use error::Error;
+pub use product::{Product, ProductCatalog};
 pub use purchase_list::{Purchase, PurchaseList};
+pub use station::{Station, StationList};
+pub use supplier::{Supplier, SupplierList};
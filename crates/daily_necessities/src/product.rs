@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct Product {
+    pub product_id: String,
+    #[serde(rename = "prname")]
+    pub product_name: String,
+    pub class: String,
+    pub price: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductCatalog {
+    data: Vec<Product>,
+}
+
+impl ProductCatalog {
+    pub fn iter(&self) -> impl Iterator<Item = &Product> + '_ {
+        self.data.iter()
+    }
+
+    pub fn get<R: AsRef<str>>(&self, product_id: R) -> Option<&Product> {
+        self.data
+            .iter()
+            .find(|product| product.product_id == product_id.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize_product_catalog() -> ProductCatalog {
+        let json = include_bytes!("../../../testdata/product-list.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    #[test]
+    fn test_product_catalog_get() {
+        let product_catalog = deserialize_product_catalog();
+        assert_eq!(product_catalog.iter().count(), 117);
+        assert_eq!(
+            product_catalog.get("A815"),
+            Some(&Product {
+                product_id: "A815".to_string(),
+                product_name: "散裝尿素水--諾瓦".to_string(),
+                class: "102".to_string(),
+                price: "9.0000".to_string(),
+            })
+        );
+        assert_eq!(product_catalog.get("Z999"), None);
+    }
+}
@@ -1,4 +1,8 @@
+use crate::product::{Product, ProductCatalog};
+use crate::station::{Station, StationList};
+use crate::supplier::{Supplier, SupplierList};
 use jiff::civil::Date;
+use roc_calendar::RocDate;
 use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer};
 use std::fmt::Formatter;
@@ -16,6 +20,7 @@ pub struct Purchase {
     pub product_name: String,
     pub class: String,
     pub sup_id: Option<String>,
+    #[serde(rename = "supname")]
     pub sup_name: Option<String>,
     pub rcpt: String,
     pub price: String,
@@ -31,6 +36,26 @@ pub struct Purchase {
     pub sep: String,
 }
 
+impl Purchase {
+    /// The receipt date, if `rcpt` holds a ROC date such as `1140821` or `114.09.01` rather than a
+    /// sequence number.
+    pub fn receipt_date(&self) -> Option<Date> {
+        self.rcpt.parse::<RocDate>().ok().map(|date| date.date())
+    }
+
+    pub fn product<'a>(&self, product_catalog: &'a ProductCatalog) -> Option<&'a Product> {
+        product_catalog.get(&self.product_id)
+    }
+
+    pub fn supplier<'a>(&self, supplier_list: &'a SupplierList) -> Option<&'a Supplier> {
+        supplier_list.get(self.sup_id.as_ref()?)
+    }
+
+    pub fn station<'a>(&self, station_list: &'a StationList) -> Option<&'a Station> {
+        station_list.get(&self.station_id)
+    }
+}
+
 fn deserialize_date<'de, D>(deserializer: D) -> Result<Date, D::Error>
 where
     D: Deserializer<'de>,
@@ -66,6 +91,24 @@ impl PurchaseList {
     pub fn iter(&self) -> impl Iterator<Item = &Purchase> + '_ {
         self.data.iter()
     }
+
+    pub fn merge<I: IntoIterator<Item = PurchaseList>>(purchase_lists: I) -> PurchaseList {
+        PurchaseList {
+            data: purchase_lists
+                .into_iter()
+                .flat_map(|purchase_list| purchase_list.data)
+                .collect(),
+        }
+    }
+
+    /// Fills in the supplier name of every purchase whose `supname` came back null.
+    pub fn fill_supplier_names(&mut self, supplier_list: &SupplierList) {
+        for purchase in self.data.iter_mut().filter(|p| p.sup_name.is_none()) {
+            purchase.sup_name = purchase
+                .supplier(supplier_list)
+                .map(|supplier| supplier.sup_name.clone());
+        }
+    }
 }
 
 #[cfg(test)]
@@ -124,4 +167,52 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn test_purchase_receipt_date() {
+        let purchase_list = deserialize_purchase_list();
+        let mut iter = purchase_list.iter();
+        assert_eq!(
+            iter.next().unwrap().receipt_date(),
+            Some(Date::new(2025, 8, 21).unwrap())
+        );
+        assert_eq!(iter.next().unwrap().receipt_date(), None);
+        assert!(
+            purchase_list
+                .iter()
+                .filter(|purchase| purchase.rcpt == "114.09.01")
+                .all(|purchase| purchase.receipt_date() == Some(Date::new(2025, 9, 1).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_purchase_list_merge() {
+        let purchase_list =
+            PurchaseList::merge([deserialize_purchase_list(), deserialize_purchase_list()]);
+        assert_eq!(purchase_list.iter().count(), 1436);
+    }
+
+    #[test]
+    fn test_purchase_list_fill_supplier_names() {
+        let json = include_bytes!("../../../testdata/supplier-list.json");
+        let supplier_list: SupplierList = serde_json::from_slice(json).unwrap();
+        let mut purchase_list = deserialize_purchase_list();
+        let sup_names = purchase_list
+            .iter()
+            .map(|purchase| purchase.sup_name.clone())
+            .collect::<Vec<_>>();
+        assert!(sup_names.iter().any(|sup_name| sup_name.is_some()));
+
+        for purchase in purchase_list.data.iter_mut() {
+            purchase.sup_name = None;
+        }
+        purchase_list.fill_supplier_names(&supplier_list);
+        assert_eq!(
+            purchase_list
+                .iter()
+                .map(|purchase| purchase.sup_name.clone())
+                .collect::<Vec<_>>(),
+            sup_names
+        );
+    }
 }
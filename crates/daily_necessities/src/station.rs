@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct Station {
+    #[serde(rename = "step_id")]
+    pub station_id: String,
+    #[serde(rename = "stepname")]
+    pub station_name: String,
+    #[serde(rename = "GROUP")]
+    pub group: String,
+    #[serde(rename = "AREA")]
+    pub area: String,
+    pub dep: String,
+    pub sep: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StationList {
+    data: Vec<Station>,
+}
+
+impl StationList {
+    pub fn iter(&self) -> impl Iterator<Item = &Station> + '_ {
+        self.data.iter()
+    }
+
+    pub fn get<R: AsRef<str>>(&self, station_id: R) -> Option<&Station> {
+        self.data
+            .iter()
+            .find(|station| station.station_id == station_id.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize_station_list() -> StationList {
+        let json = include_bytes!("../../../testdata/station-list.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    #[test]
+    fn test_station_list_get() {
+        let station_list = deserialize_station_list();
+        assert_eq!(station_list.iter().count(), 69);
+        assert_eq!(
+            station_list.get("SSC58"),
+            Some(&Station {
+                station_id: "SSC58".to_string(),
+                station_name: "南州".to_string(),
+                group: "南".to_string(),
+                area: "6".to_string(),
+                dep: "13".to_string(),
+                sep: "64".to_string(),
+            })
+        );
+        assert_eq!(station_list.get("XXX00"), None);
+    }
+}
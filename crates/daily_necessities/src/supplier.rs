@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct Supplier {
+    pub sup_id: String,
+    #[serde(rename = "supname")]
+    pub sup_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SupplierList {
+    data: Vec<Supplier>,
+}
+
+impl SupplierList {
+    pub fn iter(&self) -> impl Iterator<Item = &Supplier> + '_ {
+        self.data.iter()
+    }
+
+    pub fn get<R: AsRef<str>>(&self, sup_id: R) -> Option<&Supplier> {
+        self.data
+            .iter()
+            .find(|supplier| supplier.sup_id == sup_id.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize_supplier_list() -> SupplierList {
+        let json = include_bytes!("../../../testdata/supplier-list.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    #[test]
+    fn test_supplier_list_get() {
+        let supplier_list = deserialize_supplier_list();
+        assert_eq!(supplier_list.iter().count(), 10);
+        assert_eq!(
+            supplier_list.get("10"),
+            Some(&Supplier {
+                sup_id: "10".to_string(),
+                sup_name: "標案大面".to_string(),
+            })
+        );
+        assert_eq!(supplier_list.get("99"), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    Error, Freebie, OrderNumber, StationQuantity, generate_purchase_order_report,
+    station_quantities,
+};
+use jiff::civil::Date;
+use log::{info, warn};
+use roc_calendar::WorkingDayCalendar;
+use tscred::ItemNeeds;
+use umya_spreadsheet::Spreadsheet;
+
+/// A weekly order to generate in a batch.
+pub struct BatchOrder<'a> {
+    pub freebie: Freebie,
+    pub template: &'a Spreadsheet,
+    pub order_number: OrderNumber,
+}
+
+/// What became of a weekly order generated in a batch.
+pub enum BatchOutcome {
+    Generated {
+        spreadsheet: Box<Spreadsheet>,
+        stations: Vec<StationQuantity>,
+    },
+    /// No station requested the freebie, so there is nothing to order.
+    ZeroQuantity,
+    /// The order could not be generated, such as when the freebie is missing in the item needs.
+    Skipped(Error),
+}
+
+pub struct BatchReport {
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub outcome: BatchOutcome,
+}
+
+impl BatchReport {
+    pub fn total_quantity(&self) -> u64 {
+        match &self.outcome {
+            BatchOutcome::Generated { stations, .. } => stations
+                .iter()
+                .map(|station_quantity| station_quantity.quantity)
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+/// Generates the weekly orders of several freebies from the same item needs, so they only have to
+/// be fetched once.
+pub fn generate_batch_purchase_order_reports(
+    orders: &[BatchOrder],
+    item_needs_slice: &[ItemNeeds],
+    notification_date: &Date,
+    calendar: &WorkingDayCalendar,
+) -> Vec<BatchReport> {
+    orders
+        .iter()
+        .map(|order| {
+            let outcome = match station_quantities(item_needs_slice, &order.freebie) {
+                Ok(stations) if stations.iter().all(|station| station.quantity == 0) => {
+                    BatchOutcome::ZeroQuantity
+                }
+                Ok(stations) => match generate_purchase_order_report(
+                    order.template,
+                    item_needs_slice,
+                    &order.freebie,
+                    notification_date,
+                    order.order_number.to_string(),
+                    calendar,
+                    &[],
+                ) {
+                    Ok(spreadsheet) => BatchOutcome::Generated {
+                        spreadsheet: Box::new(spreadsheet),
+                        stations,
+                    },
+                    Err(error) => BatchOutcome::Skipped(error),
+                },
+                Err(error) => BatchOutcome::Skipped(error),
+            };
+            match &outcome {
+                BatchOutcome::Generated { .. } => {}
+                BatchOutcome::ZeroQuantity => {
+                    info!(freebie = order.freebie.short_name(); "no station requested the freebie")
+                }
+                BatchOutcome::Skipped(error) => {
+                    warn!(freebie = order.freebie.short_name(), error:%; "skipped the freebie")
+                }
+            }
+            BatchReport {
+                freebie: order.freebie,
+                order_number: order.order_number,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// Puts the generated weekly orders of a batch into one workbook, with a sheet per freebie.
+pub fn combine_batch_reports(reports: &[BatchReport]) -> Spreadsheet {
+    let mut combined = umya_spreadsheet::new_file_empty_worksheet();
+    for report in reports {
+        if let BatchOutcome::Generated { spreadsheet, .. } = &report.outcome
+            && let Some(worksheet) = spreadsheet.get_sheet(&0)
+        {
+            let mut worksheet = worksheet.clone();
+            worksheet.set_name(report.freebie.short_name());
+            combined.add_sheet(worksheet).expect("unable to add sheet");
+        }
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use umya_spreadsheet::reader;
+
+    #[test]
+    fn test_generate_batch_purchase_order_reports() {
+        let json = include_bytes!("../../../testdata/generate-purchase-order-report.json");
+        let item_needs: ItemNeeds = serde_json::from_slice(json).unwrap();
+        let tissue_60 = reader::xlsx::read_reader(
+            Cursor::new(include_bytes!(
+                "../../../assets/templates/60抽面紙每週訂購單.xlsx"
+            )),
+            true,
+        )
+        .unwrap();
+        let mineral_water = reader::xlsx::read_reader(
+            Cursor::new(include_bytes!(
+                "../../../assets/templates/礦泉水每週訂購單.xlsx"
+            )),
+            true,
+        )
+        .unwrap();
+        let orders = [
+            BatchOrder {
+                freebie: Freebie::Tissue60,
+                template: &tissue_60,
+                order_number: "10-3".parse().unwrap(),
+            },
+            BatchOrder {
+                freebie: Freebie::MineralWater,
+                template: &mineral_water,
+                order_number: "10-2".parse().unwrap(),
+            },
+        ];
+
+        let reports = generate_batch_purchase_order_reports(
+            &orders,
+            &[item_needs],
+            &Date::new(2025, 10, 21).unwrap(),
+            &WorkingDayCalendar::bundled(),
+        );
+        assert_eq!(reports[0].total_quantity(), 1240);
+        assert_eq!(reports[1].total_quantity(), 180);
+
+        let combined = combine_batch_reports(&reports);
+        let sheet_names = combined
+            .get_sheet_collection()
+            .iter()
+            .map(|worksheet| worksheet.get_name())
+            .collect::<Vec<_>>();
+        assert_eq!(sheet_names, vec!["60抽面紙", "礦泉水"]);
+        assert_eq!(combined.get_sheet(&1).unwrap().get_value("F2"), "南訂10-2");
+
+        // Freebies missing in the item needs are skipped
+        let reports = generate_batch_purchase_order_reports(
+            &orders,
+            &[],
+            &Date::new(2025, 10, 21).unwrap(),
+            &WorkingDayCalendar::bundled(),
+        );
+        assert!(matches!(
+            reports[0].outcome,
+            BatchOutcome::Skipped(Error::FreebieNotFound)
+        ));
+    }
+}
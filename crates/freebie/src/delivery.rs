@@ -0,0 +1,129 @@
+use jiff::civil::{Date, Weekday};
+use roc_calendar::WorkingDayCalendar;
+
+/// How soon a vendor delivers after being notified of an order.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LeadTime {
+    /// Delivered within the given number of working days after the notification date.
+    WorkingDays(u32),
+    /// Delivered on the first working day of the given weekday after the notification date.
+    Weekday(Weekday),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Vendor {
+    pub name: &'static str,
+    pub lead_time: LeadTime,
+}
+
+impl Vendor {
+    /// Returns the proposed delivery date (擬交貨日期) of an order, which is the earliest date
+    /// allowed by the lead time, unless the station requested a later date.
+    pub fn proposed_delivery_date(
+        &self,
+        calendar: &WorkingDayCalendar,
+        notification_date: &Date,
+        order_date: &Date,
+    ) -> Date {
+        let earliest = match self.lead_time {
+            LeadTime::WorkingDays(n) => calendar.add_working_days(notification_date, n),
+            LeadTime::Weekday(weekday) => {
+                let mut date = notification_date.tomorrow().expect("date out of range");
+                while date.weekday() != weekday || !calendar.is_working_day(&date) {
+                    date = date.tomorrow().expect("date out of range");
+                }
+                date
+            }
+        };
+
+        let requested = calendar.next_working_day_from(order_date);
+        earliest.max(requested)
+    }
+}
+
+/// The reasons boxes of a delivery are rejected, as counted in the 不合格品項 columns of the monthly
+/// delivery statistics.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum DefectKind {
+    Damaged,
+    Stained,
+    Damp,
+    CartonDamaged,
+    WaterQuality,
+    DentedBottle,
+    BrokenBottle,
+}
+
+impl DefectKind {
+    pub fn all() -> [DefectKind; 7] {
+        [
+            DefectKind::Damaged,
+            DefectKind::Stained,
+            DefectKind::Damp,
+            DefectKind::CartonDamaged,
+            DefectKind::WaterQuality,
+            DefectKind::DentedBottle,
+            DefectKind::BrokenBottle,
+        ]
+    }
+
+    pub fn from_label(label: &str) -> Option<DefectKind> {
+        DefectKind::all()
+            .into_iter()
+            .find(|kind| kind.label() == label)
+    }
+
+    /// The header of the defect column, such as `破損`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DefectKind::Damaged => "破損",
+            DefectKind::Stained => "汙染",
+            DefectKind::Damp => "潮濕",
+            DefectKind::CartonDamaged => "紙箱破損",
+            DefectKind::WaterQuality => "水質",
+            DefectKind::DentedBottle => "凹瓶",
+            DefectKind::BrokenBottle => "破瓶",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposed_delivery_date() {
+        let calendar = WorkingDayCalendar::bundled();
+        let notification_date = Date::new(2025, 10, 7).unwrap();
+        let order_date = Date::new(2025, 9, 30).unwrap();
+
+        let vendor = Vendor {
+            name: "正隆公司",
+            lead_time: LeadTime::WorkingDays(5),
+        };
+        assert_eq!(
+            vendor.proposed_delivery_date(&calendar, &notification_date, &order_date),
+            Date::new(2025, 10, 15).unwrap()
+        );
+
+        // The station requested a later date, which falls on a Saturday
+        assert_eq!(
+            vendor.proposed_delivery_date(
+                &calendar,
+                &notification_date,
+                &Date::new(2025, 10, 18).unwrap()
+            ),
+            Date::new(2025, 10, 20).unwrap()
+        );
+
+        // The Friday after the notification date is National Day
+        let vendor = Vendor {
+            name: "台糖公司",
+            lead_time: LeadTime::Weekday(Weekday::Friday),
+        };
+        assert_eq!(
+            vendor.proposed_delivery_date(&calendar, &notification_date, &order_date),
+            Date::new(2025, 10, 17).unwrap()
+        );
+    }
+}
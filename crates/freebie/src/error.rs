@@ -8,6 +8,24 @@ pub enum Error {
     #[error("unable to find freebie in item needs")]
     FreebieNotFound,
 
+    #[error("unable to find station in item needs: {0}")]
+    StationNotFound(String),
+
     #[error("provided item needs are empty")]
     ItemNeedsEmpty,
+
+    #[error("invalid order number: {0}")]
+    InvalidOrderNumber(String),
+
+    #[error("order number already used: {0}")]
+    DuplicateOrderNumber(String),
+
+    #[error("sheet already exists: {0}")]
+    DuplicateSheet(String),
+
+    #[error("missing order worksheet")]
+    MissingOrderWorksheet,
+
+    #[error(transparent)]
+    RocDateError(#[from] roc_calendar::Error),
 }
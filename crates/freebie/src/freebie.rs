@@ -1,6 +1,8 @@
+use crate::delivery::{DefectKind, LeadTime, Vendor};
+use jiff::civil::Weekday;
 use umya_spreadsheet::helper::coordinate::CellCoordinates;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Freebie {
     Tissue60,
     Tissue110,
@@ -12,6 +14,12 @@ impl Freebie {
         [Freebie::Tissue60, Freebie::Tissue110, Freebie::MineralWater]
     }
 
+    pub fn from_name(name: &str) -> Option<Freebie> {
+        Freebie::all()
+            .into_iter()
+            .find(|freebie| freebie.name() == name)
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Freebie::Tissue60 => "60抽盒裝面紙",
@@ -20,6 +28,48 @@ impl Freebie {
         }
     }
 
+    /// The short name used by the tabs, sheets and file names, such as `60抽面紙`.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Freebie::Tissue60 => "60抽面紙",
+            Freebie::Tissue110 => "110抽面紙",
+            Freebie::MineralWater => "礦泉水",
+        }
+    }
+
+    /// The vendor supplying the freebie and its lead time.
+    pub fn vendor(&self) -> Vendor {
+        match self {
+            Freebie::Tissue60 => Vendor {
+                name: "正隆公司",
+                lead_time: LeadTime::WorkingDays(5),
+            },
+            Freebie::Tissue110 => Vendor {
+                name: "欣達紙業",
+                lead_time: LeadTime::WorkingDays(7),
+            },
+            Freebie::MineralWater => Vendor {
+                name: "台糖公司",
+                lead_time: LeadTime::Weekday(Weekday::Thursday),
+            },
+        }
+    }
+
+    /// The defects the deliveries of the freebie are inspected for.
+    pub fn defect_kinds(&self) -> &'static [DefectKind] {
+        match self {
+            Freebie::Tissue60 | Freebie::Tissue110 => {
+                &[DefectKind::Damaged, DefectKind::Stained, DefectKind::Damp]
+            }
+            Freebie::MineralWater => &[
+                DefectKind::CartonDamaged,
+                DefectKind::WaterQuality,
+                DefectKind::DentedBottle,
+                DefectKind::BrokenBottle,
+            ],
+        }
+    }
+
     pub fn notification_date_coord(&self) -> impl Into<CellCoordinates> {
         match self {
             Freebie::Tissue60 => "E2",
@@ -36,11 +86,32 @@ impl Freebie {
         }
     }
 
+    /// The column of the proposed delivery date (擬交貨日期) of each station.
+    pub fn delivery_date_column(&self) -> &'static str {
+        "B"
+    }
+
+    /// The column of the remark (備註) of each station.
+    pub fn remark_column(&self) -> &'static str {
+        "F"
+    }
+
+    /// The prefix of the order numbers issued to the vendor, such as `南訂10-2`.
+    pub fn order_number_prefix(&self) -> &'static str {
+        match self {
+            Freebie::Tissue60 => "",
+            Freebie::Tissue110 => "",
+            Freebie::MineralWater => "南訂",
+        }
+    }
+
     pub fn order_number_cell_value<R: AsRef<str>>(&self, order_number: R) -> String {
         match self {
             Freebie::Tissue60 => format!("訂單編號：{}", order_number.as_ref()),
             Freebie::Tissue110 => format!("訂單編號：{}", order_number.as_ref()),
-            Freebie::MineralWater => format!("南訂{}", order_number.as_ref()),
+            Freebie::MineralWater => {
+                format!("{}{}", self.order_number_prefix(), order_number.as_ref())
+            }
         }
     }
 }
@@ -1,7 +1,25 @@
+mod batch;
+mod delivery;
 mod error;
 mod freebie;
+mod order_number;
 mod purchase_order;
+mod weekly_volume;
+mod yearly_workbook;
 
+pub use crate::batch::{
+    BatchOrder, BatchOutcome, BatchReport, combine_batch_reports,
+    generate_batch_purchase_order_reports,
+};
+pub use crate::delivery::{DefectKind, LeadTime, Vendor};
 pub use crate::error::Error;
 pub use crate::freebie::Freebie;
-pub use crate::purchase_order::generate_purchase_order_report;
+pub use crate::order_number::{IssuedOrderNumber, OrderNumber, OrderNumberRegistry};
+pub use crate::purchase_order::{
+    QuantityAdjustment, QuantityOverride, StationQuantity, adjusted_station_quantities,
+    generate_purchase_order_report, station_quantities,
+};
+pub use crate::weekly_volume::WeeklyVolume;
+pub use crate::yearly_workbook::{
+    SUMMARY_SHEET_NAME, append_purchase_order, refresh_summary_sheet,
+};
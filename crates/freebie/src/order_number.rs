@@ -0,0 +1,324 @@
+use crate::{Error, Freebie};
+use jiff::civil::Date;
+use roc_calendar::RocDate;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The labels and prefixes written in front of the order numbers on the order forms.
+const ORDER_NUMBER_PREFIXES: [&str; 3] = ["訂單編號：", "訂單編號:", "南訂"];
+
+/// The weekly order number `{month}-{sequence}`, such as `10-3` for the third order in October.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct OrderNumber {
+    month: i8,
+    sequence: u32,
+}
+
+impl OrderNumber {
+    pub fn new(month: i8, sequence: u32) -> Result<Self, Error> {
+        if !(1..=12).contains(&month) || sequence == 0 {
+            return Err(Error::InvalidOrderNumber(format!("{}-{}", month, sequence)));
+        }
+        Ok(OrderNumber { month, sequence })
+    }
+
+    pub fn month(&self) -> i8 {
+        self.month
+    }
+
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+impl FromStr for OrderNumber {
+    type Err = Error;
+
+    /// Parses an order number with or without a prefix, such as `10-2` or `南訂10-2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut number = s.trim();
+        for prefix in ORDER_NUMBER_PREFIXES {
+            number = number.strip_prefix(prefix).unwrap_or(number).trim_start();
+        }
+
+        let (month, sequence) = number
+            .split_once('-')
+            .ok_or_else(|| Error::InvalidOrderNumber(s.to_string()))?;
+        Self::new(
+            month
+                .parse()
+                .map_err(|_| Error::InvalidOrderNumber(s.to_string()))?,
+            sequence
+                .parse()
+                .map_err(|_| Error::InvalidOrderNumber(s.to_string()))?,
+        )
+    }
+}
+
+impl Display for OrderNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.month, self.sequence)
+    }
+}
+
+/// An order number which was used on an order form.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IssuedOrderNumber {
+    pub freebie: Freebie,
+    pub notification_date: Date,
+    pub order_number: OrderNumber,
+}
+
+impl IssuedOrderNumber {
+    fn is_same_order_number(&self, other: &IssuedOrderNumber) -> bool {
+        self.freebie == other.freebie
+            && self.order_number == other.order_number
+            && self.notification_date.year() == other.notification_date.year()
+    }
+}
+
+impl FromStr for IssuedOrderNumber {
+    type Err = Error;
+
+    /// Parses a line formatted as `114/10/21,60抽盒裝面紙,10-3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split(',').map(str::trim).collect::<Vec<_>>();
+        let [notification_date, freebie, order_number] = fields[..] else {
+            return Err(Error::InvalidOrderNumber(s.to_string()));
+        };
+
+        Ok(IssuedOrderNumber {
+            freebie: Freebie::from_name(freebie)
+                .ok_or_else(|| Error::InvalidOrderNumber(s.to_string()))?,
+            notification_date: notification_date.parse::<RocDate>()?.date(),
+            order_number: order_number.parse()?,
+        })
+    }
+}
+
+impl Display for IssuedOrderNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match RocDate::try_from(self.notification_date) {
+            Ok(date) => write!(f, "{},{},{}", date, self.freebie.name(), self.order_number),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+/// The order numbers used so far, which issues the next order number of each freebie per month.
+#[derive(Clone, Debug, Default)]
+pub struct OrderNumberRegistry {
+    issued: Vec<IssuedOrderNumber>,
+}
+
+impl OrderNumberRegistry {
+    pub fn iter(&self) -> impl Iterator<Item = &IssuedOrderNumber> + '_ {
+        self.issued.iter()
+    }
+
+    /// Returns the order number following the last one of the freebie in the month of the
+    /// notification date.
+    pub fn next(&self, freebie: &Freebie, notification_date: &Date) -> OrderNumber {
+        let month = notification_date.month();
+        let sequence = self
+            .issued
+            .iter()
+            .filter(|issued| {
+                issued.freebie == *freebie
+                    && issued.order_number.month == month
+                    && issued.notification_date.year() == notification_date.year()
+            })
+            .map(|issued| issued.order_number.sequence)
+            .max()
+            .unwrap_or_default();
+        OrderNumber {
+            month,
+            sequence: sequence + 1,
+        }
+    }
+
+    /// Finds the earlier use of the order number of the freebie within the year of the
+    /// notification date.
+    pub fn find(
+        &self,
+        freebie: &Freebie,
+        notification_date: &Date,
+        order_number: &OrderNumber,
+    ) -> Option<&IssuedOrderNumber> {
+        let issued = IssuedOrderNumber {
+            freebie: *freebie,
+            notification_date: *notification_date,
+            order_number: *order_number,
+        };
+        self.issued
+            .iter()
+            .find(|other| other.is_same_order_number(&issued))
+    }
+
+    /// Records the order number, refusing the ones which were used before or whose notification
+    /// date cannot be written as an ROC date.
+    pub fn issue(&mut self, issued: IssuedOrderNumber) -> Result<(), Error> {
+        RocDate::try_from(issued.notification_date)?;
+        if self
+            .find(
+                &issued.freebie,
+                &issued.notification_date,
+                &issued.order_number,
+            )
+            .is_some()
+        {
+            return Err(Error::DuplicateOrderNumber(issued.to_string()));
+        }
+        self.issued.push(issued);
+        Ok(())
+    }
+
+    /// Records the order number even if it was used before, refusing the ones whose notification
+    /// date cannot be written as an ROC date.
+    pub fn record(&mut self, issued: IssuedOrderNumber) -> Result<(), Error> {
+        RocDate::try_from(issued.notification_date)?;
+        self.issued.push(issued);
+        Ok(())
+    }
+
+    /// Returns the order numbers used more than once, grouped by order number.
+    pub fn duplicates(&self) -> Vec<Vec<&IssuedOrderNumber>> {
+        let mut groups: Vec<Vec<&IssuedOrderNumber>> = vec![];
+        for issued in self.issued.iter() {
+            match groups
+                .iter_mut()
+                .find(|group| group[0].is_same_order_number(issued))
+            {
+                Some(group) => group.push(issued),
+                None => groups.push(vec![issued]),
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+}
+
+impl FromStr for OrderNumberRegistry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let issued = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(OrderNumberRegistry { issued })
+    }
+}
+
+impl Display for OrderNumberRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for issued in self.issued.iter() {
+            writeln!(f, "{}", issued)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_order_number() {
+        let expected = OrderNumber::new(10, 2).unwrap();
+        for s in [
+            "10-2",
+            " 10-2 ",
+            "南訂10-2",
+            "訂單編號：10-2",
+            "訂單編號: 10-2",
+        ] {
+            assert_eq!(s.parse::<OrderNumber>().unwrap(), expected, "{}", s);
+        }
+        assert_eq!(expected.to_string(), "10-2");
+
+        for s in ["", "10", "13-1", "10-0", "10-a", "十-2"] {
+            assert!(s.parse::<OrderNumber>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_order_number_registry() {
+        let mut registry = "\
+            114/10/07,60抽盒裝面紙,10-1\n\
+            114/10/14,60抽盒裝面紙,10-2\n\
+            114/10/14,台糖礦泉水/箱,10-1\n"
+            .parse::<OrderNumberRegistry>()
+            .unwrap();
+
+        let notification_date = Date::new(2025, 10, 21).unwrap();
+        let order_number = registry.next(&Freebie::Tissue60, &notification_date);
+        assert_eq!(order_number.to_string(), "10-3");
+        assert_eq!(
+            registry
+                .next(&Freebie::MineralWater, &notification_date)
+                .to_string(),
+            "10-2"
+        );
+        assert_eq!(
+            registry
+                .next(&Freebie::Tissue110, &notification_date)
+                .to_string(),
+            "10-1"
+        );
+        assert_eq!(
+            registry
+                .next(&Freebie::Tissue60, &Date::new(2025, 11, 4).unwrap())
+                .to_string(),
+            "11-1"
+        );
+
+        registry
+            .issue(IssuedOrderNumber {
+                freebie: Freebie::Tissue60,
+                notification_date,
+                order_number,
+            })
+            .unwrap();
+        assert!(registry.duplicates().is_empty());
+
+        // The same number of another year is not a duplicate
+        let duplicate = IssuedOrderNumber {
+            freebie: Freebie::Tissue60,
+            notification_date,
+            order_number: "10-2".parse().unwrap(),
+        };
+        assert_eq!(
+            registry
+                .find(
+                    &Freebie::Tissue60,
+                    &Date::new(2024, 10, 15).unwrap(),
+                    &duplicate.order_number
+                )
+                .map(|issued| issued.notification_date),
+            None
+        );
+        assert!(registry.issue(duplicate.clone()).is_err());
+
+        registry.record(duplicate).unwrap();
+        let duplicates = registry.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0][0].to_string(), "114/10/14,60抽盒裝面紙,10-2");
+
+        let s = registry.to_string();
+        assert_eq!(s.lines().count(), 5);
+        assert_eq!(s.parse::<OrderNumberRegistry>().unwrap().iter().count(), 5);
+
+        // Dates before the ROC era are refused rather than left unable to be saved
+        let before_roc_era = IssuedOrderNumber {
+            freebie: Freebie::Tissue60,
+            notification_date: Date::new(1900, 10, 14).unwrap(),
+            order_number: "10-1".parse().unwrap(),
+        };
+        assert!(registry.issue(before_roc_era.clone()).is_err());
+        assert!(registry.record(before_roc_era).is_err());
+        assert_eq!(registry.iter().count(), 5);
+    }
+}
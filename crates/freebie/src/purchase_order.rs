@@ -1,15 +1,128 @@
 use crate::{Error, Freebie};
 use jiff::civil::Date;
+use log::{info, warn};
+use roc_calendar::{RocDate, WorkingDayCalendar};
 use std::collections::HashMap;
 use tscred::ItemNeeds;
 use umya_spreadsheet::Spreadsheet;
 
+/// The quantity of a freebie requested by a station.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StationQuantity {
+    pub station_name: String,
+    /// The latest date the station requested the freebie for.
+    pub order_date: Date,
+    pub quantity: u64,
+    /// The manual change of the quantity, if the user overrode it.
+    pub adjustment: Option<QuantityAdjustment>,
+}
+
+/// The record of a quantity changed by the user before saving the order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantityAdjustment {
+    /// The quantity the station requested in the item needs.
+    pub original_quantity: u64,
+    pub reason: String,
+}
+
+/// The quantity of a station set by the user, overriding the one in the item needs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantityOverride {
+    pub station_name: String,
+    pub quantity: u64,
+    pub reason: String,
+}
+
+/// Sums the quantities of the freebie requested by each station, in the order the stations first
+/// appear in the item needs.
+pub fn station_quantities(
+    item_needs_slice: &[ItemNeeds],
+    freebie: &Freebie,
+) -> Result<Vec<StationQuantity>, Error> {
+    // Get the freebie ID
+    let id = item_needs_slice
+        .iter()
+        .find_map(|item_needs| {
+            item_needs
+                .get_all_items()
+                .into_iter()
+                .find(|item| item.title == freebie.name())
+                .map(|item| item.id)
+        })
+        .ok_or(Error::FreebieNotFound)?;
+
+    let mut station_quantities: Vec<StationQuantity> = vec![];
+    for item_needs in item_needs_slice {
+        for item_need in item_needs.iter() {
+            let Some(count) = item_need.items_count.get(id.as_str()) else {
+                continue;
+            };
+            match station_quantities
+                .iter_mut()
+                .find(|station_quantity| station_quantity.station_name == item_need.station_name)
+            {
+                Some(station_quantity) => {
+                    station_quantity.quantity += count;
+                    if *count > 0 {
+                        station_quantity.order_date =
+                            station_quantity.order_date.max(item_need.order_date);
+                    }
+                }
+                None => station_quantities.push(StationQuantity {
+                    station_name: item_need.station_name.to_string(),
+                    order_date: item_need.order_date,
+                    quantity: *count,
+                    adjustment: None,
+                }),
+            }
+        }
+    }
+
+    Ok(station_quantities)
+}
+
+/// Sums the quantities of the freebie requested by each station like `station_quantities`, then
+/// applies the overrides of the user, keeping the original quantities and the reasons.
+pub fn adjusted_station_quantities(
+    item_needs_slice: &[ItemNeeds],
+    freebie: &Freebie,
+    overrides: &[QuantityOverride],
+) -> Result<Vec<StationQuantity>, Error> {
+    let mut station_quantities = station_quantities(item_needs_slice, freebie)?;
+    for quantity_override in overrides {
+        let station_quantity = station_quantities
+            .iter_mut()
+            .find(|station_quantity| {
+                station_quantity.station_name == quantity_override.station_name
+            })
+            .ok_or_else(|| Error::StationNotFound(quantity_override.station_name.clone()))?;
+        if station_quantity.quantity == quantity_override.quantity {
+            continue;
+        }
+        let original_quantity = station_quantity
+            .adjustment
+            .as_ref()
+            .map_or(station_quantity.quantity, |adjustment| {
+                adjustment.original_quantity
+            });
+        station_quantity.quantity = quantity_override.quantity;
+        station_quantity.adjustment = Some(QuantityAdjustment {
+            original_quantity,
+            reason: quantity_override.reason.clone(),
+        });
+    }
+
+    Ok(station_quantities)
+}
+
 pub fn generate_purchase_order_report<R: AsRef<str>>(
     template: &Spreadsheet,
     item_needs_slice: &[ItemNeeds],
     freebie: &Freebie,
     notification_date: &Date,
     order_number: R,
+    calendar: &WorkingDayCalendar,
+    overrides: &[QuantityOverride],
 ) -> Result<Spreadsheet, Error> {
     let mut spreadsheet = umya_spreadsheet::new_file_empty_worksheet();
     let worksheet = template
@@ -22,51 +135,77 @@ pub fn generate_purchase_order_report<R: AsRef<str>>(
     let worksheet = spreadsheet.get_sheet_mut(&0).unwrap();
 
     // Set the notification date
-    let notification_date_string = format!(
-        "{}/{:02}/{:02}",
-        notification_date.year() - 1911,
-        notification_date.month(),
-        notification_date.day()
-    );
     worksheet
         .get_cell_mut(freebie.notification_date_coord())
-        .set_value(notification_date_string);
+        .set_value(RocDate::try_from(*notification_date)?.to_string());
 
     // Set the order number
+    let order_number = order_number.as_ref();
     worksheet
         .get_cell_mut(freebie.order_number_coord())
         .set_value(freebie.order_number_cell_value(order_number));
 
-    // Get the freebie ID
-    let id = item_needs_slice
-        .iter()
-        .find_map(|item_needs| {
-            item_needs
-                .get_all_items()
-                .into_iter()
-                .find(|item| item.title == freebie.name())
-                .map(|item| item.id)
-        })
-        .ok_or(Error::FreebieNotFound)?;
-
-    // Set the item needs
+    // Set the item needs, the proposed delivery dates and the reasons of the adjusted quantities
+    let vendor = freebie.vendor();
     let stations = worksheet
         .get_cell_value_by_range("A5:A25")
         .into_iter()
         .enumerate()
         .map(|(i, value)| (value.get_value(), i + 5))
         .collect::<HashMap<_, _>>();
-    for item_needs in item_needs_slice {
-        for item_need in item_needs.iter() {
-            if let Some(count) = item_need.items_count.get(id.as_str())
-                && let Some(cord) = stations.get(item_need.station_name)
-            {
-                worksheet
-                    .get_cell_mut(format!("C{}", cord))
-                    .set_value_number(*count as f64);
+    let (mut station_count, mut cases) = (0, 0);
+    let mut beyond_calendar = !calendar.covers(notification_date);
+    for station_quantity in adjusted_station_quantities(item_needs_slice, freebie, overrides)? {
+        let Some(cord) = stations.get(station_quantity.station_name.as_str()) else {
+            if station_quantity.quantity > 0 {
+                warn!(
+                    freebie = freebie.short_name(),
+                    station = station_quantity.station_name.as_str(),
+                    quantity = station_quantity.quantity;
+                    "station is not in the template"
+                );
             }
+            continue;
+        };
+        station_count += 1;
+        cases += station_quantity.quantity;
+        worksheet
+            .get_cell_mut(format!("C{}", cord))
+            .set_value_number(station_quantity.quantity as f64);
+        if station_quantity.quantity > 0 {
+            let delivery_date = vendor.proposed_delivery_date(
+                calendar,
+                notification_date,
+                &station_quantity.order_date,
+            );
+            beyond_calendar |= !calendar.covers(&delivery_date);
+            worksheet
+                .get_cell_mut(format!("{}{}", freebie.delivery_date_column(), cord))
+                .set_value(RocDate::try_from(delivery_date)?.to_string());
         }
+        if let Some(adjustment) = &station_quantity.adjustment {
+            worksheet
+                .get_cell_mut(format!("{}{}", freebie.remark_column(), cord))
+                .set_value(format!(
+                    "{}（原 {} 箱）",
+                    adjustment.reason, adjustment.original_quantity
+                ));
+        }
+    }
+    if beyond_calendar {
+        warn!(
+            freebie = freebie.short_name(),
+            order_number;
+            "proposed delivery dates are counted without the holidays of a year not bundled"
+        );
     }
+    info!(
+        freebie = freebie.short_name(),
+        order_number,
+        stations = station_count,
+        cases;
+        "generated purchase order report"
+    );
 
     Ok(spreadsheet)
 }
@@ -74,8 +213,12 @@ pub fn generate_purchase_order_report<R: AsRef<str>>(
 #[cfg(test)]
 mod tests {
     use crate::freebie::Freebie;
-    use crate::purchase_order::generate_purchase_order_report;
+    use crate::purchase_order::{
+        QuantityOverride, adjusted_station_quantities, generate_purchase_order_report,
+        station_quantities,
+    };
     use jiff::civil::Date;
+    use roc_calendar::WorkingDayCalendar;
     use std::io::Cursor;
     use tscred::ItemNeeds;
     use umya_spreadsheet::reader;
@@ -86,6 +229,112 @@ mod tests {
             .expect("Failed to deserialize generate-purchase-order-report.json")
     }
 
+    #[test]
+    fn test_station_quantities() {
+        let item_needs = deserialize_item_needs();
+        let quantities = station_quantities(&[item_needs], &Freebie::Tissue60).unwrap();
+        let station_quantity = quantities
+            .iter()
+            .find(|station_quantity| station_quantity.station_name == "新進站")
+            .unwrap();
+        assert_eq!(station_quantity.quantity, 990);
+        assert_eq!(station_quantity.order_date, Date::new(2025, 9, 15).unwrap());
+
+        // The same stations requested again in another query are summed up
+        let item_needs_slice = [deserialize_item_needs(), deserialize_item_needs()];
+        let doubled_quantities = station_quantities(&item_needs_slice, &Freebie::Tissue60).unwrap();
+        assert_eq!(doubled_quantities.len(), quantities.len());
+        assert!(
+            doubled_quantities
+                .iter()
+                .zip(quantities.iter())
+                .all(|(doubled, single)| doubled.quantity == single.quantity * 2)
+        );
+    }
+
+    #[test]
+    fn test_adjusted_station_quantities() {
+        let item_needs = [deserialize_item_needs()];
+        let overrides = [QuantityOverride {
+            station_name: "新進站".to_string(),
+            quantity: 900,
+            reason: "站長來電減量".to_string(),
+        }];
+        let quantities =
+            adjusted_station_quantities(&item_needs, &Freebie::Tissue60, &overrides).unwrap();
+        let station_quantity = quantities
+            .iter()
+            .find(|station_quantity| station_quantity.station_name == "新進站")
+            .unwrap();
+        assert_eq!(station_quantity.quantity, 900);
+        let adjustment = station_quantity.adjustment.as_ref().unwrap();
+        assert_eq!(adjustment.original_quantity, 990);
+        assert_eq!(adjustment.reason, "站長來電減量");
+        assert!(
+            quantities
+                .iter()
+                .filter(|station_quantity| station_quantity.station_name != "新進站")
+                .all(|station_quantity| station_quantity.adjustment.is_none())
+        );
+
+        let bytes = include_bytes!("../../../assets/templates/60抽面紙每週訂購單.xlsx");
+        let template = reader::xlsx::read_reader(Cursor::new(bytes), true).unwrap();
+        let sheet = generate_purchase_order_report(
+            &template,
+            &item_needs,
+            &Freebie::Tissue60,
+            &Date::new(2025, 10, 21).unwrap(),
+            "10-3",
+            &WorkingDayCalendar::bundled(),
+            &overrides,
+        )
+        .unwrap();
+        let worksheet = sheet.get_sheet(&0).unwrap();
+        assert_eq!(worksheet.get_value("F3"), "備註");
+        assert_eq!(worksheet.get_value("C11"), "900");
+        assert_eq!(worksheet.get_value("F11"), "站長來電減量（原 990 箱）");
+
+        // Overrides of stations missing in the item needs are rejected
+        let overrides = [QuantityOverride {
+            station_name: "不存在站".to_string(),
+            quantity: 10,
+            reason: String::new(),
+        }];
+        assert!(adjusted_station_quantities(&item_needs, &Freebie::Tissue60, &overrides).is_err());
+    }
+
+    #[test]
+    fn test_station_rows_on_several_dates() {
+        let json = include_bytes!("../../../testdata/station-rows-on-several-dates.json");
+        let item_needs: ItemNeeds = serde_json::from_slice(json)
+            .expect("Failed to deserialize station-rows-on-several-dates.json");
+        let item_needs = [item_needs];
+        let quantities = station_quantities(&item_needs, &Freebie::Tissue60).unwrap();
+        let station_quantity = quantities
+            .iter()
+            .find(|station_quantity| station_quantity.station_name == "新進站")
+            .unwrap();
+        assert_eq!(station_quantity.quantity, 950);
+        assert_eq!(station_quantity.order_date, Date::new(2025, 9, 29).unwrap());
+
+        // The order form gets the sum of the rows rather than the quantity of the last one
+        let bytes = include_bytes!("../../../assets/templates/60抽面紙每週訂購單.xlsx");
+        let template = reader::xlsx::read_reader(Cursor::new(bytes), true).unwrap();
+        let sheet = generate_purchase_order_report(
+            &template,
+            &item_needs,
+            &Freebie::Tissue60,
+            &Date::new(2025, 10, 21).unwrap(),
+            "10-3",
+            &WorkingDayCalendar::bundled(),
+            &[],
+        )
+        .unwrap();
+        let worksheet = sheet.get_sheet(&0).unwrap();
+        assert_eq!(worksheet.get_value("C11"), "950");
+        assert_eq!(worksheet.get_value("C5"), "20");
+    }
+
     #[test]
     fn test_generate_tissue_60_purchase_order_report() {
         let bytes = include_bytes!("../../../assets/templates/60抽面紙每週訂購單.xlsx");
@@ -100,6 +349,8 @@ mod tests {
             &Freebie::Tissue60,
             &notification_date,
             order_number,
+            &WorkingDayCalendar::bundled(),
+            &[],
         )
         .unwrap();
 
@@ -118,6 +369,8 @@ mod tests {
         );
         assert_eq!(worksheet.get_value("E2"), "114/10/21");
         assert_eq!(worksheet.get_value("C40"), "訂單編號：10-3");
+        assert_eq!(worksheet.get_value("B5"), "114/10/29");
+        assert_eq!(worksheet.get_value("B6"), "");
     }
 
     #[test]
@@ -134,6 +387,8 @@ mod tests {
             &Freebie::Tissue110,
             &notification_date,
             order_number,
+            &WorkingDayCalendar::bundled(),
+            &[],
         )
         .unwrap();
 
@@ -152,6 +407,7 @@ mod tests {
         );
         assert_eq!(worksheet.get_value("E2"), "114/10/28");
         assert_eq!(worksheet.get_value("D38"), "訂單編號：10-4");
+        assert_eq!(worksheet.get_value("B7"), "114/11/06");
     }
 
     #[test]
@@ -168,6 +424,8 @@ mod tests {
             &Freebie::MineralWater,
             &notification_date,
             order_number,
+            &WorkingDayCalendar::bundled(),
+            &[],
         )
         .unwrap();
 
@@ -186,5 +444,6 @@ mod tests {
         );
         assert_eq!(worksheet.get_value("F3"), "114/10/14");
         assert_eq!(worksheet.get_value("F2"), "南訂10-2");
+        assert_eq!(worksheet.get_value("B5"), "114/10/16");
     }
 }
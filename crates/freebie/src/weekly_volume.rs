@@ -0,0 +1,218 @@
+use crate::Freebie;
+use jiff::civil::Date;
+use jiff::{Span, ToSpan};
+use std::collections::HashMap;
+use tscred::ItemNeeds;
+
+/// The number of weeks before the current one which it is compared against.
+const AVERAGE_WEEKS: usize = 4;
+
+/// The cases of the freebies requested in consecutive weeks, each starting on a Monday and the
+/// last one holding the end date.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeeklyVolume {
+    /// The Mondays starting the weeks, oldest first.
+    pub weeks: Vec<Date>,
+    entries: Vec<VolumeEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct VolumeEntry {
+    week: usize,
+    freebie: Freebie,
+    region: String,
+    station_name: String,
+    cases: u64,
+}
+
+impl WeeklyVolume {
+    /// The inclusive date range of the number of weeks up to the end date, to fetch the item
+    /// needs for, or `None` if the weeks start before the earliest date.
+    pub fn date_range(end_date: &Date, weeks: usize) -> Option<(Date, Date)> {
+        let monday = end_date
+            .checked_sub((end_date.weekday().to_monday_zero_offset() as i64).days())
+            .ok()?;
+        let days = i64::try_from(weeks.saturating_sub(1))
+            .ok()?
+            .checked_mul(7)?;
+        let start_date = monday.checked_sub(Span::new().try_days(days).ok()?).ok()?;
+        Some((start_date, *end_date))
+    }
+
+    /// Sums the cases of the freebies requested in the item needs of each region, such as an
+    /// operation center, by the week of their order dates. Requests outside the weeks are left
+    /// out. Returns `None` if the weeks start before the earliest date.
+    pub fn new(
+        regions: &[(String, ItemNeeds)],
+        end_date: &Date,
+        weeks: usize,
+    ) -> Option<WeeklyVolume> {
+        let (start_date, _) = Self::date_range(end_date, weeks)?;
+        let mut entries: Vec<VolumeEntry> = vec![];
+        for (region, item_needs) in regions {
+            let ids = item_needs
+                .get_all_items()
+                .into_iter()
+                .filter_map(|item| {
+                    Freebie::from_name(&item.title).map(|freebie| (item.id, freebie))
+                })
+                .collect::<HashMap<_, _>>();
+            for item_need in item_needs.iter() {
+                if item_need.order_date < start_date || item_need.order_date > *end_date {
+                    continue;
+                }
+                let week = (item_need.order_date - start_date).get_days() as usize / 7;
+                for (id, freebie) in ids.iter() {
+                    let Some(&cases) = item_need.items_count.get(id.as_str()) else {
+                        continue;
+                    };
+                    if cases == 0 {
+                        continue;
+                    }
+                    match entries.iter_mut().find(|entry| {
+                        entry.week == week
+                            && entry.freebie == *freebie
+                            && entry.region == *region
+                            && entry.station_name == item_need.station_name
+                    }) {
+                        Some(entry) => entry.cases += cases,
+                        None => entries.push(VolumeEntry {
+                            week,
+                            freebie: *freebie,
+                            region: region.clone(),
+                            station_name: item_need.station_name.to_string(),
+                            cases,
+                        }),
+                    }
+                }
+            }
+        }
+
+        Some(WeeklyVolume {
+            weeks: (0..weeks)
+                .map(|week| start_date.checked_add((7 * week as i64).days()).ok())
+                .collect::<Option<_>>()?,
+            entries,
+        })
+    }
+
+    /// The cases of the freebie requested in each week, oldest first.
+    pub fn weekly_cases(&self, freebie: &Freebie) -> Vec<(Date, u64)> {
+        self.weeks
+            .iter()
+            .enumerate()
+            .map(|(week, monday)| {
+                let cases = self
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.week == week && entry.freebie == *freebie)
+                    .map(|entry| entry.cases)
+                    .sum();
+                (*monday, cases)
+            })
+            .collect()
+    }
+
+    /// The stations requesting the most cases of all freebies over the weeks, most first.
+    pub fn top_stations(&self, limit: usize) -> Vec<(String, u64)> {
+        let mut stations = sum_by(&self.entries, |entry| &entry.station_name);
+        stations.truncate(limit);
+        stations
+    }
+
+    /// The cases of all freebies requested by each region over the weeks, most first.
+    pub fn region_cases(&self) -> Vec<(String, u64)> {
+        sum_by(&self.entries, |entry| &entry.region)
+    }
+
+    /// The cases of the freebie requested in the last week and the average of the four weeks
+    /// before it, or `None` if there are not that many weeks.
+    pub fn this_week_vs_average(&self, freebie: &Freebie) -> Option<(u64, f64)> {
+        let weekly_cases = self.weekly_cases(freebie);
+        let (&(_, this_week), previous_weeks) = weekly_cases.split_last()?;
+        if previous_weeks.len() < AVERAGE_WEEKS {
+            return None;
+        }
+        let average = previous_weeks[previous_weeks.len() - AVERAGE_WEEKS..]
+            .iter()
+            .map(|(_, cases)| *cases)
+            .sum::<u64>() as f64
+            / AVERAGE_WEEKS as f64;
+        Some((this_week, average))
+    }
+}
+
+fn sum_by<'a>(
+    entries: &'a [VolumeEntry],
+    key: impl Fn(&'a VolumeEntry) -> &'a String,
+) -> Vec<(String, u64)> {
+    let mut sums: Vec<(String, u64)> = vec![];
+    for entry in entries {
+        match sums.iter_mut().find(|(name, _)| name == key(entry)) {
+            Some((_, cases)) => *cases += entry.cases,
+            None => sums.push((key(entry).clone(), entry.cases)),
+        }
+    }
+    // Keep the order the names first appear in for equal cases
+    sums.sort_by(|(_, a), (_, b)| b.cmp(a));
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize_item_needs() -> ItemNeeds {
+        let json = include_bytes!("../../../testdata/GetItemNeedCount.json");
+        serde_json::from_slice(json).expect("Failed to deserialize GetItemNeedCount.json")
+    }
+
+    #[test]
+    fn test_date_range() {
+        // 2025-09-30 is a Tuesday
+        let end_date = Date::new(2025, 9, 30).unwrap();
+        let (start_date, end_date) = WeeklyVolume::date_range(&end_date, 3).unwrap();
+        assert_eq!(start_date, Date::new(2025, 9, 15).unwrap());
+        assert_eq!(end_date, Date::new(2025, 9, 30).unwrap());
+
+        // Weeks reaching before the earliest date
+        assert_eq!(WeeklyVolume::date_range(&end_date, 999999), None);
+        assert_eq!(WeeklyVolume::date_range(&end_date, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_weekly_volume() {
+        let regions = vec![
+            ("北區".to_string(), deserialize_item_needs()),
+            ("南區".to_string(), deserialize_item_needs()),
+        ];
+        let volume = WeeklyVolume::new(&regions, &Date::new(2025, 9, 30).unwrap(), 5).unwrap();
+        assert_eq!(volume.weeks.len(), 5);
+        assert_eq!(volume.weeks[0], Date::new(2025, 9, 1).unwrap());
+        assert_eq!(volume.weeks[4], Date::new(2025, 9, 29).unwrap());
+
+        let weekly_cases = volume
+            .weekly_cases(&Freebie::Tissue60)
+            .into_iter()
+            .map(|(_, cases)| cases)
+            .collect::<Vec<_>>();
+        assert_eq!(weekly_cases, vec![400, 120, 180, 60, 0]);
+
+        assert_eq!(
+            volume.region_cases(),
+            vec![("北區".to_string(), 1830), ("南區".to_string(), 1830)]
+        );
+        assert_eq!(
+            volume.top_stations(2),
+            vec![("豐德站".to_string(), 500), ("月眉站".to_string(), 320)]
+        );
+        assert_eq!(
+            volume.this_week_vs_average(&Freebie::Tissue110),
+            Some((260, 450.))
+        );
+
+        // Not enough weeks to compare against
+        let volume = WeeklyVolume::new(&regions, &Date::new(2025, 9, 30).unwrap(), 4).unwrap();
+        assert_eq!(volume.this_week_vs_average(&Freebie::Tissue110), None);
+    }
+}
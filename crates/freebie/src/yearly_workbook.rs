@@ -0,0 +1,223 @@
+use crate::{Error, Freebie, OrderNumber};
+use log::info;
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+/// The name of the sheet summarizing the weekly orders of a yearly workbook.
+pub const SUMMARY_SHEET_NAME: &str = "總表";
+
+/// Adds a weekly order, as generated by `generate_purchase_order_report`, to the yearly workbook of
+/// the freebie as a sheet named after the order number, and refreshes the summary sheet.
+pub fn append_purchase_order(
+    yearly_workbook: &mut Spreadsheet,
+    purchase_order: &Spreadsheet,
+    freebie: &Freebie,
+    order_number: &OrderNumber,
+) -> Result<(), Error> {
+    let sheet_name = order_number.to_string();
+    if yearly_workbook.get_sheet_by_name(&sheet_name).is_some() {
+        return Err(Error::DuplicateSheet(sheet_name));
+    }
+
+    let mut worksheet = purchase_order
+        .get_sheet(&0)
+        .ok_or(Error::MissingOrderWorksheet)?
+        .clone();
+    worksheet.set_name(&sheet_name);
+    yearly_workbook
+        .add_sheet(worksheet)
+        .expect("unable to add sheet");
+
+    refresh_summary_sheet(yearly_workbook, freebie);
+    info!(
+        freebie = freebie.short_name(),
+        sheet = sheet_name.as_str();
+        "appended purchase order to yearly workbook"
+    );
+
+    Ok(())
+}
+
+/// Rebuilds the summary sheet, the first sheet of the yearly workbook, with a row of the quantity
+/// of each station per weekly order and a row of their totals.
+pub fn refresh_summary_sheet(yearly_workbook: &mut Spreadsheet, freebie: &Freebie) {
+    let _ = yearly_workbook.remove_sheet_by_name(SUMMARY_SHEET_NAME);
+
+    // Collect the weekly orders, with the stations in the order they first appear
+    let mut station_names: Vec<String> = vec![];
+    let mut rows = vec![];
+    for worksheet in yearly_workbook.get_sheet_collection() {
+        let Some(quantities) = weekly_order_quantities(worksheet) else {
+            continue;
+        };
+        // Skip the blank forms prepared for the next order
+        if quantities.iter().all(|(_, quantity)| *quantity == 0.) {
+            continue;
+        }
+        for (station_name, _) in quantities.iter() {
+            if !station_names.contains(station_name) {
+                station_names.push(station_name.clone());
+            }
+        }
+        rows.push((
+            worksheet.get_name().to_string(),
+            worksheet.get_value(freebie.notification_date_coord()),
+            quantities,
+        ));
+    }
+
+    let mut summary = Worksheet::default();
+    summary.set_name(SUMMARY_SHEET_NAME);
+    let total_column = station_names.len() as u32 + 3;
+    let headers = ["工作表", "通知日期"]
+        .into_iter()
+        .chain(station_names.iter().map(String::as_str))
+        .chain(["合計"]);
+    for (column, header) in headers.enumerate() {
+        summary
+            .get_cell_mut((column as u32 + 1, 1))
+            .set_value(header)
+            .get_style_mut()
+            .get_font_mut()
+            .set_bold(true);
+    }
+
+    let mut station_totals = vec![0.; station_names.len()];
+    for (i, (sheet_name, notification_date, quantities)) in rows.iter().enumerate() {
+        let row = i as u32 + 2;
+        summary.get_cell_mut((1, row)).set_value(sheet_name);
+        summary.get_cell_mut((2, row)).set_value(notification_date);
+        for (station_name, quantity) in quantities {
+            let index = station_names
+                .iter()
+                .position(|name| name == station_name)
+                .unwrap();
+            station_totals[index] += quantity;
+            summary
+                .get_cell_mut((index as u32 + 3, row))
+                .set_value_number(*quantity);
+        }
+        summary
+            .get_cell_mut((total_column, row))
+            .set_value_number(quantities.iter().map(|(_, quantity)| quantity).sum::<f64>());
+    }
+
+    let total_row = rows.len() as u32 + 2;
+    summary
+        .get_cell_mut((1, total_row))
+        .set_value("合計")
+        .get_style_mut()
+        .get_font_mut()
+        .set_bold(true);
+    for (index, total) in station_totals.iter().enumerate() {
+        summary
+            .get_cell_mut((index as u32 + 3, total_row))
+            .set_value_number(*total);
+    }
+    summary
+        .get_cell_mut((total_column, total_row))
+        .set_value_number(station_totals.iter().sum::<f64>());
+
+    // Keep the summary in front of the weekly orders
+    yearly_workbook
+        .get_sheet_collection_mut()
+        .insert(0, summary);
+}
+
+/// Returns the quantity of each station of a weekly order form, or `None` if the sheet is not one.
+/// The forms have their header `站別` in A3 or A4 and the stations in A5:A25.
+fn weekly_order_quantities(worksheet: &Worksheet) -> Option<Vec<(String, f64)>> {
+    if worksheet.get_value("A3") != "站別" && worksheet.get_value("A4") != "站別" {
+        return None;
+    }
+
+    let quantities = (5..=25)
+        .filter_map(|row| {
+            let station_name = worksheet.get_value((1, row)).trim().to_string();
+            if station_name.is_empty() || station_name.starts_with("合計") {
+                return None;
+            }
+            let quantity = worksheet
+                .get_cell((3, row))
+                .and_then(|cell| {
+                    cell.get_value_number()
+                        .or_else(|| cell.get_value().trim().parse().ok())
+                })
+                .unwrap_or_default();
+            Some((station_name, quantity))
+        })
+        .collect();
+    Some(quantities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_purchase_order_report;
+    use jiff::civil::Date;
+    use roc_calendar::WorkingDayCalendar;
+    use std::io::Cursor;
+    use tscred::ItemNeeds;
+    use umya_spreadsheet::reader;
+
+    #[test]
+    fn test_append_purchase_order() {
+        let bytes = include_bytes!("../../../assets/templates/60抽面紙每週訂購單.xlsx");
+        let template = reader::xlsx::read_reader(Cursor::new(bytes), true).unwrap();
+        let json = include_bytes!("../../../testdata/generate-purchase-order-report.json");
+        let item_needs: ItemNeeds = serde_json::from_slice(json).unwrap();
+        let calendar = WorkingDayCalendar::bundled();
+
+        let mut yearly_workbook = umya_spreadsheet::new_file_empty_worksheet();
+        for (notification_date, order_number) in [
+            (Date::new(2025, 10, 21).unwrap(), "10-3"),
+            (Date::new(2025, 10, 28).unwrap(), "10-4"),
+        ] {
+            let purchase_order = generate_purchase_order_report(
+                &template,
+                std::slice::from_ref(&item_needs),
+                &Freebie::Tissue60,
+                &notification_date,
+                order_number,
+                &calendar,
+                &[],
+            )
+            .unwrap();
+            append_purchase_order(
+                &mut yearly_workbook,
+                &purchase_order,
+                &Freebie::Tissue60,
+                &order_number.parse().unwrap(),
+            )
+            .unwrap();
+        }
+
+        let sheet_names = yearly_workbook
+            .get_sheet_collection()
+            .iter()
+            .map(|worksheet| worksheet.get_name())
+            .collect::<Vec<_>>();
+        assert_eq!(sheet_names, vec![SUMMARY_SHEET_NAME, "10-3", "10-4"]);
+
+        let summary = yearly_workbook.get_sheet(&0).unwrap();
+        assert_eq!(summary.get_value("A1"), "工作表");
+        assert_eq!(summary.get_value("C1"), "潭頂站");
+        assert_eq!(summary.get_value("A2"), "10-3");
+        assert_eq!(summary.get_value("B3"), "114/10/28");
+        assert_eq!(summary.get_value("A4"), "合計");
+        assert_eq!(summary.get_value("C4"), "40");
+        assert_eq!(summary.get_value("X2"), "1240");
+        assert_eq!(summary.get_value("X4"), "2480");
+
+        // The same order number cannot be added twice
+        let purchase_order = yearly_workbook.clone();
+        assert!(matches!(
+            append_purchase_order(
+                &mut yearly_workbook,
+                &purchase_order,
+                &Freebie::Tissue60,
+                &"10-3".parse().unwrap(),
+            ),
+            Err(Error::DuplicateSheet(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::UnmappedCell;
+use calamine::{Data, Range};
+use jiff::ToSpan;
+use jiff::civil::Date;
+use roc_calendar::RocDate;
+
+/// A worksheet being mapped, which keeps track of the cells it could not map.
+pub(crate) struct SheetReader<'a> {
+    range: &'a Range<Data>,
+    pub(crate) unmapped: Vec<UnmappedCell>,
+}
+
+impl<'a> SheetReader<'a> {
+    pub(crate) fn new(range: &'a Range<Data>) -> Self {
+        SheetReader {
+            range,
+            unmapped: vec![],
+        }
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self.range.height()
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.range.width()
+    }
+
+    /// Returns the trimmed text of the cell, or `None` if the cell is empty.
+    pub(crate) fn text(&self, row: usize, column: usize) -> Option<String> {
+        let text = match self.range.get((row, column))? {
+            Data::Empty | Data::Error(_) => return None,
+            Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.trim().to_string(),
+            Data::Float(f) => f.to_string(),
+            Data::Int(i) => i.to_string(),
+            Data::Bool(b) => b.to_string(),
+            Data::DateTime(date_time) => date_time.to_string(),
+        };
+        Some(text).filter(|text| !text.is_empty())
+    }
+
+    /// Returns the position of the first cell whose text satisfies the predicate, searching row by
+    /// row.
+    pub(crate) fn find<P: Fn(&str) -> bool>(&self, predicate: P) -> Option<(usize, usize)> {
+        (0..self.height()).find_map(|row| {
+            (0..self.width()).find_map(|column| {
+                self.text(row, column)
+                    .filter(|text| predicate(text))
+                    .map(|_| (row, column))
+            })
+        })
+    }
+
+    /// Returns the value of a labeled field, which is either the rest of the label cell, such as
+    /// `訂單編號：1-1`, or the next non-empty cell on its right.
+    pub(crate) fn labeled_value(&self, label: &str) -> Option<(String, usize, usize)> {
+        let (row, column) = self.find(|text| text.starts_with(label))?;
+        let text = self.text(row, column)?;
+        let value = text[label.len()..]
+            .trim_start_matches([':', '：', ' '])
+            .trim();
+        if !value.is_empty() {
+            return Some((value.to_string(), row, column));
+        }
+        (column + 1..self.width())
+            .find_map(|column| self.text(row, column).map(|text| (text, row, column)))
+    }
+
+    /// Returns the whole number in the cell, recording the cell as unmapped if it holds anything
+    /// else.
+    pub(crate) fn quantity(&mut self, row: usize, column: usize) -> Option<u64> {
+        let value = match self.range.get((row, column))? {
+            Data::Float(f) if f.fract() == 0. && *f >= 0. => Some(*f as u64),
+            Data::Int(i) => u64::try_from(*i).ok(),
+            Data::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        if value.is_none() {
+            self.record_unmapped(row, column, "不是數量");
+        }
+        value
+    }
+
+    /// Returns the date in the cell, recording the cell as unmapped if it is not a date.
+    pub(crate) fn date(&mut self, row: usize, column: usize) -> Option<Date> {
+        let value = match self.range.get((row, column))? {
+            Data::Empty => return None,
+            Data::String(s) => s.parse::<RocDate>().ok().map(|date| date.date()),
+            Data::DateTimeIso(s) => s.get(..10).and_then(|s| s.parse().ok()),
+            Data::DateTime(date_time) => from_serial(date_time.as_f64()),
+            _ => None,
+        };
+        if value.is_none() {
+            self.record_unmapped(row, column, "不是日期");
+        }
+        value
+    }
+
+    pub(crate) fn record_unmapped(&mut self, row: usize, column: usize, reason: &str) {
+        let Some(value) = self.text(row, column) else {
+            return;
+        };
+        let (start_row, start_column) = self.range.start().unwrap_or_default();
+        self.unmapped.push(UnmappedCell {
+            cell: coordinate(start_row as usize + row, start_column as usize + column),
+            value,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Converts a spreadsheet date serial number, counted from 1899-12-30, into a date.
+pub(crate) fn from_serial(serial: f64) -> Option<Date> {
+    let epoch = Date::new(1899, 12, 30).unwrap();
+    epoch.checked_add((serial.floor() as i64).days()).ok()
+}
+
+/// Formats a zero-based cell position as a coordinate such as `C12`.
+fn coordinate(row: usize, column: usize) -> String {
+    let mut letters = vec![];
+    let mut column = column + 1;
+    while column > 0 {
+        letters.push((b'A' + ((column - 1) % 26) as u8) as char);
+        column = (column - 1) / 26;
+    }
+    letters.iter().rev().collect::<String>() + &(row + 1).to_string()
+}
+
+/// Normalizes the station names of the different sheets, such as `忠勇`, `忠勇站` and `台糖忠勇站`,
+/// into the name used by the order forms.
+pub(crate) fn normalize_station_name(name: &str) -> String {
+    let name = name.trim();
+    let name = name.strip_prefix("台糖").unwrap_or(name);
+    if name.ends_with('站') {
+        name.to_string()
+    } else {
+        format!("{}站", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_helpers() {
+        assert_eq!(coordinate(0, 0), "A1");
+        assert_eq!(coordinate(11, 2), "C12");
+        assert_eq!(coordinate(0, 26), "AA1");
+        assert_eq!(from_serial(45658.), Date::new(2025, 1, 1).ok());
+        assert_eq!(normalize_station_name("忠勇"), "忠勇站");
+        assert_eq!(normalize_station_name("台糖糖友站"), "糖友站");
+        assert_eq!(normalize_station_name(" 七星站 "), "七星站");
+    }
+}
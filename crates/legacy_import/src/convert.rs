@@ -0,0 +1,90 @@
+use crate::Error;
+use crate::cell::from_serial;
+use calamine::{Data, Reader, Sheets, open_workbook_auto};
+use roc_calendar::RocDate;
+use std::io::{Read, Seek};
+use std::path::Path;
+use umya_spreadsheet::Spreadsheet;
+use umya_spreadsheet::helper::coordinate::coordinate_from_index;
+
+/// Converts a legacy workbook, such as `.xls` and `.ods`, into a spreadsheet which can be saved as
+/// `.xlsx`. Only the values, and the merged cells of `.xls`, are kept; dates are written as ROC
+/// dates like the order forms do.
+pub fn convert_workbook<P: AsRef<Path>>(path: P) -> Result<Spreadsheet, Error> {
+    let mut workbook = open_workbook_auto(path)?;
+    convert_sheets(&mut workbook)
+}
+
+fn convert_sheets<RS: Read + Seek>(workbook: &mut Sheets<RS>) -> Result<Spreadsheet, Error> {
+    let mut spreadsheet = umya_spreadsheet::new_file_empty_worksheet();
+    for sheet_name in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&sheet_name)?;
+        let worksheet = spreadsheet
+            .new_sheet(&sheet_name)
+            .expect("unable to add sheet");
+
+        let (start_row, start_column) = range.start().unwrap_or_default();
+        for (row, column, data) in range.used_cells() {
+            let cell = worksheet
+                .get_cell_mut((start_column + column as u32 + 1, start_row + row as u32 + 1));
+            match data {
+                Data::Empty | Data::Error(_) => {}
+                Data::Float(f) => {
+                    cell.set_value_number(*f);
+                }
+                Data::Int(i) => {
+                    cell.set_value_number(*i as f64);
+                }
+                Data::Bool(b) => {
+                    cell.set_value_bool(*b);
+                }
+                Data::DateTime(date_time) => {
+                    match from_serial(date_time.as_f64())
+                        .and_then(|date| RocDate::try_from(date).ok())
+                    {
+                        Some(date) => cell.set_value(date.to_string()),
+                        None => cell.set_value_number(date_time.as_f64()),
+                    };
+                }
+                Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => {
+                    cell.set_value(s);
+                }
+            }
+        }
+
+        if let Sheets::Xls(xls) = workbook
+            && let Some(merged_cells) = xls.worksheet_merge_cells(&sheet_name)
+        {
+            for dimensions in merged_cells {
+                let (start_row, start_column) = dimensions.start;
+                let (end_row, end_column) = dimensions.end;
+                worksheet.add_merge_cells(format!(
+                    "{}:{}",
+                    coordinate_from_index(&(start_column + 1), &(start_row + 1)),
+                    coordinate_from_index(&(end_column + 1), &(end_row + 1)),
+                ));
+            }
+        }
+    }
+    Ok(spreadsheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_workbook() {
+        let spreadsheet = convert_workbook("../../reports/114年礦泉水訂購單.xls").unwrap();
+        let worksheet = spreadsheet.get_sheet_by_name("1140107").unwrap();
+        assert_eq!(worksheet.get_value("A4"), "站別");
+        assert_eq!(worksheet.get_value("F2"), "中訂1-1");
+        assert_eq!(worksheet.get_value("F3"), "114/01/07");
+        assert!(!worksheet.get_merge_cells().is_empty());
+
+        let spreadsheet = convert_workbook("../../reports/114年面紙訂購單60抽.ods").unwrap();
+        let worksheet = spreadsheet.get_sheet_by_name("1140107").unwrap();
+        assert_eq!(worksheet.get_value("A3"), "站別");
+        assert_eq!(worksheet.get_value("A5"), "忠勇站");
+    }
+}
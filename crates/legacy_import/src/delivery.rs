@@ -0,0 +1,237 @@
+use crate::cell::{SheetReader, normalize_station_name};
+use crate::order::serialize_freebie;
+use freebie::Freebie;
+use jiff::civil::Date;
+use serde::{Serialize, Serializer};
+
+/// A delivery found in a delivery statistics sheet (交貨統計表) or an acceptance report (驗收報告單).
+#[derive(Clone, Debug, Serialize)]
+pub struct LegacyDelivery {
+    pub sheet_name: String,
+    /// The product as named by the column header, such as `盒裝110抽衛生紙`.
+    pub product: String,
+    #[serde(serialize_with = "serialize_optional_freebie")]
+    pub freebie: Option<Freebie>,
+    pub station_name: String,
+    pub station_id: Option<String>,
+    pub quantity: u64,
+    /// Whether the delivery was marked as accepted (合格), if the sheet has the column.
+    pub accepted: Option<bool>,
+    pub order_date: Option<Date>,
+    pub delivery_date: Option<Date>,
+    pub delay_days: Option<u64>,
+    pub defects: Vec<LegacyDefect>,
+    pub remark: Option<String>,
+}
+
+/// The number of boxes rejected for a reason, such as 破損 or 凹瓶.
+#[derive(Clone, Debug, Serialize)]
+pub struct LegacyDefect {
+    pub kind: String,
+    pub quantity: u64,
+}
+
+/// The columns of a delivery sheet, found by their headers.
+#[derive(Default)]
+struct DeliveryColumns {
+    station: usize,
+    station_id: Option<usize>,
+    products: Vec<(usize, String)>,
+    accepted: Option<usize>,
+    order_date: Option<usize>,
+    delivery_date: Option<usize>,
+    delay_days: Option<usize>,
+    defects: Vec<(usize, String)>,
+    ignored: Vec<usize>,
+    remark: Option<usize>,
+}
+
+impl DeliveryColumns {
+    fn is_known(&self, column: usize) -> bool {
+        column == self.station
+            || self.station_id == Some(column)
+            || self.products.iter().any(|(c, _)| *c == column)
+            || self.accepted == Some(column)
+            || self.order_date == Some(column)
+            || self.delivery_date == Some(column)
+            || self.delay_days == Some(column)
+            || self.defects.iter().any(|(c, _)| *c == column)
+            || self.ignored.contains(&column)
+            || self.remark == Some(column)
+    }
+}
+
+/// Maps a delivery sheet, whose header row starts with `站名` or `站別` and is followed by a
+/// sub-header of the defect kinds. Rows without a station continue the station above them.
+pub(crate) fn parse_deliveries(
+    reader: &mut SheetReader,
+    sheet_name: &str,
+    title: &str,
+) -> Option<Vec<LegacyDelivery>> {
+    let (header_row, station) = reader.find(|text| text == "站名" || text == "站別")?;
+    let sub_header_row = header_row + 1;
+    let mut columns = DeliveryColumns {
+        station,
+        ..Default::default()
+    };
+
+    // The product quantities come before 合格, unless the only quantity is named after the title
+    for column in station + 1..reader.width() {
+        let Some(header) = reader.text(header_row, column) else {
+            continue;
+        };
+        match header.as_str() {
+            "站號" => columns.station_id = Some(column),
+            "合格" => columns.accepted = Some(column),
+            "訂貨日期" => columns.order_date = Some(column),
+            "交貨日期" => columns.delivery_date = Some(column),
+            "備註" => columns.remark = Some(column),
+            header if header.starts_with("延遲") => columns.delay_days = Some(column),
+            header if header.starts_with("不合格") => {}
+            header if header.starts_with("數量") => {
+                columns.products.push((column, title_product(title)))
+            }
+            header if columns.accepted.is_none() => {
+                columns.products.push((column, header.to_string()))
+            }
+            _ => {}
+        }
+    }
+    for column in 0..reader.width() {
+        let Some(header) = reader.text(sub_header_row, column) else {
+            continue;
+        };
+        match header.as_str() {
+            "備註" => columns.remark = Some(column),
+            // The total of the defects is derived from the other defect columns
+            "合計" => columns.ignored.push(column),
+            _ => columns.defects.push((column, header)),
+        }
+    }
+    if columns.products.is_empty() {
+        return None;
+    }
+
+    let mut deliveries = vec![];
+    let mut current_station: Option<(String, Option<String>)> = None;
+    for row in sub_header_row + 1..reader.height() {
+        match reader.text(row, columns.station) {
+            Some(text) if text.contains("計") || text.starts_with("經辦") => break,
+            Some(text) => {
+                let station_id = columns.station_id.and_then(|c| reader.text(row, c));
+                current_station = Some((normalize_station_name(&text), station_id));
+            }
+            None if (0..reader.width()).all(|column| reader.text(row, column).is_none()) => {
+                continue;
+            }
+            None if current_station.is_none() => {
+                for column in 0..reader.width() {
+                    reader.record_unmapped(row, column, "沒有站別");
+                }
+                continue;
+            }
+            None => {}
+        }
+        for column in 0..reader.width() {
+            if !columns.is_known(column) {
+                reader.record_unmapped(row, column, "不在已知欄位");
+            }
+        }
+
+        let mut quantities = vec![];
+        for (column, product) in columns.products.iter() {
+            if reader.text(row, *column).is_some()
+                && let Some(quantity) = reader.quantity(row, *column)
+                && quantity > 0
+            {
+                quantities.push((product.clone(), quantity));
+            }
+        }
+        let accepted = columns
+            .accepted
+            .map(|c| reader.text(row, c).is_some_and(|text| text == "V"));
+        let order_date = columns.order_date.and_then(|c| reader.date(row, c));
+        let delivery_date = columns.delivery_date.and_then(|c| reader.date(row, c));
+        let delay_days = columns.delay_days.and_then(|c| {
+            reader
+                .text(row, c)
+                .is_some()
+                .then(|| reader.quantity(row, c))
+                .flatten()
+        });
+        let mut defects = vec![];
+        for (column, kind) in columns.defects.iter() {
+            if reader.text(row, *column).is_some()
+                && let Some(quantity) = reader.quantity(row, *column)
+                && quantity > 0
+            {
+                defects.push(LegacyDefect {
+                    kind: kind.clone(),
+                    quantity,
+                });
+            }
+        }
+        let remark = columns.remark.and_then(|c| reader.text(row, c));
+
+        if quantities.is_empty() {
+            if !defects.is_empty() {
+                for (column, _) in columns.defects.iter() {
+                    reader.record_unmapped(row, *column, "沒有交貨數量");
+                }
+            }
+            continue;
+        }
+
+        // The defects of a row are counted against its first product
+        let (station_name, station_id) = current_station.clone().unwrap();
+        for (i, (product, quantity)) in quantities.into_iter().enumerate() {
+            deliveries.push(LegacyDelivery {
+                sheet_name: sheet_name.to_string(),
+                freebie: product_freebie(&product),
+                product,
+                station_name: station_name.clone(),
+                station_id: station_id.clone(),
+                quantity,
+                accepted,
+                order_date,
+                delivery_date,
+                delay_days,
+                defects: if i == 0 { defects.clone() } else { vec![] },
+                remark: remark.clone(),
+            });
+        }
+    }
+
+    Some(deliveries)
+}
+
+/// Returns the product in the corner brackets of the title, such as `600cc礦泉水`.
+fn title_product(title: &str) -> String {
+    title
+        .split_once('「')
+        .and_then(|(_, rest)| rest.split_once('」'))
+        .map(|(product, _)| product.to_string())
+        .unwrap_or_else(|| title.to_string())
+}
+
+fn product_freebie(product: &str) -> Option<Freebie> {
+    if product.contains("礦泉水") {
+        Some(Freebie::MineralWater)
+    } else if product.contains("110抽") {
+        Some(Freebie::Tissue110)
+    } else if product.contains("60抽") {
+        Some(Freebie::Tissue60)
+    } else {
+        None
+    }
+}
+
+fn serialize_optional_freebie<S: Serializer>(
+    freebie: &Option<Freebie>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match freebie {
+        Some(freebie) => serialize_freebie(freebie, serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    CalamineError(#[from] calamine::Error),
+
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+}
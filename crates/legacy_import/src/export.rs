@@ -0,0 +1,204 @@
+use crate::{LegacyImport, SheetLayout};
+use jiff::civil::Date;
+use roc_calendar::RocDate;
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+/// Writes the imported records into a workbook, with a sheet each for the orders, the deliveries
+/// and the per-sheet report of the cells which could not be mapped.
+pub fn generate_legacy_import_report(import: &LegacyImport) -> Spreadsheet {
+    let mut spreadsheet = umya_spreadsheet::new_file_empty_worksheet();
+
+    let worksheet = spreadsheet
+        .new_sheet("訂購單")
+        .expect("unable to add sheet");
+    set_headers(
+        worksheet,
+        &[
+            "工作表",
+            "贈品",
+            "訂單編號",
+            "通知日期",
+            "追加",
+            "站別",
+            "擬交貨日期",
+            "數量",
+            "備註",
+        ],
+    );
+    let mut row = 2;
+    for order in import.orders.iter() {
+        for line in order.lines.iter() {
+            worksheet
+                .get_cell_mut((1, row))
+                .set_value(&order.sheet_name);
+            worksheet
+                .get_cell_mut((2, row))
+                .set_value(order.freebie.name());
+            worksheet
+                .get_cell_mut((3, row))
+                .set_value(order.order_number_label.as_deref().unwrap_or_default());
+            worksheet
+                .get_cell_mut((4, row))
+                .set_value(format_date(order.notification_date));
+            worksheet
+                .get_cell_mut((5, row))
+                .set_value(if order.supplementary { "V" } else { "" });
+            worksheet
+                .get_cell_mut((6, row))
+                .set_value(&line.station_name);
+            worksheet
+                .get_cell_mut((7, row))
+                .set_value(format_date(line.delivery_date));
+            worksheet
+                .get_cell_mut((8, row))
+                .set_value_number(line.quantity as f64);
+            worksheet
+                .get_cell_mut((9, row))
+                .set_value(line.remark.as_deref().unwrap_or_default());
+            row += 1;
+        }
+    }
+
+    let worksheet = spreadsheet
+        .new_sheet("交貨紀錄")
+        .expect("unable to add sheet");
+    set_headers(
+        worksheet,
+        &[
+            "工作表",
+            "品名",
+            "站名",
+            "站號",
+            "數量",
+            "合格",
+            "訂貨日期",
+            "交貨日期",
+            "延遲交貨天數",
+            "不合格品項",
+            "備註",
+        ],
+    );
+    for (i, delivery) in import.deliveries.iter().enumerate() {
+        let row = i as u32 + 2;
+        worksheet
+            .get_cell_mut((1, row))
+            .set_value(&delivery.sheet_name);
+        worksheet
+            .get_cell_mut((2, row))
+            .set_value(&delivery.product);
+        worksheet
+            .get_cell_mut((3, row))
+            .set_value(&delivery.station_name);
+        worksheet
+            .get_cell_mut((4, row))
+            .set_value(delivery.station_id.as_deref().unwrap_or_default());
+        worksheet
+            .get_cell_mut((5, row))
+            .set_value_number(delivery.quantity as f64);
+        worksheet
+            .get_cell_mut((6, row))
+            .set_value(match delivery.accepted {
+                Some(true) => "V",
+                _ => "",
+            });
+        worksheet
+            .get_cell_mut((7, row))
+            .set_value(format_date(delivery.order_date));
+        worksheet
+            .get_cell_mut((8, row))
+            .set_value(format_date(delivery.delivery_date));
+        if let Some(delay_days) = delivery.delay_days {
+            worksheet
+                .get_cell_mut((9, row))
+                .set_value_number(delay_days as f64);
+        }
+        worksheet.get_cell_mut((10, row)).set_value(
+            delivery
+                .defects
+                .iter()
+                .map(|defect| format!("{}{}", defect.kind, defect.quantity))
+                .collect::<Vec<_>>()
+                .join("、"),
+        );
+        worksheet
+            .get_cell_mut((11, row))
+            .set_value(delivery.remark.as_deref().unwrap_or_default());
+    }
+
+    let worksheet = spreadsheet
+        .new_sheet("匯入報告")
+        .expect("unable to add sheet");
+    set_headers(
+        worksheet,
+        &["工作表", "格式", "紀錄數", "儲存格", "內容", "原因"],
+    );
+    let mut row = 2;
+    for report in import.reports.iter() {
+        worksheet
+            .get_cell_mut((1, row))
+            .set_value(&report.sheet_name);
+        worksheet
+            .get_cell_mut((2, row))
+            .set_value(match report.layout {
+                Some(SheetLayout::WeeklyOrder) => "訂貨通知單",
+                Some(SheetLayout::DeliveryStatistics) => "交貨統計表",
+                Some(SheetLayout::AcceptanceReport) => "驗收報告單",
+                None => "無法辨識",
+            });
+        worksheet
+            .get_cell_mut((3, row))
+            .set_value_number(report.records as f64);
+        if report.unmapped.is_empty() {
+            row += 1;
+        }
+        for unmapped in report.unmapped.iter() {
+            worksheet.get_cell_mut((4, row)).set_value(&unmapped.cell);
+            worksheet.get_cell_mut((5, row)).set_value(&unmapped.value);
+            worksheet.get_cell_mut((6, row)).set_value(&unmapped.reason);
+            row += 1;
+        }
+    }
+
+    spreadsheet
+}
+
+fn set_headers(worksheet: &mut Worksheet, headers: &[&str]) {
+    for (column, header) in headers.iter().enumerate() {
+        worksheet
+            .get_cell_mut((column as u32 + 1, 1))
+            .set_value(*header)
+            .get_style_mut()
+            .get_font_mut()
+            .set_bold(true);
+    }
+}
+
+fn format_date(date: Option<Date>) -> String {
+    date.and_then(|date| RocDate::try_from(date).ok())
+        .map(|date| date.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import_workbook;
+
+    #[test]
+    fn test_generate_legacy_import_report() {
+        let import = import_workbook("../../reports/114年礦泉水訂購單.xls").unwrap();
+        let spreadsheet = generate_legacy_import_report(&import);
+
+        let worksheet = spreadsheet.get_sheet_by_name("訂購單").unwrap();
+        assert_eq!(worksheet.get_value("A2"), "1140107");
+        assert_eq!(worksheet.get_value("C2"), "中訂1-1");
+        assert_eq!(worksheet.get_value("D2"), "114/01/07");
+        assert_eq!(worksheet.get_value("F2"), "鳳凰站");
+        assert_eq!(worksheet.get_value("H2"), "60");
+
+        let worksheet = spreadsheet.get_sheet_by_name("匯入報告").unwrap();
+        assert_eq!(worksheet.get_value("A2"), "1140107");
+        assert_eq!(worksheet.get_value("B2"), "訂貨通知單");
+        assert_eq!(worksheet.get_value("C2"), "1");
+    }
+}
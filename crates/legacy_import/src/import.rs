@@ -0,0 +1,256 @@
+use crate::cell::SheetReader;
+use crate::delivery::parse_deliveries;
+use crate::order::parse_weekly_order;
+use crate::{Error, LegacyDelivery, LegacyOrder};
+use calamine::{Reader, open_workbook_auto, open_workbook_auto_from_rs};
+use serde::Serialize;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum SheetLayout {
+    /// 訂貨通知單
+    WeeklyOrder,
+    /// 交貨統計表
+    DeliveryStatistics,
+    /// 驗收報告單
+    AcceptanceReport,
+}
+
+impl SheetLayout {
+    /// Recognizes the layout of a sheet by its title.
+    fn from_title(title: &str) -> Option<Self> {
+        if title.contains("交貨統計表") {
+            Some(SheetLayout::DeliveryStatistics)
+        } else if title.contains("驗收報告單") {
+            Some(SheetLayout::AcceptanceReport)
+        } else if title.contains("通知單") {
+            Some(SheetLayout::WeeklyOrder)
+        } else {
+            None
+        }
+    }
+}
+
+/// A cell which holds something the importer could not map into a record.
+#[derive(Clone, Debug, Serialize)]
+pub struct UnmappedCell {
+    pub cell: String,
+    pub value: String,
+    pub reason: String,
+}
+
+/// What the importer made of a sheet.
+#[derive(Clone, Debug, Serialize)]
+pub struct SheetReport {
+    pub sheet_name: String,
+    /// The recognized layout, or `None` if the sheet is empty or unrecognized.
+    pub layout: Option<SheetLayout>,
+    pub records: usize,
+    pub unmapped: Vec<UnmappedCell>,
+}
+
+/// The past orders and deliveries found in legacy workbooks.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LegacyImport {
+    pub orders: Vec<LegacyOrder>,
+    pub deliveries: Vec<LegacyDelivery>,
+    pub reports: Vec<SheetReport>,
+}
+
+impl LegacyImport {
+    pub fn extend(&mut self, other: LegacyImport) {
+        self.orders.extend(other.orders);
+        self.deliveries.extend(other.deliveries);
+        self.reports.extend(other.reports);
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Imports a legacy workbook in any format `calamine` can read, such as `.xls` and `.ods`.
+pub fn import_workbook<P: AsRef<Path>>(path: P) -> Result<LegacyImport, Error> {
+    let mut workbook = open_workbook_auto(path)?;
+    import_sheets(&mut workbook)
+}
+
+/// Imports a legacy workbook from a reader, detecting its format from the content.
+pub fn import_workbook_from<RS: Read + Seek + Clone>(reader: RS) -> Result<LegacyImport, Error> {
+    let mut workbook = open_workbook_auto_from_rs(reader)?;
+    import_sheets(&mut workbook)
+}
+
+fn import_sheets<RS: Read + Seek>(
+    workbook: &mut calamine::Sheets<RS>,
+) -> Result<LegacyImport, Error> {
+    let mut import = LegacyImport::default();
+    for sheet_name in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&sheet_name)?;
+        let mut reader = SheetReader::new(&range);
+
+        let title = reader
+            .find(|_| true)
+            .and_then(|(row, column)| reader.text(row, column));
+        let layout = title.as_deref().and_then(SheetLayout::from_title);
+        let records = match (layout, title.as_deref()) {
+            (Some(SheetLayout::WeeklyOrder), Some(title)) => {
+                match parse_weekly_order(&mut reader, &sheet_name, title) {
+                    // Skip the blank forms prepared for the next order
+                    Some(order) if order.lines.is_empty() => 0,
+                    Some(order) => {
+                        import.orders.push(order);
+                        1
+                    }
+                    None => 0,
+                }
+            }
+            (Some(_), Some(title)) => {
+                let deliveries =
+                    parse_deliveries(&mut reader, &sheet_name, title).unwrap_or_default();
+                let records = deliveries.len();
+                import.deliveries.extend(deliveries);
+                records
+            }
+            (None, Some(_)) => {
+                let (row, column) = reader.find(|_| true).unwrap();
+                reader.record_unmapped(row, column, "無法辨識的工作表格式");
+                0
+            }
+            (_, None) => 0,
+        };
+
+        import.reports.push(SheetReport {
+            sheet_name,
+            layout,
+            records,
+            unmapped: reader.unmapped,
+        });
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freebie::Freebie;
+    use jiff::civil::Date;
+
+    fn import_report(file_name: &str) -> LegacyImport {
+        import_workbook(Path::new("../../reports").join(file_name)).unwrap()
+    }
+
+    #[test]
+    fn test_import_weekly_orders() {
+        let import = import_report("114年礦泉水訂購單.xls");
+        assert!(import.deliveries.is_empty());
+
+        let order = &import.orders[0];
+        assert_eq!(order.sheet_name, "1140107");
+        assert_eq!(order.freebie, Freebie::MineralWater);
+        assert_eq!(order.order_number_label.as_deref(), Some("中訂1-1"));
+        assert_eq!(order.order_number.unwrap().to_string(), "1-1");
+        assert_eq!(order.notification_date, Date::new(2025, 1, 7).ok());
+        assert_eq!(order.total_quantity(), 240);
+        assert_eq!(order.lines[1].station_name, "七星站");
+        assert_eq!(order.lines[1].quantity, 60);
+        assert!(
+            import
+                .orders
+                .iter()
+                .any(|order| order.supplementary && order.sheet_name == "1140701(追加)")
+        );
+
+        let import = import_report("114年面紙訂購單60抽.ods");
+        assert!(
+            import
+                .orders
+                .iter()
+                .all(|order| order.freebie == Freebie::Tissue60)
+        );
+        let order = &import.orders[0];
+        assert_eq!(order.order_number_label.as_deref(), Some("1-1"));
+        assert_eq!(order.notification_date, Date::new(2024, 12, 31).ok());
+        assert_eq!(order.total_quantity(), 90);
+
+        let import = import_report("114年110抽面紙每週訂單(中區).xls");
+        assert!(
+            import
+                .orders
+                .iter()
+                .all(|order| order.freebie == Freebie::Tissue110)
+        );
+        assert_eq!(import.orders[0].total_quantity(), 290);
+    }
+
+    #[test]
+    fn test_import_delivery_statistics() {
+        let import = import_report("面紙交貨統計表110抽.xls");
+        assert!(import.orders.is_empty());
+
+        let report = import
+            .reports
+            .iter()
+            .find(|report| report.sheet_name == "114年9月")
+            .unwrap();
+        assert_eq!(report.layout, Some(SheetLayout::DeliveryStatistics));
+
+        let deliveries = import
+            .deliveries
+            .iter()
+            .filter(|delivery| delivery.sheet_name == "114年9月")
+            .collect::<Vec<_>>();
+        let delivery = deliveries
+            .iter()
+            .find(|delivery| delivery.station_name == "七星站")
+            .unwrap();
+        assert_eq!(delivery.station_id.as_deref(), Some("1145"));
+        assert_eq!(delivery.freebie, Some(Freebie::Tissue110));
+        assert_eq!(delivery.quantity, 30);
+        assert_eq!(delivery.accepted, Some(true));
+        assert_eq!(delivery.order_date, Date::new(2025, 9, 9).ok());
+        assert_eq!(delivery.delivery_date, Date::new(2025, 9, 12).ok());
+
+        // The rows below 豐德 without a station continue it
+        let deliveries_of_fengde = deliveries
+            .iter()
+            .filter(|delivery| delivery.station_name == "豐德站" && delivery.freebie.is_some())
+            .map(|delivery| delivery.quantity)
+            .collect::<Vec<_>>();
+        assert_eq!(deliveries_of_fengde, vec![50, 100, 100]);
+
+        // The other product in the same sheet
+        assert!(deliveries.iter().any(|delivery| {
+            delivery.station_name == "嘉保站"
+                && delivery.product == "洗手間環保大捲筒衛生紙"
+                && delivery.quantity == 3
+        }));
+    }
+
+    #[test]
+    fn test_import_acceptance_report() {
+        let import = import_report("礦泉水月結表.xls");
+        let delivery = &import.deliveries[0];
+        assert_eq!(delivery.freebie, Some(Freebie::MineralWater));
+        assert_eq!(delivery.product, "600cc礦泉水");
+        assert_eq!(delivery.station_name, "糖友站");
+        assert_eq!(delivery.delivery_date, Date::new(2024, 12, 11).ok());
+        assert_eq!(delivery.quantity, 60);
+        assert_eq!(
+            import
+                .reports
+                .iter()
+                .map(|report| report.layout)
+                .collect::<Vec<_>>(),
+            vec![
+                Some(SheetLayout::AcceptanceReport),
+                Some(SheetLayout::AcceptanceReport)
+            ]
+        );
+
+        let json = import.to_json().unwrap();
+        assert!(json.contains("\"freebie\": \"台糖礦泉水/箱\""));
+        assert!(json.contains("\"delivery_date\": \"2024-12-11\""));
+    }
+}
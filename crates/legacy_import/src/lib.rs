@@ -0,0 +1,16 @@
+mod cell;
+mod convert;
+mod delivery;
+mod error;
+mod export;
+mod import;
+mod order;
+
+pub use crate::convert::convert_workbook;
+pub use crate::delivery::{LegacyDefect, LegacyDelivery};
+pub use crate::error::Error;
+pub use crate::export::generate_legacy_import_report;
+pub use crate::import::{
+    LegacyImport, SheetLayout, SheetReport, UnmappedCell, import_workbook, import_workbook_from,
+};
+pub use crate::order::{LegacyOrder, LegacyOrderLine};
@@ -0,0 +1,180 @@
+use crate::cell::{SheetReader, normalize_station_name};
+use freebie::{Freebie, OrderNumber};
+use jiff::civil::Date;
+use roc_calendar::RocDate;
+use serde::{Serialize, Serializer};
+
+/// A weekly order form (訂貨通知單) found in a legacy workbook.
+#[derive(Clone, Debug, Serialize)]
+pub struct LegacyOrder {
+    pub sheet_name: String,
+    #[serde(serialize_with = "serialize_freebie")]
+    pub freebie: Freebie,
+    /// The order number as written on the form, such as `中訂1-1`.
+    pub order_number_label: Option<String>,
+    #[serde(serialize_with = "serialize_order_number")]
+    pub order_number: Option<OrderNumber>,
+    pub notification_date: Option<Date>,
+    /// Whether the order was placed in addition to the weekly one (追加).
+    pub supplementary: bool,
+    /// The stations with a positive quantity.
+    pub lines: Vec<LegacyOrderLine>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LegacyOrderLine {
+    pub station_name: String,
+    pub delivery_date: Option<Date>,
+    pub quantity: u64,
+    pub remark: Option<String>,
+}
+
+impl LegacyOrder {
+    pub fn total_quantity(&self) -> u64 {
+        self.lines.iter().map(|line| line.quantity).sum()
+    }
+}
+
+/// Maps a weekly order form, whose header row starts with `站別` and whose rows end with `合計`.
+pub(crate) fn parse_weekly_order(
+    reader: &mut SheetReader,
+    sheet_name: &str,
+    title: &str,
+) -> Option<LegacyOrder> {
+    let (header_row, station_column) = reader.find(|text| text == "站別")?;
+    let find_column = |reader: &SheetReader, predicate: &dyn Fn(&str) -> bool| {
+        (0..reader.width()).find(|column| {
+            reader
+                .text(header_row, *column)
+                .is_some_and(|t| predicate(&t))
+        })
+    };
+    let quantity_column = find_column(reader, &|text| text.starts_with("數量"))?;
+    let delivery_date_column = find_column(reader, &|text| text.ends_with("交貨日期"));
+    let remark_column = find_column(reader, &|text| text == "備註");
+    let ignored_columns = (0..reader.width())
+        .filter(|column| {
+            reader
+                .text(header_row, *column)
+                .is_some_and(|text| text.ends_with("絡人") || text == "電話")
+        })
+        .collect::<Vec<_>>();
+
+    // Tell the tissues apart by the title, or by the sub-header under the quantity
+    let sub_header = reader
+        .text(header_row + 1, quantity_column)
+        .unwrap_or_default();
+    let freebie = if title.contains("礦泉水") {
+        Freebie::MineralWater
+    } else if title.contains("110抽") || sub_header.contains("110抽") {
+        Freebie::Tissue110
+    } else {
+        Freebie::Tissue60
+    };
+
+    let notification_date = reader
+        .labeled_value("通知日期")
+        .and_then(|(value, row, column)| match value.parse::<RocDate>() {
+            Ok(date) => Some(date.date()),
+            Err(_) => {
+                reader.record_unmapped(row, column, "不是日期");
+                None
+            }
+        });
+    let order_number_label = reader.labeled_value("訂單編號");
+    let order_number = order_number_label
+        .as_ref()
+        .and_then(|(value, row, column)| {
+            // Skip the prefix of the operation center, such as `南訂` and `中訂`
+            match value
+                .trim_start_matches(|c: char| !c.is_ascii_digit())
+                .parse::<OrderNumber>()
+            {
+                Ok(order_number) => Some(order_number),
+                Err(_) => {
+                    reader.record_unmapped(*row, *column, "不是訂單編號");
+                    None
+                }
+            }
+        });
+
+    let mut lines = vec![];
+    let mut total = None;
+    for row in header_row + 1..reader.height() {
+        let Some(station) = reader.text(row, station_column) else {
+            // Only the sub-header may go without a station
+            if row > header_row + 1 {
+                for column in 0..reader.width() {
+                    reader.record_unmapped(row, column, "沒有站別");
+                }
+            }
+            continue;
+        };
+        if station.starts_with("合計") {
+            total = Some(row);
+            break;
+        }
+
+        let quantity = match reader.text(row, quantity_column) {
+            Some(_) => reader.quantity(row, quantity_column).unwrap_or_default(),
+            None => 0,
+        };
+        let delivery_date = delivery_date_column.and_then(|column| reader.date(row, column));
+        let remark = remark_column.and_then(|column| reader.text(row, column));
+        for column in 0..reader.width() {
+            if column != station_column
+                && column != quantity_column
+                && Some(column) != delivery_date_column
+                && Some(column) != remark_column
+                && !ignored_columns.contains(&column)
+            {
+                reader.record_unmapped(row, column, "不在已知欄位");
+            }
+        }
+
+        if quantity > 0 {
+            lines.push(LegacyOrderLine {
+                station_name: normalize_station_name(&station),
+                delivery_date,
+                quantity,
+                remark,
+            });
+        }
+    }
+
+    let order = LegacyOrder {
+        sheet_name: sheet_name.to_string(),
+        freebie,
+        order_number_label: order_number_label.map(|(value, _, _)| value),
+        order_number,
+        notification_date,
+        supplementary: title.contains("追加") || sheet_name.contains("追加"),
+        lines,
+    };
+
+    // Check the total against the stations, which reveals the rows that were left out
+    if let Some(row) = total
+        && reader.quantity(row, quantity_column) != Some(order.total_quantity())
+    {
+        reader.record_unmapped(row, quantity_column, "合計與各站數量不符");
+    }
+
+    Some(order)
+}
+
+pub(crate) fn serialize_freebie<S: Serializer>(
+    freebie: &Freebie,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(freebie.name())
+}
+
+fn serialize_order_number<S: Serializer>(
+    order_number: &Option<OrderNumber>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match order_number {
+        Some(order_number) => serializer.collect_str(order_number),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,48 @@
+use freebie::{DefectKind, Freebie};
+use jiff::civil::Date;
+
+/// A delivery to a station to record against its order.
+#[derive(Clone, Debug)]
+pub struct NewDelivery {
+    pub order_id: i64,
+    pub station_name: String,
+    pub delivery_date: Date,
+    /// The boxes the station received, including the defective ones.
+    pub quantity_received: u64,
+    pub defects: Vec<Defect>,
+}
+
+/// A delivery to a station recorded in the ledger.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Delivery {
+    pub id: i64,
+    pub order_id: i64,
+    pub freebie: Freebie,
+    pub station_name: String,
+    pub delivery_date: Date,
+    pub quantity_received: u64,
+    pub defects: Vec<Defect>,
+}
+
+impl Delivery {
+    /// The boxes rejected for any reason.
+    pub fn defect_quantity(&self) -> u64 {
+        self.defects.iter().map(|defect| defect.quantity).sum()
+    }
+}
+
+/// The boxes of a delivery rejected for a reason.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Defect {
+    pub kind: DefectKind,
+    pub quantity: u64,
+}
+
+/// The filters for finding deliveries. The dates filter on the delivery date, inclusively.
+#[derive(Clone, Debug, Default)]
+pub struct DeliveryQuery {
+    pub freebie: Option<Freebie>,
+    pub order_id: Option<i64>,
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
+}
@@ -0,0 +1,58 @@
+use crate::OrderStatus;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    SqliteError(#[from] rusqlite::Error),
+
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    JiffError(#[from] jiff::Error),
+
+    #[error(transparent)]
+    FreebieError(#[from] freebie::Error),
+
+    #[error("unknown freebie in ledger: {0}")]
+    UnknownFreebie(String),
+
+    #[error("unknown order status in ledger: {0}")]
+    UnknownOrderStatus(String),
+
+    #[error("order {0} not found")]
+    OrderNotFound(i64),
+
+    #[error("unknown defect kind in ledger: {0}")]
+    UnknownDefectKind(String),
+
+    #[error("{station_name} is not in order {order_id}")]
+    StationNotInOrder { order_id: i64, station_name: String },
+
+    #[error("{station_name} would receive {received} of the {ordered} ordered")]
+    ExcessDelivery {
+        station_name: String,
+        ordered: u64,
+        received: u64,
+    },
+
+    #[error("{station_name} cannot have {defects} defects in {received} received")]
+    ExcessDefects {
+        station_name: String,
+        defects: u64,
+        received: u64,
+    },
+
+    #[error("{freebie} is not inspected for {kind}")]
+    InapplicableDefectKind { freebie: String, kind: String },
+
+    #[error("delivery on {delivery_date} is before the notification on {notification_date}")]
+    DeliveryBeforeNotification {
+        delivery_date: jiff::civil::Date,
+        notification_date: jiff::civil::Date,
+    },
+
+    #[error("order cannot move from {from} to {to}")]
+    InvalidStatusTransition { from: OrderStatus, to: OrderStatus },
+}
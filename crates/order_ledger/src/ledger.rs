@@ -0,0 +1,1067 @@
+use crate::{
+    Confirmation, Defect, Delivery, DeliveryQuery, Error, NewDelivery, NewOrder, Order, OrderQuery,
+    OrderStatus, SentMail,
+};
+use freebie::{DefectKind, Freebie, QuantityAdjustment, StationQuantity};
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
+use tscred::ItemNeeds;
+
+/// The schema changes, applied in order and tracked with `PRAGMA user_version`.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE orders (
+        id INTEGER PRIMARY KEY,
+        freebie TEXT NOT NULL,
+        order_number TEXT NOT NULL,
+        notification_date TEXT NOT NULL,
+        report_start_date TEXT NOT NULL,
+        report_end_date TEXT NOT NULL,
+        output_path TEXT NOT NULL,
+        item_needs TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX orders_notification_date ON orders (notification_date);
+    CREATE TABLE order_stations (
+        order_id INTEGER NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        station_name TEXT NOT NULL,
+        order_date TEXT NOT NULL,
+        quantity INTEGER NOT NULL,
+        PRIMARY KEY (order_id, position)
+    );
+    CREATE INDEX order_stations_station_name ON order_stations (station_name);
+",
+    "
+    ALTER TABLE order_stations ADD COLUMN original_quantity INTEGER;
+    ALTER TABLE order_stations ADD COLUMN adjustment_reason TEXT;
+",
+    "
+    CREATE TABLE sent_mails (
+        id INTEGER PRIMARY KEY,
+        freebie TEXT NOT NULL,
+        order_number TEXT NOT NULL,
+        recipients TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        attachment_name TEXT NOT NULL,
+        sent_at TEXT NOT NULL
+    );
+    CREATE INDEX sent_mails_order ON sent_mails (freebie, order_number);
+",
+    "
+    ALTER TABLE orders ADD COLUMN status TEXT NOT NULL DEFAULT 'draft';
+    ALTER TABLE orders ADD COLUMN sent_date TEXT;
+    CREATE TABLE order_confirmations (
+        order_id INTEGER PRIMARY KEY REFERENCES orders (id) ON DELETE CASCADE,
+        confirmation_date TEXT NOT NULL,
+        confirmer TEXT NOT NULL,
+        scan_path TEXT
+    );
+",
+    "
+    CREATE TABLE deliveries (
+        id INTEGER PRIMARY KEY,
+        order_id INTEGER NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+        station_name TEXT NOT NULL,
+        delivery_date TEXT NOT NULL,
+        quantity_received INTEGER NOT NULL,
+        recorded_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE INDEX deliveries_order ON deliveries (order_id, station_name);
+    CREATE INDEX deliveries_delivery_date ON deliveries (delivery_date);
+    CREATE TABLE delivery_defects (
+        delivery_id INTEGER NOT NULL REFERENCES deliveries (id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        quantity INTEGER NOT NULL,
+        PRIMARY KEY (delivery_id, kind)
+    );
+",
+    // Order numbers repeat every year, so link the mails to the orders themselves, guessing the
+    // latest order notified before each mail was sent
+    "
+    ALTER TABLE sent_mails ADD COLUMN order_id INTEGER REFERENCES orders (id) ON DELETE SET NULL;
+    UPDATE sent_mails SET order_id = (
+        SELECT id FROM orders
+        WHERE orders.freebie = sent_mails.freebie
+            AND orders.order_number = sent_mails.order_number
+            AND orders.notification_date <= substr(sent_mails.sent_at, 1, 10)
+        ORDER BY orders.notification_date DESC, orders.id DESC
+        LIMIT 1
+    );
+    CREATE INDEX sent_mails_order_id ON sent_mails (order_id);
+",
+];
+
+const ORDER_COLUMNS: &str = "id, freebie, order_number, notification_date, report_start_date, \
+    report_end_date, output_path, status, sent_date";
+
+/// The record of the generated purchase orders, stored in a SQLite database.
+pub struct Ledger {
+    connection: Connection,
+}
+
+impl Ledger {
+    /// Opens the ledger at the path, creating the database if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self, Error> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let transaction = connection.transaction()?;
+        for migration in MIGRATIONS.iter().skip(version) {
+            transaction.execute_batch(migration)?;
+        }
+        transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        transaction.commit()?;
+
+        Ok(Ledger { connection })
+    }
+
+    /// Records a generated order and returns its ID.
+    pub fn record_order(&mut self, order: &NewOrder) -> Result<i64, Error> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO orders (freebie, order_number, notification_date, report_start_date, \
+                report_end_date, output_path, item_needs) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                order.freebie.name(),
+                order.order_number.to_string(),
+                order.notification_date.to_string(),
+                order.report_start_date.to_string(),
+                order.report_end_date.to_string(),
+                order.output_path.to_string_lossy(),
+                serde_json::to_string(order.item_needs)?,
+            ],
+        )?;
+        let id = transaction.last_insert_rowid();
+
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO order_stations (order_id, position, station_name, order_date, \
+                    quantity, original_quantity, adjustment_reason) \
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (position, station) in order.stations.iter().enumerate() {
+                statement.execute(params![
+                    id,
+                    position,
+                    station.station_name,
+                    station.order_date.to_string(),
+                    station.quantity,
+                    station
+                        .adjustment
+                        .as_ref()
+                        .map(|adjustment| adjustment.original_quantity),
+                    station
+                        .adjustment
+                        .as_ref()
+                        .map(|adjustment| &adjustment.reason),
+                ])?;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(id)
+    }
+
+    pub fn get_order(&self, id: i64) -> Result<Option<Order>, Error> {
+        let order = self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM orders WHERE id = ?1", ORDER_COLUMNS),
+                [id],
+                OrderRow::from_row,
+            )
+            .optional()?;
+        order.map(|order| self.load_order(order)).transpose()
+    }
+
+    /// Finds the orders matching the query, ordered by notification date.
+    pub fn find_orders(&self, query: &OrderQuery) -> Result<Vec<Order>, Error> {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        if let Some(freebie) = &query.freebie {
+            conditions.push("freebie = ?");
+            values.push(Value::from(freebie.name().to_string()));
+        }
+        if let Some(station_name) = query.station_name {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM order_stations WHERE order_id = orders.id \
+                    AND station_name = ? AND quantity > 0)",
+            );
+            values.push(Value::from(station_name.to_string()));
+        }
+        if let Some(start_date) = &query.start_date {
+            conditions.push("notification_date >= ?");
+            values.push(Value::from(start_date.to_string()));
+        }
+        if let Some(end_date) = &query.end_date {
+            conditions.push("notification_date <= ?");
+            values.push(Value::from(end_date.to_string()));
+        }
+        if let Some(status) = &query.status {
+            conditions.push("status = ?");
+            values.push(Value::from(status.to_string()));
+        }
+
+        let mut sql = format!("SELECT {} FROM orders", ORDER_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY notification_date, id");
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(values), OrderRow::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter().map(|row| self.load_order(row)).collect()
+    }
+
+    /// Moves the order forward to the status on the date, which also becomes its sent date if it
+    /// skipped being sent.
+    pub fn set_status(&mut self, id: i64, status: OrderStatus, date: &Date) -> Result<(), Error> {
+        let current = self.status_of(id)?;
+        if status <= current {
+            return Err(Error::InvalidStatusTransition {
+                from: current,
+                to: status,
+            });
+        }
+        self.connection.execute(
+            "UPDATE orders SET status = ?2, sent_date = COALESCE(sent_date, ?3) WHERE id = ?1",
+            params![id, status.to_string(), date.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Records or corrects the confirmation returned by the vendor, moving the order forward to
+    /// confirmed unless it is already further along.
+    pub fn confirm_order(&mut self, id: i64, confirmation: &Confirmation) -> Result<(), Error> {
+        let current = self.status_of(id)?;
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO order_confirmations (order_id, confirmation_date, confirmer, \
+                scan_path) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                confirmation.confirmation_date.to_string(),
+                confirmation.confirmer,
+                confirmation
+                    .scan_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy()),
+            ],
+        )?;
+        if current < OrderStatus::Confirmed {
+            transaction.execute(
+                "UPDATE orders SET status = ?2, sent_date = COALESCE(sent_date, ?3) WHERE id = ?1",
+                params![
+                    id,
+                    OrderStatus::Confirmed.to_string(),
+                    confirmation.confirmation_date.to_string()
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn status_of(&self, id: i64) -> Result<OrderStatus, Error> {
+        let status: Option<String> = self
+            .connection
+            .query_row("SELECT status FROM orders WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        status.ok_or(Error::OrderNotFound(id))?.parse()
+    }
+
+    /// Returns the item needs the order was generated from.
+    pub fn item_needs_snapshot(&self, id: i64) -> Result<Vec<ItemNeeds>, Error> {
+        let json: String = self.connection.query_row(
+            "SELECT item_needs FROM orders WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Records an order form mailed to the vendor and returns its ID, marking its order as sent
+    /// if it is a draft.
+    pub fn record_sent_mail(&mut self, sent_mail: &SentMail) -> Result<i64, Error> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO sent_mails (order_id, freebie, order_number, recipients, subject, \
+                attachment_name, sent_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                sent_mail.order_id,
+                sent_mail.freebie.name(),
+                sent_mail.order_number.to_string(),
+                serde_json::to_string(&sent_mail.recipients)?,
+                sent_mail.subject,
+                sent_mail.attachment_name,
+                sent_mail.sent_at.to_string(),
+            ],
+        )?;
+        let id = transaction.last_insert_rowid();
+        transaction.execute(
+            "UPDATE orders SET status = ?2, sent_date = ?3 WHERE id = ?1 AND status = ?4",
+            params![
+                sent_mail.order_id,
+                OrderStatus::Sent.to_string(),
+                sent_mail
+                    .sent_at
+                    .to_zoned(TimeZone::system())
+                    .date()
+                    .to_string(),
+                OrderStatus::Draft.to_string(),
+            ],
+        )?;
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    /// Returns the mails sent for the order, oldest first.
+    pub fn sent_mails(&self, order_id: i64) -> Result<Vec<SentMail>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT freebie, order_number, recipients, subject, attachment_name, sent_at \
+                FROM sent_mails WHERE order_id = ?1 ORDER BY sent_at, id",
+        )?;
+        statement
+            .query_map([order_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })?
+            .map(|sent_mail| {
+                let (freebie, order_number, recipients, subject, attachment_name, sent_at) =
+                    sent_mail?;
+                Ok(SentMail {
+                    order_id: Some(order_id),
+                    freebie: Freebie::from_name(&freebie).ok_or(Error::UnknownFreebie(freebie))?,
+                    order_number: order_number.parse()?,
+                    recipients: serde_json::from_str(&recipients)?,
+                    subject,
+                    attachment_name,
+                    sent_at: sent_at.parse()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Records the deliveries and returns their IDs, after checking each against its order: the
+    /// station must be in the order, the defects must be ones the freebie is inspected for and no
+    /// more than the boxes received, and the station must not receive more than it ordered in all.
+    /// An order moves to delivered once every station has received its quantity.
+    pub fn record_deliveries(&mut self, deliveries: &[NewDelivery]) -> Result<Vec<i64>, Error> {
+        let mut orders: HashMap<i64, Order> = HashMap::new();
+        let mut received: HashMap<(i64, &str), u64> = HashMap::new();
+        for delivery in deliveries {
+            let order = match orders.entry(delivery.order_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    self.get_order(delivery.order_id)?
+                        .ok_or(Error::OrderNotFound(delivery.order_id))?,
+                ),
+            };
+            let station_name = delivery.station_name.as_str();
+            if !order
+                .stations
+                .iter()
+                .any(|station| station.station_name == station_name)
+            {
+                return Err(Error::StationNotInOrder {
+                    order_id: order.id,
+                    station_name: station_name.to_string(),
+                });
+            }
+            if delivery.delivery_date < order.notification_date {
+                return Err(Error::DeliveryBeforeNotification {
+                    delivery_date: delivery.delivery_date,
+                    notification_date: order.notification_date,
+                });
+            }
+            if let Some(defect) = delivery
+                .defects
+                .iter()
+                .find(|defect| !order.freebie.defect_kinds().contains(&defect.kind))
+            {
+                return Err(Error::InapplicableDefectKind {
+                    freebie: order.freebie.short_name().to_string(),
+                    kind: defect.kind.label().to_string(),
+                });
+            }
+            let defects = delivery.defects.iter().map(|defect| defect.quantity).sum();
+            if defects > delivery.quantity_received {
+                return Err(Error::ExcessDefects {
+                    station_name: station_name.to_string(),
+                    defects,
+                    received: delivery.quantity_received,
+                });
+            }
+
+            let received = match received.entry((order.id, station_name)) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(self.received_quantity(order.id, station_name)?)
+                }
+            };
+            let ordered = order.quantity_of(station_name);
+            if *received + delivery.quantity_received > ordered {
+                return Err(Error::ExcessDelivery {
+                    station_name: station_name.to_string(),
+                    ordered,
+                    received: *received + delivery.quantity_received,
+                });
+            }
+            *received += delivery.quantity_received;
+        }
+
+        let mut delivered_orders = vec![];
+        for order in orders.values() {
+            if order.status >= OrderStatus::Delivered {
+                continue;
+            }
+            let mut is_fully_delivered = true;
+            for station in order.stations.iter() {
+                let quantity = match received.get(&(order.id, station.station_name.as_str())) {
+                    Some(quantity) => *quantity,
+                    None => self.received_quantity(order.id, &station.station_name)?,
+                };
+                is_fully_delivered &= quantity >= station.quantity;
+            }
+            if is_fully_delivered {
+                delivered_orders.push(order);
+            }
+        }
+
+        let transaction = self.connection.transaction()?;
+        let mut ids = vec![];
+        for delivery in deliveries {
+            transaction.execute(
+                "INSERT INTO deliveries (order_id, station_name, delivery_date, quantity_received) \
+                    VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    delivery.order_id,
+                    delivery.station_name,
+                    delivery.delivery_date.to_string(),
+                    delivery.quantity_received,
+                ],
+            )?;
+            let id = transaction.last_insert_rowid();
+            for defect in delivery.defects.iter().filter(|defect| defect.quantity > 0) {
+                transaction.execute(
+                    "INSERT INTO delivery_defects (delivery_id, kind, quantity) VALUES (?1, ?2, ?3)",
+                    params![id, defect.kind.label(), defect.quantity],
+                )?;
+            }
+            ids.push(id);
+        }
+        for order in delivered_orders {
+            transaction.execute(
+                "UPDATE orders SET status = ?2 WHERE id = ?1",
+                params![order.id, OrderStatus::Delivered.to_string()],
+            )?;
+        }
+        transaction.commit()?;
+
+        Ok(ids)
+    }
+
+    /// The boxes the station has received for the order so far.
+    pub fn received_quantity(&self, order_id: i64, station_name: &str) -> Result<u64, Error> {
+        Ok(self.connection.query_row(
+            "SELECT COALESCE(SUM(quantity_received), 0) FROM deliveries \
+                WHERE order_id = ?1 AND station_name = ?2",
+            params![order_id, station_name],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Finds the deliveries matching the query, ordered by delivery date.
+    pub fn find_deliveries(&self, query: &DeliveryQuery) -> Result<Vec<Delivery>, Error> {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        if let Some(freebie) = &query.freebie {
+            conditions.push("orders.freebie = ?");
+            values.push(Value::from(freebie.name().to_string()));
+        }
+        if let Some(order_id) = query.order_id {
+            conditions.push("deliveries.order_id = ?");
+            values.push(Value::from(order_id));
+        }
+        if let Some(start_date) = &query.start_date {
+            conditions.push("deliveries.delivery_date >= ?");
+            values.push(Value::from(start_date.to_string()));
+        }
+        if let Some(end_date) = &query.end_date {
+            conditions.push("deliveries.delivery_date <= ?");
+            values.push(Value::from(end_date.to_string()));
+        }
+
+        let mut sql = "SELECT deliveries.id, deliveries.order_id, orders.freebie, \
+            deliveries.station_name, deliveries.delivery_date, deliveries.quantity_received \
+            FROM deliveries JOIN orders ON orders.id = deliveries.order_id"
+            .to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY deliveries.delivery_date, deliveries.id");
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, u64>(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(
+                |(id, order_id, freebie, station_name, delivery_date, quantity_received)| {
+                    Ok(Delivery {
+                        id,
+                        order_id,
+                        freebie: Freebie::from_name(&freebie)
+                            .ok_or(Error::UnknownFreebie(freebie))?,
+                        station_name,
+                        delivery_date: delivery_date.parse()?,
+                        quantity_received,
+                        defects: self.load_defects(id)?,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Deletes a delivery entered by mistake, returning whether it existed. The status of its order
+    /// stays as it is.
+    pub fn delete_delivery(&mut self, id: i64) -> Result<bool, Error> {
+        Ok(self
+            .connection
+            .execute("DELETE FROM deliveries WHERE id = ?1", [id])?
+            > 0)
+    }
+
+    fn load_defects(&self, delivery_id: i64) -> Result<Vec<Defect>, Error> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT kind, quantity FROM delivery_defects WHERE delivery_id = ?1")?;
+        let mut defects = statement
+            .query_map([delivery_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+            })?
+            .map(|defect| {
+                let (kind, quantity) = defect?;
+                Ok(Defect {
+                    kind: DefectKind::from_label(&kind).ok_or(Error::UnknownDefectKind(kind))?,
+                    quantity,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        defects.sort_by_key(|defect| defect.kind);
+        Ok(defects)
+    }
+
+    fn load_order(&self, row: OrderRow) -> Result<Order, Error> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT station_name, order_date, quantity, original_quantity, adjustment_reason \
+                FROM order_stations WHERE order_id = ?1 ORDER BY position",
+        )?;
+        let stations = statement
+            .query_map([row.id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, Option<u64>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })?
+            .map(|station| {
+                let (station_name, order_date, quantity, original_quantity, reason) = station?;
+                Ok(StationQuantity {
+                    station_name,
+                    order_date: order_date.parse()?,
+                    quantity,
+                    adjustment: original_quantity.map(|original_quantity| QuantityAdjustment {
+                        original_quantity,
+                        reason: reason.unwrap_or_default(),
+                    }),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let confirmation = self
+            .connection
+            .query_row(
+                "SELECT confirmation_date, confirmer, scan_path FROM order_confirmations \
+                    WHERE order_id = ?1",
+                [row.id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                    ))
+                },
+            )
+            .optional()?
+            .map(|(confirmation_date, confirmer, scan_path)| {
+                Ok::<_, Error>(Confirmation {
+                    confirmation_date: confirmation_date.parse()?,
+                    confirmer,
+                    scan_path: scan_path.map(PathBuf::from),
+                })
+            })
+            .transpose()?;
+
+        Ok(Order {
+            id: row.id,
+            freebie: Freebie::from_name(&row.freebie).ok_or(Error::UnknownFreebie(row.freebie))?,
+            order_number: row.order_number.parse()?,
+            notification_date: row.notification_date.parse()?,
+            report_start_date: row.report_start_date.parse()?,
+            report_end_date: row.report_end_date.parse()?,
+            stations,
+            output_path: PathBuf::from(row.output_path),
+            status: row.status.parse()?,
+            sent_date: row.sent_date.map(|date| date.parse()).transpose()?,
+            confirmation,
+        })
+    }
+}
+
+/// The columns of an order as stored, before parsing.
+struct OrderRow {
+    id: i64,
+    freebie: String,
+    order_number: String,
+    notification_date: String,
+    report_start_date: String,
+    report_end_date: String,
+    output_path: String,
+    status: String,
+    sent_date: Option<String>,
+}
+
+impl OrderRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(OrderRow {
+            id: row.get(0)?,
+            freebie: row.get(1)?,
+            order_number: row.get(2)?,
+            notification_date: row.get(3)?,
+            report_start_date: row.get(4)?,
+            report_end_date: row.get(5)?,
+            output_path: row.get(6)?,
+            status: row.get(7)?,
+            sent_date: row.get(8)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roc_calendar::WorkingDayCalendar;
+
+    fn deserialize_item_needs() -> ItemNeeds {
+        let json = include_bytes!("../../../testdata/generate-purchase-order-report.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    fn record_order(ledger: &mut Ledger, freebie: Freebie, notification_date: Date) -> i64 {
+        record_numbered_order(ledger, freebie, "8-2", notification_date)
+    }
+
+    fn record_numbered_order(
+        ledger: &mut Ledger,
+        freebie: Freebie,
+        order_number: &str,
+        notification_date: Date,
+    ) -> i64 {
+        let item_needs = [deserialize_item_needs()];
+        let stations = freebie::station_quantities(&item_needs, &freebie).unwrap();
+        ledger
+            .record_order(&NewOrder {
+                freebie,
+                order_number: order_number.parse().unwrap(),
+                notification_date,
+                report_start_date: Date::new(2025, 8, 1).unwrap(),
+                report_end_date: Date::new(2025, 8, 7).unwrap(),
+                stations: &stations,
+                item_needs: &item_needs,
+                output_path: Path::new("訂購單.xlsx"),
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_record_order() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let id = record_order(
+            &mut ledger,
+            Freebie::Tissue60,
+            Date::new(2025, 8, 12).unwrap(),
+        );
+
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.freebie, Freebie::Tissue60);
+        assert_eq!(order.order_number.to_string(), "8-2");
+        assert_eq!(order.notification_date, Date::new(2025, 8, 12).unwrap());
+        assert_eq!(order.report_end_date, Date::new(2025, 8, 7).unwrap());
+        assert_eq!(order.output_path, PathBuf::from("訂購單.xlsx"));
+        assert_eq!(order.quantity_of("新進站"), 990);
+        assert_eq!(order.total_quantity(), 1240);
+
+        let snapshot = ledger.item_needs_snapshot(id).unwrap();
+        assert_eq!(
+            freebie::station_quantities(&snapshot, &Freebie::Tissue60).unwrap(),
+            order.stations
+        );
+
+        assert!(ledger.get_order(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_adjusted_order() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let item_needs = [deserialize_item_needs()];
+        let overrides = [freebie::QuantityOverride {
+            station_name: "新進站".to_string(),
+            quantity: 900,
+            reason: "站長來電減量".to_string(),
+        }];
+        let stations =
+            freebie::adjusted_station_quantities(&item_needs, &Freebie::Tissue60, &overrides)
+                .unwrap();
+        let id = ledger
+            .record_order(&NewOrder {
+                freebie: Freebie::Tissue60,
+                order_number: "8-2".parse().unwrap(),
+                notification_date: Date::new(2025, 8, 12).unwrap(),
+                report_start_date: Date::new(2025, 8, 1).unwrap(),
+                report_end_date: Date::new(2025, 8, 7).unwrap(),
+                stations: &stations,
+                item_needs: &item_needs,
+                output_path: Path::new("訂購單.xlsx"),
+            })
+            .unwrap();
+
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.stations, stations);
+        assert_eq!(order.quantity_of("新進站"), 900);
+        assert_eq!(order.total_quantity(), 1150);
+    }
+
+    #[test]
+    fn test_find_orders() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        record_order(
+            &mut ledger,
+            Freebie::Tissue60,
+            Date::new(2025, 7, 29).unwrap(),
+        );
+        let august_id = record_order(
+            &mut ledger,
+            Freebie::Tissue60,
+            Date::new(2025, 8, 12).unwrap(),
+        );
+        record_order(
+            &mut ledger,
+            Freebie::MineralWater,
+            Date::new(2025, 8, 19).unwrap(),
+        );
+
+        let query = OrderQuery {
+            station_name: Some("新進站"),
+            start_date: Some(Date::new(2025, 8, 1).unwrap()),
+            end_date: Some(Date::new(2025, 8, 31).unwrap()),
+            ..Default::default()
+        };
+        let orders = ledger.find_orders(&query).unwrap();
+        assert_eq!(
+            orders.iter().map(|order| order.id).collect::<Vec<_>>(),
+            vec![august_id]
+        );
+
+        let orders = ledger
+            .find_orders(&OrderQuery {
+                freebie: Some(Freebie::MineralWater),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(ledger.find_orders(&OrderQuery::default()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_record_sent_mail() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let id = record_numbered_order(
+            &mut ledger,
+            Freebie::Tissue110,
+            "10-3",
+            Date::new(2025, 10, 20).unwrap(),
+        );
+        let other_id = record_numbered_order(
+            &mut ledger,
+            Freebie::Tissue60,
+            "10-3",
+            Date::new(2025, 10, 20).unwrap(),
+        );
+        let sent_mail = SentMail {
+            order_id: Some(id),
+            freebie: Freebie::Tissue110,
+            order_number: "10-3".parse().unwrap(),
+            recipients: vec!["biding@p-life.com.tw".to_string()],
+            subject: "110抽面紙訂貨通知單 10-3".to_string(),
+            attachment_name: "110抽面紙每週訂購單10-3.pdf".to_string(),
+            sent_at: "2025-10-21T02:30:00Z".parse().unwrap(),
+        };
+        ledger.record_sent_mail(&sent_mail).unwrap();
+
+        assert_eq!(ledger.sent_mails(id).unwrap(), vec![sent_mail]);
+        assert!(ledger.sent_mails(other_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_sent_mail_of_repeated_order_number() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let last_year_id = record_numbered_order(
+            &mut ledger,
+            Freebie::Tissue110,
+            "10-3",
+            Date::new(2024, 10, 21).unwrap(),
+        );
+        let id = record_numbered_order(
+            &mut ledger,
+            Freebie::Tissue110,
+            "10-3",
+            Date::new(2025, 10, 20).unwrap(),
+        );
+        ledger
+            .record_sent_mail(&SentMail {
+                order_id: Some(id),
+                freebie: Freebie::Tissue110,
+                order_number: "10-3".parse().unwrap(),
+                recipients: vec!["biding@p-life.com.tw".to_string()],
+                subject: "110抽面紙訂貨通知單 10-3".to_string(),
+                attachment_name: "110抽面紙每週訂購單10-3.pdf".to_string(),
+                sent_at: "2025-10-21T02:30:00Z".parse().unwrap(),
+            })
+            .unwrap();
+
+        // Only the order the mail was sent for is marked as sent
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Sent);
+        assert_eq!(order.sent_date, Some(Date::new(2025, 10, 21).unwrap()));
+        let last_year_order = ledger.get_order(last_year_id).unwrap().unwrap();
+        assert_eq!(last_year_order.status, OrderStatus::Draft);
+        assert_eq!(last_year_order.sent_date, None);
+        assert_eq!(ledger.sent_mails(id).unwrap().len(), 1);
+        assert!(ledger.sent_mails(last_year_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_order_status() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let id = record_order(
+            &mut ledger,
+            Freebie::Tissue110,
+            Date::new(2025, 9, 1).unwrap(),
+        );
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Draft);
+        assert_eq!(order.sent_date, None);
+
+        ledger
+            .record_sent_mail(&SentMail {
+                order_id: Some(id),
+                freebie: Freebie::Tissue110,
+                order_number: "8-2".parse().unwrap(),
+                recipients: vec!["biding@p-life.com.tw".to_string()],
+                subject: "110抽面紙訂貨通知單 8-2".to_string(),
+                attachment_name: "110抽面紙每週訂購單8-2.pdf".to_string(),
+                sent_at: "2025-09-02T02:30:00Z".parse().unwrap(),
+            })
+            .unwrap();
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Sent);
+        assert_eq!(order.sent_date, Some(Date::new(2025, 9, 2).unwrap()));
+        let calendar = WorkingDayCalendar::bundled();
+        assert_eq!(
+            order.unconfirmed_working_days(&calendar, &Date::new(2025, 9, 5).unwrap()),
+            Some(3)
+        );
+        assert_eq!(
+            ledger
+                .find_orders(&OrderQuery {
+                    status: Some(OrderStatus::Sent),
+                    ..Default::default()
+                })
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let confirmation = Confirmation {
+            confirmation_date: Date::new(2025, 9, 3).unwrap(),
+            confirmer: "王小明".to_string(),
+            scan_path: Some(PathBuf::from("scans/8-2.pdf")),
+        };
+        ledger.confirm_order(id, &confirmation).unwrap();
+        let order = ledger.get_order(id).unwrap().unwrap();
+        assert_eq!(order.status, OrderStatus::Confirmed);
+        assert_eq!(order.confirmation, Some(confirmation));
+        assert_eq!(
+            order.unconfirmed_working_days(&calendar, &Date::new(2025, 9, 5).unwrap()),
+            None
+        );
+
+        ledger
+            .set_status(id, OrderStatus::Delivered, &Date::new(2025, 9, 8).unwrap())
+            .unwrap();
+        assert!(matches!(
+            ledger.set_status(id, OrderStatus::Sent, &Date::new(2025, 9, 8).unwrap()),
+            Err(Error::InvalidStatusTransition {
+                from: OrderStatus::Delivered,
+                to: OrderStatus::Sent
+            })
+        ));
+        assert!(matches!(
+            ledger.set_status(id + 1, OrderStatus::Sent, &Date::new(2025, 9, 8).unwrap()),
+            Err(Error::OrderNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_deliveries() {
+        let mut ledger = Ledger::open_in_memory().unwrap();
+        let id = record_order(
+            &mut ledger,
+            Freebie::Tissue60,
+            Date::new(2025, 8, 12).unwrap(),
+        );
+        let order = ledger.get_order(id).unwrap().unwrap();
+        let delivery = |station_name: &str, quantity_received, defects: Vec<Defect>| NewDelivery {
+            order_id: id,
+            station_name: station_name.to_string(),
+            delivery_date: Date::new(2025, 8, 19).unwrap(),
+            quantity_received,
+            defects,
+        };
+
+        let ids = ledger
+            .record_deliveries(&[
+                delivery(
+                    "新進站",
+                    600,
+                    vec![Defect {
+                        kind: DefectKind::Damp,
+                        quantity: 2,
+                    }],
+                ),
+                delivery("新進站", 300, vec![]),
+            ])
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ledger.received_quantity(id, "新進站").unwrap(), 900);
+
+        // The station would receive more than it ordered in all
+        assert!(matches!(
+            ledger.record_deliveries(&[delivery("新進站", 91, vec![])]),
+            Err(Error::ExcessDelivery {
+                ordered: 990,
+                received: 991,
+                ..
+            })
+        ));
+        assert!(matches!(
+            ledger.record_deliveries(&[delivery("不存在站", 1, vec![])]),
+            Err(Error::StationNotInOrder { .. })
+        ));
+        assert!(matches!(
+            ledger.record_deliveries(&[delivery(
+                "新進站",
+                10,
+                vec![Defect {
+                    kind: DefectKind::DentedBottle,
+                    quantity: 1,
+                }]
+            )]),
+            Err(Error::InapplicableDefectKind { .. })
+        ));
+        assert!(matches!(
+            ledger.record_deliveries(&[delivery(
+                "新進站",
+                1,
+                vec![Defect {
+                    kind: DefectKind::Damaged,
+                    quantity: 2,
+                }]
+            )]),
+            Err(Error::ExcessDefects { .. })
+        ));
+        assert_eq!(ledger.received_quantity(id, "新進站").unwrap(), 900);
+
+        let deliveries = ledger
+            .find_deliveries(&DeliveryQuery {
+                freebie: Some(Freebie::Tissue60),
+                start_date: Some(Date::new(2025, 8, 1).unwrap()),
+                end_date: Some(Date::new(2025, 8, 31).unwrap()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].defect_quantity(), 2);
+        assert_eq!(
+            ledger.get_order(id).unwrap().unwrap().status,
+            OrderStatus::Draft
+        );
+
+        // Delivering the rest of every station completes the order
+        let rest = order
+            .stations
+            .iter()
+            .map(|station| {
+                let received = ledger.received_quantity(id, &station.station_name).unwrap();
+                delivery(&station.station_name, station.quantity - received, vec![])
+            })
+            .collect::<Vec<_>>();
+        ledger.record_deliveries(&rest).unwrap();
+        assert_eq!(
+            ledger.get_order(id).unwrap().unwrap().status,
+            OrderStatus::Delivered
+        );
+
+        assert!(ledger.delete_delivery(ids[1]).unwrap());
+        assert_eq!(ledger.received_quantity(id, "新進站").unwrap(), 690);
+    }
+}
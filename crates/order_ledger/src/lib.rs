@@ -0,0 +1,11 @@
+mod delivery;
+mod error;
+mod ledger;
+mod mail;
+mod order;
+
+pub use crate::delivery::{Defect, Delivery, DeliveryQuery, NewDelivery};
+pub use crate::error::Error;
+pub use crate::ledger::Ledger;
+pub use crate::mail::SentMail;
+pub use crate::order::{Confirmation, NewOrder, Order, OrderQuery, OrderStatus};
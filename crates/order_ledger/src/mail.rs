@@ -0,0 +1,15 @@
+use freebie::{Freebie, OrderNumber};
+use jiff::Timestamp;
+
+/// An order form mailed to the vendor, recorded in the ledger.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SentMail {
+    /// The order the mailed form was generated for, or `None` if it could not be recorded.
+    pub order_id: Option<i64>,
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub attachment_name: String,
+    pub sent_at: Timestamp,
+}
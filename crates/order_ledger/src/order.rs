@@ -0,0 +1,150 @@
+use crate::Error;
+use freebie::{Freebie, OrderNumber, StationQuantity};
+use jiff::civil::Date;
+use roc_calendar::WorkingDayCalendar;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tscred::ItemNeeds;
+
+/// A generated purchase order to record in the ledger.
+pub struct NewOrder<'a> {
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub notification_date: Date,
+    pub report_start_date: Date,
+    pub report_end_date: Date,
+    pub stations: &'a [StationQuantity],
+    /// The item needs the order was generated from.
+    pub item_needs: &'a [ItemNeeds],
+    pub output_path: &'a Path,
+}
+
+/// A purchase order recorded in the ledger.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Order {
+    pub id: i64,
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub notification_date: Date,
+    pub report_start_date: Date,
+    pub report_end_date: Date,
+    pub stations: Vec<StationQuantity>,
+    pub output_path: PathBuf,
+    pub status: OrderStatus,
+    /// When the order was sent to the vendor, by mail or otherwise.
+    pub sent_date: Option<Date>,
+    pub confirmation: Option<Confirmation>,
+}
+
+impl Order {
+    /// The quantity ordered for the station, or zero if the station is not in the order.
+    pub fn quantity_of(&self, station_name: &str) -> u64 {
+        self.stations
+            .iter()
+            .filter(|station| station.station_name == station_name)
+            .map(|station| station.quantity)
+            .sum()
+    }
+
+    pub fn total_quantity(&self) -> u64 {
+        self.stations.iter().map(|station| station.quantity).sum()
+    }
+
+    /// The working days the vendor has left a sent order unconfirmed as of today, or `None` if the
+    /// order is not waiting for a confirmation.
+    pub fn unconfirmed_working_days(
+        &self,
+        calendar: &WorkingDayCalendar,
+        today: &Date,
+    ) -> Option<i32> {
+        if self.status != OrderStatus::Sent {
+            return None;
+        }
+        let sent_date = self.sent_date.unwrap_or(self.notification_date);
+        Some(calendar.working_days_between(&sent_date, today).max(0))
+    }
+}
+
+/// The stages of an order, which only moves forward.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum OrderStatus {
+    /// Generated but not yet sent to the vendor.
+    Draft,
+    Sent,
+    /// The vendor returned the form with its 供貨商確認章 and 確認日期.
+    Confirmed,
+    Delivered,
+    /// The stations accepted the delivery.
+    Accepted,
+}
+
+impl OrderStatus {
+    pub fn all() -> [OrderStatus; 5] {
+        [
+            OrderStatus::Draft,
+            OrderStatus::Sent,
+            OrderStatus::Confirmed,
+            OrderStatus::Delivered,
+            OrderStatus::Accepted,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrderStatus::Draft => "草稿",
+            OrderStatus::Sent => "已寄出",
+            OrderStatus::Confirmed => "已確認",
+            OrderStatus::Delivered => "已交貨",
+            OrderStatus::Accepted => "已驗收",
+        }
+    }
+
+    /// The status following this one, or `None` for the last one.
+    pub fn next(&self) -> Option<OrderStatus> {
+        OrderStatus::all().into_iter().find(|status| status > self)
+    }
+}
+
+impl Display for OrderStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OrderStatus::Draft => "draft",
+            OrderStatus::Sent => "sent",
+            OrderStatus::Confirmed => "confirmed",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Accepted => "accepted",
+        })
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OrderStatus::all()
+            .into_iter()
+            .find(|status| status.to_string() == s)
+            .ok_or_else(|| Error::UnknownOrderStatus(s.to_string()))
+    }
+}
+
+/// The confirmation of an order returned by the vendor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Confirmation {
+    pub confirmation_date: Date,
+    pub confirmer: String,
+    /// The scan of the stamped form.
+    pub scan_path: Option<PathBuf>,
+}
+
+/// The filters for finding orders. The dates filter on the notification date, inclusively.
+#[derive(Clone, Debug, Default)]
+pub struct OrderQuery<'a> {
+    pub freebie: Option<Freebie>,
+    /// Only the orders with a positive quantity for the station.
+    pub station_name: Option<&'a str>,
+    pub start_date: Option<Date>,
+    pub end_date: Option<Date>,
+    pub status: Option<OrderStatus>,
+}
@@ -0,0 +1,160 @@
+use crate::{AttachmentFormat, Error};
+use freebie::Freebie;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const DEFAULT_SUBJECT_TEMPLATE: &str =
+    "台糖公司{freebie}訂貨通知單 {order_number}（通知日期 {notification_date}）";
+
+const DEFAULT_BODY_TEMPLATE: &str = "\
+{vendor} 您好：
+
+檢附台糖公司油品事業部台南區營業組{freebie}訂貨通知單，訂單編號 {order_number}，\
+通知日期 {notification_date}，各站數量及擬交貨日期詳如附件。
+收到後請回覆確認，謝謝。
+
+台糖公司油品事業部 台南區營業組
+";
+
+/// The settings of the mailer, saved as TOML.
+///
+/// The subject and body templates may contain `{freebie}`, `{vendor}`, `{order_number}` and
+/// `{notification_date}`, which are replaced by the short name of the freebie, the name of its
+/// vendor, the order number with its prefix and the ROC notification date.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MailerConfig {
+    pub smtp: SmtpConfig,
+    /// The sender of the orders, such as `台南區營業組 <someone@taisugar.com.tw>`.
+    pub sender: String,
+    /// The addresses copied on every order, such as the sender itself.
+    pub cc: Vec<String>,
+    pub attachment: AttachmentFormat,
+    pub subject_template: String,
+    pub body_template: String,
+    /// The addresses of the vendor of each freebie, by the short name of the freebie.
+    pub vendors: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        MailerConfig {
+            smtp: SmtpConfig::default(),
+            sender: String::new(),
+            cc: vec![],
+            attachment: AttachmentFormat::Xlsx,
+            subject_template: DEFAULT_SUBJECT_TEMPLATE.to_string(),
+            body_template: DEFAULT_BODY_TEMPLATE.to_string(),
+            // The address printed in the contact block of the 110抽面紙 template
+            vendors: Freebie::all()
+                .into_iter()
+                .map(|freebie| {
+                    let addresses = match freebie {
+                        Freebie::Tissue110 => vec!["biding@p-life.com.tw".to_string()],
+                        _ => vec![],
+                    };
+                    (freebie.short_name().to_string(), addresses)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MailerConfig {
+    /// The addresses of the vendor of the freebie.
+    pub fn recipients(&self, freebie: &Freebie) -> &[String] {
+        self.vendors
+            .get(freebie.short_name())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for MailerConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+impl Display for MailerConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = toml::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    /// The user name to log in with, or empty if the server accepts mail without logging in.
+    pub username: String,
+    pub password: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        SmtpConfig {
+            host: String::new(),
+            port: 587,
+            security: Security::StartTls,
+            username: String::new(),
+            password: String::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// Plain text, only meant for a local test server such as smtp4dev or MailHog.
+    None,
+    /// Upgraded with `STARTTLS`, usually on port 587.
+    StartTls,
+    /// TLS from the start, usually on port 465.
+    Tls,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mailer_config() {
+        let config: MailerConfig = r#"
+sender = "台南區營業組 <tainan@example.com>"
+attachment = "pdf"
+
+[smtp]
+host = "smtp.example.com"
+port = 465
+security = "tls"
+
+[vendors]
+"60抽面紙" = ["order@example.com"]
+"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(config.smtp.port, 465);
+        assert_eq!(config.smtp.security, Security::Tls);
+        assert_eq!(config.smtp.timeout_secs, 30);
+        assert_eq!(config.attachment, AttachmentFormat::Pdf);
+        assert_eq!(config.recipients(&Freebie::Tissue60), ["order@example.com"]);
+        assert!(config.recipients(&Freebie::MineralWater).is_empty());
+        assert_eq!(config.subject_template, DEFAULT_SUBJECT_TEMPLATE);
+
+        // The default settings survive a round trip
+        let config = MailerConfig::default();
+        assert_eq!(config.to_string().parse::<MailerConfig>().unwrap(), config);
+    }
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    TomlDeError(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    AddressError(#[from] lettre::address::AddressError),
+
+    #[error(transparent)]
+    MessageError(#[from] lettre::error::Error),
+
+    #[error(transparent)]
+    SmtpError(#[from] lettre::transport::smtp::Error),
+
+    #[error("no vendor address is configured for {0}")]
+    NoRecipients(String),
+
+    #[error("SMTP server is not configured")]
+    MissingSmtpHost,
+}
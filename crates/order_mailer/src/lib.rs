@@ -0,0 +1,9 @@
+mod config;
+mod error;
+mod mail;
+mod mailer;
+
+pub use crate::config::{MailerConfig, Security, SmtpConfig};
+pub use crate::error::Error;
+pub use crate::mail::{AttachmentFormat, OrderDocument, OrderMail};
+pub use crate::mailer::Mailer;
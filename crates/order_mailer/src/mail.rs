@@ -0,0 +1,55 @@
+use freebie::{Freebie, OrderNumber};
+use jiff::civil::Date;
+use serde::{Deserialize, Serialize};
+
+/// The file format of the order form attached to the mail.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentFormat {
+    Xlsx,
+    Pdf,
+}
+
+impl AttachmentFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AttachmentFormat::Xlsx => "xlsx",
+            AttachmentFormat::Pdf => "pdf",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AttachmentFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            AttachmentFormat::Pdf => "application/pdf",
+        }
+    }
+}
+
+/// A generated order form to send to the vendor of the freebie.
+pub struct OrderDocument {
+    /// The order in the ledger, or `None` if it could not be recorded.
+    pub order_id: Option<i64>,
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub notification_date: Date,
+    pub format: AttachmentFormat,
+    pub content: Vec<u8>,
+}
+
+/// An order form composed into a mail, to be confirmed by the user before it is sent.
+#[derive(Clone, Debug)]
+pub struct OrderMail {
+    pub order_id: Option<i64>,
+    pub freebie: Freebie,
+    pub order_number: OrderNumber,
+    pub recipients: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachment_name: String,
+    pub format: AttachmentFormat,
+    pub attachment: Vec<u8>,
+}
@@ -0,0 +1,254 @@
+use crate::{Error, MailerConfig, OrderDocument, OrderMail, Security};
+use jiff::Timestamp;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use order_ledger::SentMail;
+use roc_calendar::RocDate;
+use std::time::Duration;
+
+/// Sends the order forms to the vendors over SMTP.
+pub struct Mailer {
+    config: MailerConfig,
+}
+
+impl Mailer {
+    pub fn new(config: MailerConfig) -> Self {
+        Mailer { config }
+    }
+
+    pub fn config(&self) -> &MailerConfig {
+        &self.config
+    }
+
+    /// Addresses the document to the vendor of its freebie, filling in the subject and body
+    /// templates.
+    pub fn compose(&self, document: OrderDocument) -> Result<OrderMail, Error> {
+        let freebie = document.freebie;
+        let recipients = self.config.recipients(&freebie).to_vec();
+        if recipients.is_empty() {
+            return Err(Error::NoRecipients(freebie.short_name().to_string()));
+        }
+
+        let notification_date = RocDate::try_from(document.notification_date)
+            .map(|date| date.to_string())
+            .unwrap_or_else(|_| document.notification_date.to_string());
+        let fill_in = |template: &str| {
+            template
+                .replace("{freebie}", freebie.short_name())
+                .replace("{vendor}", freebie.vendor().name)
+                .replace(
+                    "{order_number}",
+                    &freebie.order_number_cell_value(document.order_number.to_string()),
+                )
+                .replace("{notification_date}", &notification_date)
+        };
+
+        Ok(OrderMail {
+            order_id: document.order_id,
+            freebie,
+            order_number: document.order_number,
+            recipients,
+            cc: self.config.cc.clone(),
+            subject: fill_in(&self.config.subject_template),
+            body: fill_in(&self.config.body_template),
+            attachment_name: format!(
+                "{}每週訂購單{}.{}",
+                freebie.short_name(),
+                document.order_number,
+                document.format.extension()
+            ),
+            format: document.format,
+            attachment: document.content,
+        })
+    }
+
+    /// Sends the mail and returns the record of it to keep in the ledger.
+    pub fn send(&self, mail: &OrderMail) -> Result<SentMail, Error> {
+        let smtp = &self.config.smtp;
+        if smtp.host.trim().is_empty() {
+            return Err(Error::MissingSmtpHost);
+        }
+
+        let mut builder = Message::builder()
+            .from(self.config.sender.parse::<Mailbox>()?)
+            .subject(&mail.subject);
+        for recipient in mail.recipients.iter() {
+            builder = builder.to(recipient.parse()?);
+        }
+        for cc in mail.cc.iter() {
+            builder = builder.cc(cc.parse()?);
+        }
+        let content_type =
+            ContentType::parse(mail.format.content_type()).expect("invalid content type");
+        let message = builder.multipart(
+            MultiPart::mixed()
+                .singlepart(SinglePart::plain(mail.body.clone()))
+                .singlepart(
+                    Attachment::new(mail.attachment_name.clone())
+                        .body(mail.attachment.clone(), content_type),
+                ),
+        )?;
+
+        let mut transport = match smtp.security {
+            Security::None => SmtpTransport::builder_dangerous(smtp.host.trim()),
+            Security::StartTls => SmtpTransport::starttls_relay(smtp.host.trim())?,
+            Security::Tls => SmtpTransport::relay(smtp.host.trim())?,
+        }
+        .port(smtp.port)
+        .timeout(Some(Duration::from_secs(smtp.timeout_secs)));
+        if !smtp.username.is_empty() {
+            transport = transport.credentials(Credentials::new(
+                smtp.username.clone(),
+                smtp.password.clone(),
+            ));
+        }
+        transport.build().send(&message)?;
+
+        Ok(SentMail {
+            order_id: mail.order_id,
+            freebie: mail.freebie,
+            order_number: mail.order_number,
+            recipients: mail.recipients.clone(),
+            subject: mail.subject.clone(),
+            attachment_name: mail.attachment_name.clone(),
+            sent_at: Timestamp::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AttachmentFormat, SmtpConfig};
+    use freebie::Freebie;
+    use jiff::civil::Date;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Accepts one SMTP session on a local port, returning the port and the handle of the thread
+    /// which yields the envelope recipients and the data of the message.
+    fn spawn_smtp_stand_in() -> (u16, thread::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut recipients = vec![];
+            let mut data = String::new();
+            writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let command = line.trim_end().to_ascii_uppercase();
+                if command.starts_with("EHLO") || command.starts_with("HELO") {
+                    writer.write_all(b"250 localhost\r\n").unwrap();
+                } else if command.starts_with("RCPT TO:") {
+                    recipients.push(line.trim_end()[8..].trim_matches(['<', '>']).to_string());
+                    writer.write_all(b"250 OK\r\n").unwrap();
+                } else if command == "DATA" {
+                    writer.write_all(b"354 End data with .\r\n").unwrap();
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line == ".\r\n" {
+                            break;
+                        }
+                        data.push_str(&line);
+                    }
+                    writer.write_all(b"250 OK\r\n").unwrap();
+                } else if command == "QUIT" {
+                    writer.write_all(b"221 Bye\r\n").unwrap();
+                    break;
+                } else {
+                    writer.write_all(b"250 OK\r\n").unwrap();
+                }
+            }
+            (recipients, data)
+        });
+        (port, handle)
+    }
+
+    fn document() -> OrderDocument {
+        OrderDocument {
+            order_id: Some(1),
+            freebie: Freebie::MineralWater,
+            order_number: "10-2".parse().unwrap(),
+            notification_date: Date::new(2025, 10, 21).unwrap(),
+            format: AttachmentFormat::Pdf,
+            content: b"%PDF-1.3".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut config = MailerConfig::default();
+        let mailer = Mailer::new(config.clone());
+        assert!(matches!(
+            mailer.compose(document()),
+            Err(Error::NoRecipients(_))
+        ));
+
+        config
+            .vendors
+            .insert("礦泉水".to_string(), vec!["water@example.com".to_string()]);
+        let mail = Mailer::new(config).compose(document()).unwrap();
+        assert_eq!(mail.recipients, ["water@example.com"]);
+        assert_eq!(
+            mail.subject,
+            "台糖公司礦泉水訂貨通知單 南訂10-2（通知日期 114/10/21）"
+        );
+        assert!(mail.body.starts_with("台糖公司 您好："));
+        assert_eq!(mail.attachment_name, "礦泉水每週訂購單10-2.pdf");
+    }
+
+    #[test]
+    fn test_send() {
+        let (port, handle) = spawn_smtp_stand_in();
+        let mut config = MailerConfig {
+            smtp: SmtpConfig {
+                host: "127.0.0.1".to_string(),
+                port,
+                security: Security::None,
+                timeout_secs: 5,
+                ..Default::default()
+            },
+            sender: "台南區營業組 <tainan@example.com>".to_string(),
+            cc: vec!["tainan@example.com".to_string()],
+            ..Default::default()
+        };
+        config
+            .vendors
+            .insert("礦泉水".to_string(), vec!["water@example.com".to_string()]);
+        let mailer = Mailer::new(config);
+
+        let mail = mailer.compose(document()).unwrap();
+        let sent_mail = mailer.send(&mail).unwrap();
+        assert_eq!(sent_mail.recipients, ["water@example.com"]);
+        assert_eq!(sent_mail.attachment_name, "礦泉水每週訂購單10-2.pdf");
+        assert_eq!(sent_mail.order_id, Some(1));
+
+        let (recipients, data) = handle.join().unwrap();
+        assert_eq!(recipients, ["water@example.com", "tainan@example.com"]);
+        assert!(data.contains("To: water@example.com"));
+        assert!(data.contains("Cc: tainan@example.com"));
+        assert!(data.contains("Content-Type: application/pdf"));
+        assert!(data.contains("Content-Disposition: attachment"));
+    }
+
+    #[test]
+    fn test_send_without_smtp_host() {
+        let mut config = MailerConfig::default();
+        config
+            .vendors
+            .insert("礦泉水".to_string(), vec!["water@example.com".to_string()]);
+        let mailer = Mailer::new(config);
+        let mail = mailer.compose(document()).unwrap();
+        assert!(matches!(mailer.send(&mail), Err(Error::MissingSmtpHost)));
+    }
+}
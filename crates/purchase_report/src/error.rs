@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid {field}: {value}")]
+    InvalidNumber { field: &'static str, value: String },
+
+    #[error(transparent)]
+    RocDateError(#[from] roc_calendar::Error),
+}
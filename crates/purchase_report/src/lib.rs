@@ -0,0 +1,13 @@
+mod error;
+mod number;
+mod reconciliation;
+mod settlement;
+
+pub use crate::error::Error;
+pub use crate::reconciliation::{
+    NetQuantity, ReturnEntry, ReturnMatch, ReturnsReconciliation,
+    generate_returns_reconciliation_report,
+};
+pub use crate::settlement::{
+    SettlementLine, SettlementStatement, generate_settlement_statement_report,
+};
@@ -0,0 +1,15 @@
+use crate::Error;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::str::FromStr;
+
+pub(crate) fn parse_decimal(field: &'static str, value: &str) -> Result<Decimal, Error> {
+    Decimal::from_str(value).map_err(|_| Error::InvalidNumber {
+        field,
+        value: value.to_string(),
+    })
+}
+
+pub(crate) fn to_cell_number(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
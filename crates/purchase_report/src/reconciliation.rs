@@ -0,0 +1,383 @@
+use crate::Error;
+use crate::number::{parse_decimal, to_cell_number};
+use daily_necessities::{Purchase, PurchaseList};
+use roc_calendar::RocDate;
+use rust_decimal::Decimal;
+use umya_spreadsheet::Spreadsheet;
+
+const RETURNS_SHEET_NAME: &str = "退貨對帳";
+const NET_QUANTITIES_SHEET_NAME: &str = "淨數量";
+
+/// A purchase which is (partially) reversed by a return.
+#[derive(Debug)]
+pub struct ReturnMatch<'a> {
+    pub purchase: &'a Purchase,
+    pub quantity: Decimal,
+}
+
+/// A row with a negative quantity, together with the purchases it reverses.
+#[derive(Debug)]
+pub struct ReturnEntry<'a> {
+    pub purchase: &'a Purchase,
+    pub quantity: Decimal,
+    pub amount_before_tax: Decimal,
+    pub matches: Vec<ReturnMatch<'a>>,
+    pub unmatched_quantity: Decimal,
+}
+
+impl ReturnEntry<'_> {
+    pub fn is_matched(&self) -> bool {
+        self.unmatched_quantity.is_zero()
+    }
+
+    fn status(&self) -> &'static str {
+        if self.is_matched() {
+            "已對應"
+        } else if self.matches.is_empty() {
+            "無對應進貨"
+        } else {
+            "部分對應"
+        }
+    }
+}
+
+/// The purchased and returned quantities of a product at a station.
+#[derive(Debug)]
+pub struct NetQuantity<'a> {
+    pub station_id: &'a str,
+    pub station_name: &'a str,
+    pub product_id: &'a str,
+    pub product_name: &'a str,
+    pub purchased: Decimal,
+    pub returned: Decimal,
+}
+
+impl NetQuantity<'_> {
+    pub fn net(&self) -> Decimal {
+        self.purchased - self.returned
+    }
+}
+
+#[derive(Debug)]
+pub struct ReturnsReconciliation<'a> {
+    pub returns: Vec<ReturnEntry<'a>>,
+    pub net_quantities: Vec<NetQuantity<'a>>,
+}
+
+impl<'a> ReturnsReconciliation<'a> {
+    /// Pairs every return in the purchase list with the purchases it reverses. A return is matched
+    /// against purchases of the same station, product and price dated on or before it, starting
+    /// from the most recent one, and each purchased quantity can only be reversed once.
+    pub fn new(purchase_list: &'a PurchaseList) -> Result<Self, Error> {
+        let mut purchases = vec![];
+        let mut returns = vec![];
+        for purchase in purchase_list.iter() {
+            let quantity = parse_decimal("quantity", &purchase.quantity)?;
+            let price = parse_decimal("price", &purchase.price)?;
+            if quantity < Decimal::ZERO {
+                returns.push((purchase, price, -quantity));
+            } else if quantity > Decimal::ZERO {
+                purchases.push((purchase, price, quantity));
+            }
+        }
+
+        let mut remaining = purchases
+            .iter()
+            .map(|(_, _, quantity)| *quantity)
+            .collect::<Vec<_>>();
+        let mut return_entries = Vec::with_capacity(returns.len());
+        for (purchase, price, quantity) in returns {
+            let mut unmatched_quantity = quantity;
+            let mut matches = vec![];
+            for (index, (candidate, candidate_price, _)) in purchases.iter().enumerate().rev() {
+                if unmatched_quantity.is_zero() {
+                    break;
+                }
+                if candidate.station_id != purchase.station_id
+                    || candidate.product_id != purchase.product_id
+                    || *candidate_price != price
+                    || candidate.date > purchase.date
+                    || remaining[index].is_zero()
+                {
+                    continue;
+                }
+
+                let matched_quantity = remaining[index].min(unmatched_quantity);
+                remaining[index] -= matched_quantity;
+                unmatched_quantity -= matched_quantity;
+                matches.push(ReturnMatch {
+                    purchase: candidate,
+                    quantity: matched_quantity,
+                });
+            }
+
+            return_entries.push(ReturnEntry {
+                purchase,
+                quantity,
+                amount_before_tax: parse_decimal("amount before tax", &purchase.amount_before_tax)?
+                    .abs(),
+                matches,
+                unmatched_quantity,
+            });
+        }
+
+        let net_quantities = Self::net_quantities(&purchases, &return_entries);
+
+        Ok(Self {
+            returns: return_entries,
+            net_quantities,
+        })
+    }
+
+    pub fn unmatched(&self) -> impl Iterator<Item = &ReturnEntry<'a>> + '_ {
+        self.returns.iter().filter(|entry| !entry.is_matched())
+    }
+
+    fn net_quantities(
+        purchases: &[(&'a Purchase, Decimal, Decimal)],
+        returns: &[ReturnEntry<'a>],
+    ) -> Vec<NetQuantity<'a>> {
+        let mut net_quantities: Vec<NetQuantity> = vec![];
+        for entry in returns {
+            let purchase = entry.purchase;
+            match net_quantities.iter_mut().find(|net_quantity| {
+                net_quantity.station_id == purchase.station_id
+                    && net_quantity.product_id == purchase.product_id
+            }) {
+                Some(net_quantity) => net_quantity.returned += entry.quantity,
+                None => net_quantities.push(NetQuantity {
+                    station_id: &purchase.station_id,
+                    station_name: &purchase.station_name,
+                    product_id: &purchase.product_id,
+                    product_name: &purchase.product_name,
+                    purchased: purchases
+                        .iter()
+                        .filter(|(candidate, _, _)| {
+                            candidate.station_id == purchase.station_id
+                                && candidate.product_id == purchase.product_id
+                        })
+                        .map(|(_, _, quantity)| *quantity)
+                        .sum(),
+                    returned: entry.quantity,
+                }),
+            }
+        }
+        net_quantities
+            .sort_by(|a, b| (a.station_id, a.product_id).cmp(&(b.station_id, b.product_id)));
+        net_quantities
+    }
+}
+
+/// Writes the reconciliation into a workbook with a sheet listing every return and its matched
+/// purchases, and a sheet with the net quantities of the returned products.
+pub fn generate_returns_reconciliation_report(
+    reconciliation: &ReturnsReconciliation,
+) -> Result<Spreadsheet, Error> {
+    let mut spreadsheet = umya_spreadsheet::new_file();
+
+    let worksheet = spreadsheet.get_sheet_mut(&0).unwrap();
+    worksheet.set_name(RETURNS_SHEET_NAME);
+    for (column, header) in [
+        "退貨日期",
+        "站代號",
+        "站名",
+        "品號",
+        "品名",
+        "單價",
+        "退貨數量",
+        "退貨金額(未稅)",
+        "對應進貨日期",
+        "對應數量",
+        "未對應數量",
+        "狀態",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        worksheet
+            .get_cell_mut((column as u32 + 1, 1))
+            .set_value(header);
+    }
+    for (row, entry) in reconciliation.returns.iter().enumerate() {
+        let row = row as u32 + 2;
+        let purchase = entry.purchase;
+        let matched_dates = entry
+            .matches
+            .iter()
+            .map(|m| Ok(RocDate::try_from(m.purchase.date)?.to_string()))
+            .collect::<Result<Vec<_>, Error>>()?
+            .join("、");
+        let matched_quantity = entry.quantity - entry.unmatched_quantity;
+
+        worksheet
+            .get_cell_mut((1, row))
+            .set_value(RocDate::try_from(purchase.date)?.to_string());
+        worksheet
+            .get_cell_mut((2, row))
+            .set_value(&purchase.station_id);
+        worksheet
+            .get_cell_mut((3, row))
+            .set_value(&purchase.station_name);
+        worksheet
+            .get_cell_mut((4, row))
+            .set_value(&purchase.product_id);
+        worksheet
+            .get_cell_mut((5, row))
+            .set_value(&purchase.product_name);
+        worksheet.get_cell_mut((6, row)).set_value(&purchase.price);
+        worksheet
+            .get_cell_mut((7, row))
+            .set_value_number(to_cell_number(entry.quantity));
+        worksheet
+            .get_cell_mut((8, row))
+            .set_value_number(to_cell_number(entry.amount_before_tax));
+        worksheet.get_cell_mut((9, row)).set_value(matched_dates);
+        worksheet
+            .get_cell_mut((10, row))
+            .set_value_number(to_cell_number(matched_quantity));
+        worksheet
+            .get_cell_mut((11, row))
+            .set_value_number(to_cell_number(entry.unmatched_quantity));
+        worksheet.get_cell_mut((12, row)).set_value(entry.status());
+    }
+
+    let worksheet = spreadsheet
+        .new_sheet(NET_QUANTITIES_SHEET_NAME)
+        .expect("unable to add sheet");
+    for (column, header) in [
+        "站代號",
+        "站名",
+        "品號",
+        "品名",
+        "進貨數量",
+        "退貨數量",
+        "淨數量",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        worksheet
+            .get_cell_mut((column as u32 + 1, 1))
+            .set_value(header);
+    }
+    for (row, net_quantity) in reconciliation.net_quantities.iter().enumerate() {
+        let row = row as u32 + 2;
+        worksheet
+            .get_cell_mut((1, row))
+            .set_value(net_quantity.station_id);
+        worksheet
+            .get_cell_mut((2, row))
+            .set_value(net_quantity.station_name);
+        worksheet
+            .get_cell_mut((3, row))
+            .set_value(net_quantity.product_id);
+        worksheet
+            .get_cell_mut((4, row))
+            .set_value(net_quantity.product_name);
+        worksheet
+            .get_cell_mut((5, row))
+            .set_value_number(to_cell_number(net_quantity.purchased));
+        worksheet
+            .get_cell_mut((6, row))
+            .set_value_number(to_cell_number(net_quantity.returned));
+        worksheet
+            .get_cell_mut((7, row))
+            .set_value_number(to_cell_number(net_quantity.net()));
+    }
+
+    Ok(spreadsheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::Date;
+    use std::str::FromStr;
+
+    fn deserialize_purchase_list() -> PurchaseList {
+        let json = include_bytes!("../../../testdata/purchase-list.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_returns_reconciliation() {
+        let purchase_list = deserialize_purchase_list();
+        let reconciliation = ReturnsReconciliation::new(&purchase_list).unwrap();
+        assert_eq!(reconciliation.returns.len(), 44);
+        assert_eq!(reconciliation.unmatched().count(), 21);
+
+        // The sugar sticks returned at SSC58 reverse the purchase on the same day
+        let entry = &reconciliation.returns[0];
+        assert_eq!(entry.purchase.station_id, "SSC58");
+        assert_eq!(entry.quantity, decimal("3"));
+        assert_eq!(entry.amount_before_tax, decimal("125.01"));
+        assert!(entry.is_matched());
+        assert_eq!(entry.matches.len(), 1);
+        assert_eq!(
+            entry.matches[0].purchase.date,
+            Date::new(2025, 9, 1).unwrap()
+        );
+        assert_eq!(entry.matches[0].purchase.price, "41.6700");
+        assert_eq!(entry.matches[0].quantity, decimal("3"));
+
+        // No urea was bought at SSB86 before this return
+        let entry = &reconciliation.returns[1];
+        assert_eq!(entry.purchase.station_id, "SSB86");
+        assert!(entry.matches.is_empty());
+        assert_eq!(entry.unmatched_quantity, decimal("2187"));
+        assert_eq!(entry.status(), "無對應進貨");
+
+        // A return can be spread over several purchases
+        let entry = reconciliation
+            .returns
+            .iter()
+            .find(|entry| entry.purchase.station_id == "CCE19" && entry.quantity == decimal("8"))
+            .unwrap();
+        assert!(entry.is_matched());
+        assert_eq!(entry.matches.len(), 2);
+    }
+
+    #[test]
+    fn test_net_quantities() {
+        let purchase_list = deserialize_purchase_list();
+        let reconciliation = ReturnsReconciliation::new(&purchase_list).unwrap();
+        let net_quantity = reconciliation
+            .net_quantities
+            .iter()
+            .find(|net_quantity| {
+                net_quantity.station_id == "SSB86" && net_quantity.product_id == "A815"
+            })
+            .unwrap();
+        assert_eq!(net_quantity.purchased, Decimal::ZERO);
+        assert_eq!(net_quantity.returned, decimal("2187"));
+        assert_eq!(net_quantity.net(), decimal("-2187"));
+    }
+
+    #[test]
+    fn test_generate_returns_reconciliation_report() {
+        let purchase_list = deserialize_purchase_list();
+        let reconciliation = ReturnsReconciliation::new(&purchase_list).unwrap();
+        let spreadsheet = generate_returns_reconciliation_report(&reconciliation).unwrap();
+
+        let worksheet = spreadsheet.get_sheet_by_name(RETURNS_SHEET_NAME).unwrap();
+        assert_eq!(worksheet.get_value("A2"), "114/09/01");
+        assert_eq!(worksheet.get_value("B2"), "SSC58");
+        assert_eq!(worksheet.get_value("G2"), "3");
+        assert_eq!(worksheet.get_value("I2"), "114/09/01");
+        assert_eq!(worksheet.get_value("L2"), "已對應");
+        assert_eq!(worksheet.get_value("L3"), "無對應進貨");
+        assert_eq!(worksheet.get_highest_row(), 45);
+
+        let worksheet = spreadsheet
+            .get_sheet_by_name(NET_QUANTITIES_SHEET_NAME)
+            .unwrap();
+        assert_eq!(
+            worksheet.get_highest_row() as usize,
+            reconciliation.net_quantities.len() + 1
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use crate::Error;
+use crate::number::{parse_decimal, to_cell_number};
+use daily_necessities::{Purchase, PurchaseList};
+use roc_calendar::{RocDate, RocYearMonth};
+use rust_decimal::{Decimal, RoundingStrategy};
+use umya_spreadsheet::{HorizontalAlignmentValues, Spreadsheet};
+
+/// The 5% business tax rate.
+const BUSINESS_TAX_RATE: Decimal = Decimal::from_parts(5, 0, 0, false, 2);
+const HEADER_ROW: u32 = 4;
+
+#[derive(Debug)]
+pub struct SettlementLine<'a> {
+    pub purchase: &'a Purchase,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub amount_before_tax: Decimal,
+}
+
+/// The purchases of a supplier within a calendar month.
+#[derive(Debug)]
+pub struct SettlementStatement<'a> {
+    pub sup_id: &'a str,
+    pub sup_name: Option<&'a str>,
+    pub month: RocYearMonth,
+    pub lines: Vec<SettlementLine<'a>>,
+}
+
+impl<'a> SettlementStatement<'a> {
+    /// Groups the purchases by supplier and month into one statement each, ordered by month and
+    /// supplier ID. Purchases without a supplier are left out.
+    pub fn from_purchase_list(purchase_list: &'a PurchaseList) -> Result<Vec<Self>, Error> {
+        let mut statements: Vec<SettlementStatement> = vec![];
+        for purchase in purchase_list.iter() {
+            let Some(sup_id) = purchase.sup_id.as_deref().filter(|id| !id.is_empty()) else {
+                continue;
+            };
+            let line = SettlementLine {
+                purchase,
+                price: parse_decimal("price", &purchase.price)?,
+                quantity: parse_decimal("quantity", &purchase.quantity)?,
+                amount_before_tax: parse_decimal("amount before tax", &purchase.amount_before_tax)?,
+            };
+
+            let month = RocYearMonth::try_from(purchase.date)?;
+            match statements
+                .iter_mut()
+                .find(|statement| statement.sup_id == sup_id && statement.month == month)
+            {
+                Some(statement) => {
+                    statement.sup_name = statement.sup_name.or(purchase.sup_name.as_deref());
+                    statement.lines.push(line);
+                }
+                None => statements.push(SettlementStatement {
+                    sup_id,
+                    sup_name: purchase.sup_name.as_deref(),
+                    month,
+                    lines: vec![line],
+                }),
+            }
+        }
+
+        for statement in statements.iter_mut() {
+            statement.lines.sort_by(|a, b| {
+                (&a.purchase.station_id, a.purchase.date)
+                    .cmp(&(&b.purchase.station_id, b.purchase.date))
+            });
+        }
+        statements.sort_by_key(|statement| {
+            (
+                statement.month,
+                statement.sup_id.parse::<u32>().unwrap_or(u32::MAX),
+                statement.sup_id,
+            )
+        });
+
+        Ok(statements)
+    }
+
+    /// The lines grouped by station, in the order of the lines.
+    pub fn stations(&self) -> Vec<(&'a str, &'a str, Vec<&SettlementLine<'a>>)> {
+        let mut stations: Vec<(&str, &str, Vec<&SettlementLine>)> = vec![];
+        for line in self.lines.iter() {
+            match stations.last_mut() {
+                Some((station_id, _, lines)) if *station_id == line.purchase.station_id => {
+                    lines.push(line)
+                }
+                _ => stations.push((
+                    &line.purchase.station_id,
+                    &line.purchase.station_name,
+                    vec![line],
+                )),
+            }
+        }
+        stations
+    }
+
+    /// The pre-tax subtotal, rounded to whole dollars.
+    pub fn subtotal(&self) -> Decimal {
+        self.lines
+            .iter()
+            .map(|line| line.amount_before_tax)
+            .sum::<Decimal>()
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    /// The 5% business tax on the subtotal, rounded to whole dollars.
+    pub fn tax(&self) -> Decimal {
+        (self.subtotal() * BUSINESS_TAX_RATE)
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.subtotal() + self.tax()
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "{}{}結算表.xlsx",
+            self.month,
+            self.sup_name.unwrap_or(self.sup_id)
+        )
+    }
+}
+
+/// Writes the statement of a supplier into a workbook, listing the line items per station
+/// followed by the pre-tax subtotal, the business tax and the total.
+pub fn generate_settlement_statement_report(
+    statement: &SettlementStatement,
+) -> Result<Spreadsheet, Error> {
+    let mut spreadsheet = umya_spreadsheet::new_file();
+    let worksheet = spreadsheet.get_sheet_mut(&0).unwrap();
+    worksheet.set_name(format!(
+        "{}-{:02}",
+        statement.month.roc_year(),
+        statement.month.month()
+    ));
+
+    // Set the title and the settlement period
+    worksheet
+        .get_cell_mut("A1")
+        .set_value(format!(
+            "{}　{} 結算表",
+            statement.sup_name.unwrap_or(statement.sup_id),
+            statement.month
+        ))
+        .get_style_mut()
+        .get_font_mut()
+        .set_bold(true)
+        .set_size(16.);
+    worksheet.add_merge_cells("A1:H1");
+    worksheet
+        .get_style_mut("A1")
+        .get_alignment_mut()
+        .set_horizontal(HorizontalAlignmentValues::Center);
+    worksheet.get_cell_mut("A2").set_value(format!(
+        "結算期間：{} ~ {}",
+        statement.month.first_day(),
+        statement.month.last_day()
+    ));
+    worksheet
+        .get_cell_mut("A3")
+        .set_value(format!("供應商代號：{}", statement.sup_id));
+
+    for (column, header) in [
+        "站代號",
+        "站名",
+        "日期",
+        "品號",
+        "品名",
+        "單價",
+        "數量",
+        "未稅金額",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        worksheet
+            .get_cell_mut((column as u32 + 1, HEADER_ROW))
+            .set_value(header)
+            .get_style_mut()
+            .get_font_mut()
+            .set_bold(true);
+    }
+
+    // Set the line items of each station, followed by the subtotal of the station
+    let mut row = HEADER_ROW + 1;
+    for (station_id, station_name, lines) in statement.stations() {
+        for line in lines.iter() {
+            let purchase = line.purchase;
+            worksheet.get_cell_mut((1, row)).set_value(station_id);
+            worksheet.get_cell_mut((2, row)).set_value(station_name);
+            worksheet
+                .get_cell_mut((3, row))
+                .set_value(RocDate::try_from(purchase.date)?.to_string());
+            worksheet
+                .get_cell_mut((4, row))
+                .set_value(&purchase.product_id);
+            worksheet
+                .get_cell_mut((5, row))
+                .set_value(&purchase.product_name);
+            worksheet
+                .get_cell_mut((6, row))
+                .set_value_number(to_cell_number(line.price));
+            worksheet
+                .get_cell_mut((7, row))
+                .set_value_number(to_cell_number(line.quantity));
+            worksheet
+                .get_cell_mut((8, row))
+                .set_value_number(to_cell_number(line.amount_before_tax));
+            row += 1;
+        }
+
+        worksheet
+            .get_cell_mut((5, row))
+            .set_value(format!("{}站小計", station_name));
+        worksheet
+            .get_cell_mut((8, row))
+            .set_value_number(to_cell_number(
+                lines.iter().map(|line| line.amount_before_tax).sum(),
+            ));
+        row += 1;
+    }
+
+    // Set the totals
+    row += 1;
+    for (label, amount) in [
+        ("未稅合計", statement.subtotal()),
+        ("營業稅(5%)", statement.tax()),
+        ("總計", statement.total()),
+    ] {
+        worksheet
+            .get_cell_mut((7, row))
+            .set_value(label)
+            .get_style_mut()
+            .get_font_mut()
+            .set_bold(true);
+        worksheet
+            .get_cell_mut((8, row))
+            .set_value_number(to_cell_number(amount));
+        row += 1;
+    }
+
+    for (column, width) in [("A", 10.), ("B", 12.), ("C", 12.), ("D", 10.), ("E", 36.)] {
+        worksheet.get_column_dimension_mut(column).set_width(width);
+    }
+    for column in ["F", "G", "H"] {
+        worksheet.get_column_dimension_mut(column).set_width(14.);
+    }
+
+    Ok(spreadsheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn deserialize_purchase_list() -> PurchaseList {
+        let json = include_bytes!("../../../testdata/purchase-list.json");
+        serde_json::from_slice(json).unwrap()
+    }
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_settlement_statements() {
+        let purchase_list = deserialize_purchase_list();
+        let statements = SettlementStatement::from_purchase_list(&purchase_list).unwrap();
+        assert_eq!(statements.len(), 10);
+        assert_eq!(
+            statements
+                .iter()
+                .map(|statement| statement.sup_id)
+                .collect::<Vec<_>>(),
+            vec!["1", "2", "3", "4", "5", "7", "9", "10", "13", "14"]
+        );
+
+        let statement = statements
+            .iter()
+            .find(|statement| statement.sup_id == "10")
+            .unwrap();
+        assert_eq!(statement.sup_name, Some("標案大面"));
+        assert_eq!(statement.month, RocYearMonth::new(114, 9).unwrap());
+        assert_eq!(statement.lines.len(), 40);
+        assert_eq!(statement.stations().len(), 36);
+        assert_eq!(statement.subtotal(), decimal("530468"));
+        assert_eq!(statement.tax(), decimal("26523"));
+        assert_eq!(statement.total(), decimal("556991"));
+        assert_eq!(statement.file_name(), "114年09月標案大面結算表.xlsx");
+    }
+
+    #[test]
+    fn test_generate_settlement_statement_report() {
+        let purchase_list = deserialize_purchase_list();
+        let statements = SettlementStatement::from_purchase_list(&purchase_list).unwrap();
+        let statement = statements
+            .iter()
+            .find(|statement| statement.sup_id == "7")
+            .unwrap();
+        let spreadsheet = generate_settlement_statement_report(statement).unwrap();
+
+        let worksheet = spreadsheet.get_sheet(&0).unwrap();
+        assert_eq!(worksheet.get_name(), "114-09");
+        assert_eq!(worksheet.get_value("A1"), "中油　114年09月 結算表");
+        assert_eq!(worksheet.get_value("A2"), "結算期間：114/09/01 ~ 114/09/30");
+        assert_eq!(worksheet.get_value("H5"), "1599.9996");
+        assert_eq!(
+            worksheet.get_value("E6"),
+            format!("{}站小計", worksheet.get_value("B5"))
+        );
+        assert_eq!(worksheet.get_value("G8"), "未稅合計");
+        assert_eq!(worksheet.get_value("H8"), "1600");
+        assert_eq!(worksheet.get_value("H9"), "80");
+        assert_eq!(worksheet.get_value("H10"), "1680");
+    }
+}
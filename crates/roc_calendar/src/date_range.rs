@@ -0,0 +1,98 @@
+use jiff::ToSpan;
+use jiff::civil::Date;
+
+/// How long a date range fetched at once is, to keep each request within the timeout.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChunkSize {
+    Week,
+    Month,
+}
+
+/// Splits the inclusive date range into consecutive inclusive chunks. Week chunks span 7 days
+/// from the start date, while month chunks follow the calendar months.
+pub fn split_date_range(
+    start_date: &Date,
+    end_date: &Date,
+    chunk_size: ChunkSize,
+) -> Vec<(Date, Date)> {
+    let mut chunks = vec![];
+    let mut chunk_start = *start_date;
+    while chunk_start <= *end_date {
+        let chunk_end = match chunk_size {
+            ChunkSize::Week => chunk_start.saturating_add(6.days()),
+            ChunkSize::Month => chunk_start.last_of_month(),
+        }
+        .min(*end_date);
+        chunks.push((chunk_start, chunk_end));
+
+        match chunk_end.tomorrow() {
+            Ok(next_start) => chunk_start = next_start,
+            Err(_) => break,
+        }
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_date_range_by_month() {
+        let chunks = split_date_range(
+            &Date::new(2025, 1, 15).unwrap(),
+            &Date::new(2025, 3, 10).unwrap(),
+            ChunkSize::Month,
+        );
+        assert_eq!(
+            chunks,
+            vec![
+                (
+                    Date::new(2025, 1, 15).unwrap(),
+                    Date::new(2025, 1, 31).unwrap()
+                ),
+                (
+                    Date::new(2025, 2, 1).unwrap(),
+                    Date::new(2025, 2, 28).unwrap()
+                ),
+                (
+                    Date::new(2025, 3, 1).unwrap(),
+                    Date::new(2025, 3, 10).unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_date_range_by_week() {
+        let chunks = split_date_range(
+            &Date::new(2025, 9, 24).unwrap(),
+            &Date::new(2025, 10, 10).unwrap(),
+            ChunkSize::Week,
+        );
+        assert_eq!(
+            chunks,
+            vec![
+                (
+                    Date::new(2025, 9, 24).unwrap(),
+                    Date::new(2025, 9, 30).unwrap()
+                ),
+                (
+                    Date::new(2025, 10, 1).unwrap(),
+                    Date::new(2025, 10, 7).unwrap()
+                ),
+                (
+                    Date::new(2025, 10, 8).unwrap(),
+                    Date::new(2025, 10, 10).unwrap()
+                ),
+            ]
+        );
+
+        let date = Date::new(2025, 9, 24).unwrap();
+        assert_eq!(
+            split_date_range(&date, &date, ChunkSize::Week),
+            vec![(date, date)]
+        );
+        assert!(split_date_range(&date.tomorrow().unwrap(), &date, ChunkSize::Week).is_empty());
+    }
+}
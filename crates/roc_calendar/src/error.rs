@@ -0,0 +1,17 @@
+use jiff::civil::Date;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    JiffError(#[from] jiff::Error),
+
+    #[error("date parse error: {0}")]
+    ParseDateError(String),
+
+    #[error("{0} is before the first year of the ROC calendar")]
+    BeforeRocEra(Date),
+
+    #[error("calendar parse error: {0}")]
+    ParseCalendarError(String),
+}
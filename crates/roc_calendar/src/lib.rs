@@ -0,0 +1,9 @@
+mod date_range;
+mod error;
+mod roc_date;
+mod working_days;
+
+pub use crate::date_range::{ChunkSize, split_date_range};
+pub use crate::error::Error;
+pub use crate::roc_date::{RocDate, RocDateDisplay, RocDateFormat, RocYearMonth};
+pub use crate::working_days::{CalendarEntry, DayKind, WorkingDayCalendar};
@@ -0,0 +1,350 @@
+use crate::Error;
+use jiff::civil::Date;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The Gregorian year before the first year of the ROC calendar (民國元年 is 1912).
+const ROC_YEAR_OFFSET: i16 = 1911;
+
+/// The textual representations of ROC dates found in TSCRED, the daily necessities backstage and
+/// the report templates.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RocDateFormat {
+    /// `114/09/30`
+    #[default]
+    Slash,
+    /// `0114/9/6`
+    PaddedYearSlash,
+    /// `114-09-30`
+    Dash,
+    /// `114.09.30`
+    Dot,
+    /// `1140930`
+    Compact,
+    /// `114年09月30日`
+    Chinese,
+}
+
+/// A date in the ROC (民國) calendar.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RocDate(Date);
+
+impl RocDate {
+    pub fn new(roc_year: i16, month: i8, day: i8) -> Result<Self, Error> {
+        let year = roc_year
+            .checked_add(ROC_YEAR_OFFSET)
+            .filter(|_| roc_year >= 1)
+            .ok_or_else(|| {
+                Error::ParseDateError(format!("{}/{:02}/{:02}", roc_year, month, day))
+            })?;
+        Ok(Self(Date::new(year, month, day)?))
+    }
+
+    pub fn roc_year(&self) -> i16 {
+        self.0.year() - ROC_YEAR_OFFSET
+    }
+
+    pub fn month(&self) -> i8 {
+        self.0.month()
+    }
+
+    pub fn day(&self) -> i8 {
+        self.0.day()
+    }
+
+    pub fn date(&self) -> Date {
+        self.0
+    }
+
+    pub fn year_month(&self) -> RocYearMonth {
+        RocYearMonth {
+            roc_year: self.roc_year(),
+            month: self.month(),
+        }
+    }
+
+    /// Returns a value which displays the date in the given format.
+    pub fn format(&self, format: RocDateFormat) -> RocDateDisplay {
+        RocDateDisplay {
+            date: *self,
+            format,
+        }
+    }
+
+    fn parse_with_separator(s: &str, separator: char) -> Result<Self, Error> {
+        let fragments = s.split(separator).collect::<Vec<&str>>();
+        if fragments.len() != 3 {
+            return Err(Error::ParseDateError(s.to_string()));
+        }
+        Self::new(
+            parse_number(fragments[0], s)?,
+            parse_number(fragments[1], s)?,
+            parse_number(fragments[2], s)?,
+        )
+    }
+
+    fn parse_compact(s: &str) -> Result<Self, Error> {
+        // The year takes 1 to 3 digits, followed by 2 digits each for month and day
+        if !(5..=7).contains(&s.len()) {
+            return Err(Error::ParseDateError(s.to_string()));
+        }
+        let (year, month_day) = s.split_at(s.len() - 4);
+        let (month, day) = month_day.split_at(2);
+        Self::new(
+            parse_number(year, s)?,
+            parse_number(month, s)?,
+            parse_number(day, s)?,
+        )
+    }
+
+    fn parse_chinese(s: &str) -> Result<Self, Error> {
+        let (year, rest) = s
+            .split_once('年')
+            .ok_or_else(|| Error::ParseDateError(s.to_string()))?;
+        let (month, rest) = rest
+            .split_once('月')
+            .ok_or_else(|| Error::ParseDateError(s.to_string()))?;
+        let day = rest
+            .strip_suffix('日')
+            .ok_or_else(|| Error::ParseDateError(s.to_string()))?;
+        Self::new(
+            parse_number(year, s)?,
+            parse_number(month, s)?,
+            parse_number(day, s)?,
+        )
+    }
+}
+
+fn parse_number<T: FromStr>(fragment: &str, s: &str) -> Result<T, Error> {
+    if fragment.is_empty() || !fragment.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::ParseDateError(s.to_string()));
+    }
+    fragment
+        .parse()
+        .map_err(|_| Error::ParseDateError(s.to_string()))
+}
+
+impl TryFrom<Date> for RocDate {
+    type Error = Error;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        if date.year() <= ROC_YEAR_OFFSET {
+            return Err(Error::BeforeRocEra(date));
+        }
+        Ok(Self(date))
+    }
+}
+
+impl From<RocDate> for Date {
+    fn from(date: RocDate) -> Self {
+        date.0
+    }
+}
+
+impl FromStr for RocDate {
+    type Err = Error;
+
+    /// Parses a date in any of the [`RocDateFormat`]s.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.contains('年') {
+            Self::parse_chinese(s)
+        } else if s.contains('/') {
+            Self::parse_with_separator(s, '/')
+        } else if s.contains('-') {
+            Self::parse_with_separator(s, '-')
+        } else if s.contains('.') {
+            Self::parse_with_separator(s, '.')
+        } else {
+            Self::parse_compact(s)
+        }
+    }
+}
+
+impl Display for RocDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(RocDateFormat::default()).fmt(f)
+    }
+}
+
+/// Displays a [`RocDate`] in a [`RocDateFormat`].
+pub struct RocDateDisplay {
+    date: RocDate,
+    format: RocDateFormat,
+}
+
+impl Display for RocDateDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = (self.date.roc_year(), self.date.month(), self.date.day());
+        match self.format {
+            RocDateFormat::Slash => write!(f, "{}/{:02}/{:02}", year, month, day),
+            RocDateFormat::PaddedYearSlash => write!(f, "{:04}/{}/{}", year, month, day),
+            RocDateFormat::Dash => write!(f, "{}-{:02}-{:02}", year, month, day),
+            RocDateFormat::Dot => write!(f, "{}.{:02}.{:02}", year, month, day),
+            RocDateFormat::Compact => write!(f, "{}{:02}{:02}", year, month, day),
+            RocDateFormat::Chinese => write!(f, "{}年{:02}月{:02}日", year, month, day),
+        }
+    }
+}
+
+/// A month in the ROC calendar, displayed as `114年09月`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RocYearMonth {
+    roc_year: i16,
+    month: i8,
+}
+
+impl RocYearMonth {
+    pub fn new(roc_year: i16, month: i8) -> Result<Self, Error> {
+        Ok(RocDate::new(roc_year, month, 1)?.year_month())
+    }
+
+    pub fn roc_year(&self) -> i16 {
+        self.roc_year
+    }
+
+    pub fn month(&self) -> i8 {
+        self.month
+    }
+
+    pub fn first_day(&self) -> RocDate {
+        RocDate::new(self.roc_year, self.month, 1).expect("invalid ROC year month")
+    }
+
+    pub fn last_day(&self) -> RocDate {
+        RocDate(self.first_day().0.last_of_month())
+    }
+}
+
+impl TryFrom<Date> for RocYearMonth {
+    type Error = Error;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        Ok(RocDate::try_from(date)?.year_month())
+    }
+}
+
+impl FromStr for RocYearMonth {
+    type Err = Error;
+
+    /// Parses a month formatted as `114年09月`, `114/09` or `114-09`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (year, month) = match s.split_once('年') {
+            Some((year, rest)) => (
+                year,
+                rest.strip_suffix('月')
+                    .ok_or_else(|| Error::ParseDateError(s.to_string()))?,
+            ),
+            None => s
+                .split_once(['/', '-'])
+                .ok_or_else(|| Error::ParseDateError(s.to_string()))?,
+        };
+        Self::new(parse_number(year, s)?, parse_number(month, s)?)
+    }
+}
+
+impl Display for RocYearMonth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}年{:02}月", self.roc_year, self.month)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_roc_date() {
+        let expected = Date::new(2025, 9, 30).unwrap();
+        for s in [
+            "114-09-30",
+            "114/09/30",
+            "0114/9/30",
+            "114.09.30",
+            "1140930",
+            "114年09月30日",
+            " 114/9/30 ",
+        ] {
+            assert_eq!(s.parse::<RocDate>().unwrap().date(), expected, "{}", s);
+        }
+
+        let date = "0114/9/6".parse::<RocDate>().unwrap();
+        assert_eq!(date.date(), Date::new(2025, 9, 6).unwrap());
+
+        let date = "990821".parse::<RocDate>().unwrap();
+        assert_eq!(date.date(), Date::new(2010, 8, 21).unwrap());
+
+        let date = "114-09-31".parse::<RocDate>();
+        assert!(date.is_err_and(|e| matches!(e, Error::JiffError(_))));
+
+        for s in [
+            "114-09-30 15:25:00",
+            "114-09",
+            "1",
+            "",
+            "+114/09/30",
+            "0/01/01",
+        ] {
+            let date = s.parse::<RocDate>();
+            assert!(
+                date.is_err_and(|e| matches!(e, Error::ParseDateError(_))),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_roc_date() {
+        let date = RocDate::new(114, 9, 6).unwrap();
+        assert_eq!(date.to_string(), "114/09/06");
+        assert_eq!(date.format(RocDateFormat::Slash).to_string(), "114/09/06");
+        assert_eq!(
+            date.format(RocDateFormat::PaddedYearSlash).to_string(),
+            "0114/9/6"
+        );
+        assert_eq!(date.format(RocDateFormat::Dash).to_string(), "114-09-06");
+        assert_eq!(date.format(RocDateFormat::Dot).to_string(), "114.09.06");
+        assert_eq!(date.format(RocDateFormat::Compact).to_string(), "1140906");
+        assert_eq!(
+            date.format(RocDateFormat::Chinese).to_string(),
+            "114年09月06日"
+        );
+    }
+
+    #[test]
+    fn test_roc_year_boundaries() {
+        let date = RocDate::try_from(Date::new(1912, 1, 1).unwrap()).unwrap();
+        assert_eq!(date.roc_year(), 1);
+        assert_eq!(date.format(RocDateFormat::Compact).to_string(), "10101");
+        assert_eq!("10101".parse::<RocDate>().unwrap(), date);
+
+        let date = RocDate::try_from(Date::new(1911, 12, 31).unwrap());
+        assert!(date.is_err_and(|e| matches!(e, Error::BeforeRocEra(_))));
+
+        let date = RocDate::try_from(Date::new(2011, 1, 1).unwrap()).unwrap();
+        assert_eq!(date.roc_year(), 100);
+        assert_eq!(date.to_string(), "100/01/01");
+        assert_eq!(Date::from(date), Date::new(2011, 1, 1).unwrap());
+
+        assert!(RocDate::new(0, 12, 31).is_err());
+    }
+
+    #[test]
+    fn test_roc_year_month() {
+        let year_month = "114年09月".parse::<RocYearMonth>().unwrap();
+        assert_eq!(year_month.roc_year(), 114);
+        assert_eq!(year_month.month(), 9);
+        assert_eq!(year_month.to_string(), "114年09月");
+        assert_eq!(year_month.first_day().to_string(), "114/09/01");
+        assert_eq!(year_month.last_day().to_string(), "114/09/30");
+        assert_eq!("114/9".parse::<RocYearMonth>().unwrap(), year_month);
+        assert_eq!(
+            RocYearMonth::try_from(Date::new(2025, 9, 15).unwrap()).unwrap(),
+            year_month
+        );
+        assert!("114年13月".parse::<RocYearMonth>().is_err());
+        assert!("114年09".parse::<RocYearMonth>().is_err());
+    }
+}
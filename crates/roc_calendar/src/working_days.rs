@@ -0,0 +1,289 @@
+use crate::{Error, RocDate};
+use jiff::civil::{Date, Weekday};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+const BUNDLED_DAYS_OFF: &str = include_str!("../data/taiwan-days-off.csv");
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DayKind {
+    /// A weekday without work, such as a national holiday or a typhoon day off (放假).
+    DayOff,
+    /// A weekend day with work, making up for a bridge holiday (補班).
+    WorkingDay,
+}
+
+impl DayKind {
+    fn name(&self) -> &'static str {
+        match self {
+            DayKind::DayOff => "放假",
+            DayKind::WorkingDay => "補班",
+        }
+    }
+}
+
+impl FromStr for DayKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "放假" => Ok(DayKind::DayOff),
+            "補班" => Ok(DayKind::WorkingDay),
+            _ => Err(Error::ParseCalendarError(s.to_string())),
+        }
+    }
+}
+
+/// A day which differs from the usual Monday to Friday working week.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CalendarEntry {
+    pub date: Date,
+    pub kind: DayKind,
+    pub name: String,
+}
+
+impl FromStr for CalendarEntry {
+    type Err = Error;
+
+    /// Parses a line formatted as `114/10/10,放假,國慶日`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(',').map(str::trim);
+        let (Some(date), Some(kind)) = (fields.next(), fields.next()) else {
+            return Err(Error::ParseCalendarError(s.to_string()));
+        };
+        let name = fields.next().unwrap_or_default().to_string();
+        if fields.next().is_some() {
+            return Err(Error::ParseCalendarError(s.to_string()));
+        }
+
+        Ok(CalendarEntry {
+            date: date.parse::<RocDate>()?.date(),
+            kind: kind.parse()?,
+            name,
+        })
+    }
+}
+
+impl Display for CalendarEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match RocDate::try_from(self.date) {
+            Ok(date) => write!(f, "{},{},{}", date, self.kind.name(), self.name),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+/// The Taiwanese working-day calendar: Monday to Friday, except for the national holidays and
+/// days off, plus the make-up working days on weekends.
+#[derive(Clone, Debug, Default)]
+pub struct WorkingDayCalendar {
+    entries: BTreeMap<Date, CalendarEntry>,
+    /// The years of the bundled calendar, beyond which the holidays are unknown.
+    bundled_years: Option<RangeInclusive<i16>>,
+}
+
+impl WorkingDayCalendar {
+    /// The calendar with the holidays and make-up working days announced by the government.
+    pub fn bundled() -> Self {
+        let mut calendar: WorkingDayCalendar = BUNDLED_DAYS_OFF
+            .parse()
+            .expect("unable to parse bundled days off");
+        let first_year = calendar.entries.keys().next().map(|date| date.year());
+        let last_year = calendar.entries.keys().next_back().map(|date| date.year());
+        calendar.bundled_years = first_year.zip(last_year).map(|(first, last)| first..=last);
+        calendar
+    }
+
+    /// Whether the holidays of the year of the date are bundled. Only the weekends are days off
+    /// in the other years, so the working days counted in them may be wrong.
+    pub fn covers(&self, date: &Date) -> bool {
+        self.bundled_years
+            .as_ref()
+            .is_some_and(|years| years.contains(&date.year()))
+    }
+
+    /// Adds the entries of a calendar file, such as the typhoon days off entered by the user.
+    /// Entries override any existing entry of the same date.
+    pub fn extend_from_str(&mut self, s: &str) -> Result<(), Error> {
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            self.insert(line.parse()?);
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, entry: CalendarEntry) {
+        self.entries.insert(entry.date, entry);
+    }
+
+    pub fn get(&self, date: &Date) -> Option<&CalendarEntry> {
+        self.entries.get(date)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CalendarEntry> + '_ {
+        self.entries.values()
+    }
+
+    pub fn is_working_day(&self, date: &Date) -> bool {
+        match self.entries.get(date) {
+            Some(entry) => entry.kind == DayKind::WorkingDay,
+            None => !matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday),
+        }
+    }
+
+    /// Returns the date itself if it is a working day, otherwise the next working day.
+    pub fn next_working_day_from(&self, date: &Date) -> Date {
+        let mut date = *date;
+        while !self.is_working_day(&date) {
+            date = date.tomorrow().expect("date out of range");
+        }
+        date
+    }
+
+    /// Returns the `n`th working day after the date.
+    pub fn add_working_days(&self, date: &Date, n: u32) -> Date {
+        let mut date = *date;
+        for _ in 0..n {
+            date = self.next_working_day_from(&date.tomorrow().expect("date out of range"));
+        }
+        date
+    }
+
+    /// Counts the working days after `start_date` up to and including `end_date`, which is
+    /// negative if `end_date` is before `start_date`.
+    pub fn working_days_between(&self, start_date: &Date, end_date: &Date) -> i32 {
+        if end_date < start_date {
+            return -self.working_days_between(end_date, start_date);
+        }
+
+        let mut count = 0;
+        let mut date = *start_date;
+        while date < *end_date {
+            date = date.tomorrow().expect("date out of range");
+            if self.is_working_day(&date) {
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl FromStr for WorkingDayCalendar {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut calendar = WorkingDayCalendar::default();
+        calendar.extend_from_str(s)?;
+        Ok(calendar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i16, month: i8, day: i8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_calendar() {
+        let calendar = WorkingDayCalendar::bundled();
+        assert!(calendar.iter().count() > 0);
+        assert!(calendar.is_working_day(&date(2025, 10, 9)));
+        assert!(!calendar.is_working_day(&date(2025, 10, 10)));
+        assert!(!calendar.is_working_day(&date(2025, 10, 11)));
+        assert!(calendar.is_working_day(&date(2025, 2, 8)));
+        assert_eq!(calendar.get(&date(2025, 10, 10)).unwrap().name, "國慶日");
+
+        // The Lunar New Year of 116
+        assert!(calendar.is_working_day(&date(2027, 2, 3)));
+        assert!(!calendar.is_working_day(&date(2027, 2, 4)));
+        assert!(!calendar.is_working_day(&date(2027, 2, 10)));
+        assert!(calendar.is_working_day(&date(2027, 2, 11)));
+
+        assert!(calendar.covers(&date(2025, 1, 1)));
+        assert!(calendar.covers(&date(2027, 12, 31)));
+        assert!(!calendar.covers(&date(2028, 1, 3)));
+        assert!(!calendar.covers(&date(2024, 12, 31)));
+    }
+
+    #[test]
+    fn test_add_working_days() {
+        let calendar = WorkingDayCalendar::bundled();
+
+        // Thursday before National Day and the weekend
+        assert_eq!(
+            calendar.add_working_days(&date(2025, 10, 9), 1),
+            date(2025, 10, 13)
+        );
+        assert_eq!(
+            calendar.add_working_days(&date(2025, 10, 9), 5),
+            date(2025, 10, 17)
+        );
+        assert_eq!(
+            calendar.add_working_days(&date(2025, 10, 9), 0),
+            date(2025, 10, 9)
+        );
+
+        // The make-up working day after the Lunar New Year
+        assert_eq!(
+            calendar.add_working_days(&date(2025, 2, 7), 1),
+            date(2025, 2, 8)
+        );
+
+        assert_eq!(
+            calendar.next_working_day_from(&date(2025, 10, 10)),
+            date(2025, 10, 13)
+        );
+    }
+
+    #[test]
+    fn test_working_days_between() {
+        let calendar = WorkingDayCalendar::bundled();
+        assert_eq!(
+            calendar.working_days_between(&date(2025, 10, 9), &date(2025, 10, 17)),
+            5
+        );
+        assert_eq!(
+            calendar.working_days_between(&date(2025, 10, 17), &date(2025, 10, 9)),
+            -5
+        );
+        assert_eq!(
+            calendar.working_days_between(&date(2025, 10, 9), &date(2025, 10, 9)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_user_days_off() {
+        let mut calendar = WorkingDayCalendar::bundled();
+        calendar
+            .extend_from_str("# 颱風停班\n\n114/10/08,放假,颱風停班\n")
+            .unwrap();
+        assert!(!calendar.is_working_day(&date(2025, 10, 8)));
+
+        // The days off of the user never extend the years covered
+        calendar.extend_from_str("117/07/10,放假,颱風停班").unwrap();
+        assert!(!calendar.covers(&date(2028, 7, 10)));
+        assert_eq!(
+            calendar.add_working_days(&date(2025, 10, 7), 1),
+            date(2025, 10, 9)
+        );
+
+        let entry = calendar.get(&date(2025, 10, 8)).unwrap();
+        assert_eq!(entry.to_string(), "114/10/08,放假,颱風停班");
+
+        assert!(calendar.extend_from_str("114/10/08,停班").is_err());
+        assert!(calendar.extend_from_str("114/10/32,放假").is_err());
+        assert!(
+            calendar
+                .extend_from_str("114/10/08,放假,颱風,停班")
+                .is_err()
+        );
+    }
+}
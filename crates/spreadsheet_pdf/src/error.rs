@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    PdfError(#[from] printpdf::Error),
+
+    #[error(transparent)]
+    FontParseError(#[from] ttf_parser::FaceParsingError),
+
+    #[error("font collections cannot be embedded, use a single TrueType font")]
+    FontCollection,
+
+    #[error("spreadsheet has no worksheets")]
+    MissingWorksheet,
+}
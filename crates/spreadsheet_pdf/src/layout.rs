@@ -0,0 +1,486 @@
+use umya_spreadsheet::{
+    Cell, HorizontalAlignmentValues, OrientationValues, VerticalAlignmentValues, Worksheet,
+};
+
+/// The size of an A4 page in millimetres.
+const A4_WIDTH: f32 = 210.;
+const A4_HEIGHT: f32 = 297.;
+
+/// The widths and heights Excel uses when a worksheet does not set its own.
+const DEFAULT_COLUMN_WIDTH: f64 = 8.43;
+const DEFAULT_ROW_HEIGHT: f64 = 15.;
+
+/// The pages of a worksheet, with positions in millimetres from the top left corner of the page.
+pub(crate) struct Layout {
+    pub page_width: f32,
+    pub page_height: f32,
+    pub pages: Vec<Page>,
+}
+
+#[derive(Default)]
+pub(crate) struct Page {
+    pub texts: Vec<TextBox>,
+    pub borders: Vec<BorderLine>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// The value of a cell, or of the merged cells it is the top left corner of.
+pub(crate) struct TextBox {
+    pub rect: Rect,
+    pub lines: Vec<String>,
+    /// The font size in points, already scaled to fit the page.
+    pub font_size: f32,
+    pub bold: bool,
+    pub horizontal: Horizontal,
+    pub vertical: Vertical,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Horizontal {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Vertical {
+    Top,
+    Center,
+    Bottom,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct BorderLine {
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+    /// The thickness in points.
+    pub thickness: f32,
+}
+
+/// A range of merged cells, by their column and row numbers.
+struct MergedRange {
+    start_column: u32,
+    start_row: u32,
+    end_column: u32,
+    end_row: u32,
+}
+
+impl MergedRange {
+    fn contains(&self, column: u32, row: u32) -> bool {
+        (self.start_column..=self.end_column).contains(&column)
+            && (self.start_row..=self.end_row).contains(&row)
+    }
+}
+
+/// Where a visible row is placed.
+#[derive(Copy, Clone)]
+struct RowPosition {
+    page: usize,
+    y: f32,
+    height: f32,
+}
+
+/// Lays out the used range of the worksheet on A4 pages, scaled down to fit the width of the page
+/// and broken into pages between rows.
+pub(crate) fn layout_worksheet(worksheet: &Worksheet) -> Layout {
+    let (page_width, page_height) = match worksheet.get_page_setup().get_orientation() {
+        OrientationValues::Landscape => (A4_HEIGHT, A4_WIDTH),
+        _ => (A4_WIDTH, A4_HEIGHT),
+    };
+    let margins = worksheet.get_page_margins();
+    let left = inches_to_mm(*margins.get_left());
+    let right = inches_to_mm(*margins.get_right());
+    let top = inches_to_mm(*margins.get_top());
+    let bottom = inches_to_mm(*margins.get_bottom());
+
+    let merged_ranges = merged_ranges(worksheet);
+    let (last_column, last_row) = used_range(worksheet, &merged_ranges);
+
+    let widths = (1..=last_column)
+        .map(|column| column_width(worksheet, column))
+        .collect::<Vec<_>>();
+    let content_width = widths.iter().sum::<f32>();
+    let scale = if content_width > 0. {
+        ((page_width - left - right) / content_width).min(1.)
+    } else {
+        1.
+    };
+
+    // The left edge and width of each column, indexed from zero
+    let mut columns = Vec::with_capacity(widths.len());
+    let mut x = left;
+    for width in widths {
+        columns.push((x, width * scale));
+        x += width * scale;
+    }
+
+    // Start a new page whenever a row would cross the bottom margin
+    let mut rows: Vec<Option<RowPosition>> = Vec::with_capacity(last_row as usize);
+    let mut page = 0;
+    let mut y = top;
+    for row in 1..=last_row {
+        let height = row_height(worksheet, row) * scale;
+        if height == 0. {
+            rows.push(None);
+            continue;
+        }
+        if y + height > page_height - bottom && y > top {
+            page += 1;
+            y = top;
+        }
+        rows.push(Some(RowPosition { page, y, height }));
+        y += height;
+    }
+
+    let mut pages = (0..=page).map(|_| Page::default()).collect::<Vec<_>>();
+    for row in 1..=last_row {
+        let Some(row_position) = rows[row as usize - 1] else {
+            continue;
+        };
+        for column in 1..=last_column {
+            let (x, width) = columns[column as usize - 1];
+            if width == 0. {
+                continue;
+            }
+            let Some(cell) = worksheet.get_cell((column, row)) else {
+                continue;
+            };
+            let page = &mut pages[row_position.page];
+            let rect = Rect {
+                x,
+                y: row_position.y,
+                width,
+                height: row_position.height,
+            };
+            let merged_range = merged_ranges
+                .iter()
+                .find(|range| range.contains(column, row));
+
+            add_borders(page, cell, rect, merged_range, column, row);
+
+            // Only the top left cell of merged cells has a value
+            let rect = match merged_range {
+                Some(range) if range.start_column != column || range.start_row != row => continue,
+                Some(range) => merged_rect(range, &columns, &rows, row_position),
+                None => rect,
+            };
+            if let Some(text_box) = text_box(cell, rect, scale) {
+                page.texts.push(text_box);
+            }
+        }
+    }
+
+    Layout {
+        page_width,
+        page_height,
+        pages,
+    }
+}
+
+/// Returns the last column and row having a value, a border or merged cells, ignoring the cells
+/// that are only styled.
+fn used_range(worksheet: &Worksheet, merged_ranges: &[MergedRange]) -> (u32, u32) {
+    let mut last_column = 0;
+    let mut last_row = 0;
+    for cell in worksheet.get_cell_collection() {
+        if cell.get_value().trim().is_empty() && !has_border(cell) {
+            continue;
+        }
+        let coordinate = cell.get_coordinate();
+        last_column = last_column.max(*coordinate.get_col_num());
+        last_row = last_row.max(*coordinate.get_row_num());
+    }
+    for range in merged_ranges {
+        last_column = last_column.max(range.end_column);
+        last_row = last_row.max(range.end_row);
+    }
+    (last_column, last_row)
+}
+
+fn merged_ranges(worksheet: &Worksheet) -> Vec<MergedRange> {
+    worksheet
+        .get_merge_cells()
+        .iter()
+        .filter_map(|range| {
+            Some(MergedRange {
+                start_column: *range.get_coordinate_start_col()?.get_num(),
+                start_row: *range.get_coordinate_start_row()?.get_num(),
+                end_column: *range.get_coordinate_end_col()?.get_num(),
+                end_row: *range.get_coordinate_end_row()?.get_num(),
+            })
+        })
+        .collect()
+}
+
+/// The rectangle covering the merged cells on the page of their top left cell.
+fn merged_rect(
+    range: &MergedRange,
+    columns: &[(f32, f32)],
+    rows: &[Option<RowPosition>],
+    first_row: RowPosition,
+) -> Rect {
+    let width = (range.start_column..=range.end_column)
+        .filter_map(|column| columns.get(column as usize - 1))
+        .map(|(_, width)| width)
+        .sum();
+    let height = (range.start_row..=range.end_row)
+        .filter_map(|row| rows.get(row as usize - 1).copied().flatten())
+        .filter(|row_position| row_position.page == first_row.page)
+        .map(|row_position| row_position.height)
+        .sum();
+    Rect {
+        x: columns[range.start_column as usize - 1].0,
+        y: first_row.y,
+        width,
+        height,
+    }
+}
+
+/// Adds the borders of a cell, leaving out the edges inside merged cells.
+fn add_borders(
+    page: &mut Page,
+    cell: &Cell,
+    rect: Rect,
+    merged_range: Option<&MergedRange>,
+    column: u32,
+    row: u32,
+) {
+    let Some(borders) = cell.get_style().get_borders() else {
+        return;
+    };
+    let Rect {
+        x,
+        y,
+        width,
+        height,
+    } = rect;
+    let edges = [
+        (
+            borders.get_left().get_border_style(),
+            merged_range.is_none_or(|range| range.start_column == column),
+            (x, y),
+            (x, y + height),
+        ),
+        (
+            borders.get_right().get_border_style(),
+            merged_range.is_none_or(|range| range.end_column == column),
+            (x + width, y),
+            (x + width, y + height),
+        ),
+        (
+            borders.get_top().get_border_style(),
+            merged_range.is_none_or(|range| range.start_row == row),
+            (x, y),
+            (x + width, y),
+        ),
+        (
+            borders.get_bottom().get_border_style(),
+            merged_range.is_none_or(|range| range.end_row == row),
+            (x, y + height),
+            (x + width, y + height),
+        ),
+    ];
+    for (style, is_outer_edge, from, to) in edges {
+        if let Some(thickness) = border_thickness(style)
+            && is_outer_edge
+        {
+            page.borders.push(BorderLine {
+                from,
+                to,
+                thickness,
+            });
+        }
+    }
+}
+
+fn has_border(cell: &Cell) -> bool {
+    cell.get_style().get_borders().is_some_and(|borders| {
+        [
+            borders.get_left(),
+            borders.get_right(),
+            borders.get_top(),
+            borders.get_bottom(),
+        ]
+        .iter()
+        .any(|border| border_thickness(border.get_border_style()).is_some())
+    })
+}
+
+/// The line thickness in points of a border style, or `None` if the border is not drawn.
+fn border_thickness(style: &str) -> Option<f32> {
+    match style {
+        "" | "none" => None,
+        "medium" | "mediumDashed" | "mediumDashDot" | "mediumDashDotDot" | "double" => Some(1.),
+        "thick" => Some(1.5),
+        "hair" => Some(0.25),
+        _ => Some(0.5),
+    }
+}
+
+fn text_box(cell: &Cell, rect: Rect, scale: f32) -> Option<TextBox> {
+    let value = cell.get_formatted_value();
+    if value.trim().is_empty() {
+        return None;
+    }
+
+    let style = cell.get_style();
+    let (font_size, bold) = style
+        .get_font()
+        .map(|font| (*font.get_size() as f32, *font.get_bold()))
+        .unwrap_or((11., false));
+    let alignment = style.get_alignment();
+    let horizontal = match alignment.map(|alignment| alignment.get_horizontal()) {
+        Some(HorizontalAlignmentValues::Left) => Horizontal::Left,
+        Some(
+            HorizontalAlignmentValues::Center
+            | HorizontalAlignmentValues::CenterContinuous
+            | HorizontalAlignmentValues::Distributed,
+        ) => Horizontal::Center,
+        Some(HorizontalAlignmentValues::Right) => Horizontal::Right,
+        // Excel aligns numbers to the right and text to the left by default
+        _ if cell.get_data_type() == "n" => Horizontal::Right,
+        _ => Horizontal::Left,
+    };
+    let vertical = match alignment.map(|alignment| alignment.get_vertical()) {
+        Some(VerticalAlignmentValues::Top) => Vertical::Top,
+        Some(
+            VerticalAlignmentValues::Center
+            | VerticalAlignmentValues::Distributed
+            | VerticalAlignmentValues::Justify,
+        ) => Vertical::Center,
+        _ => Vertical::Bottom,
+    };
+
+    Some(TextBox {
+        rect,
+        lines: value.lines().map(str::to_string).collect(),
+        font_size: if font_size > 0. { font_size } else { 11. } * scale,
+        bold,
+        horizontal,
+        vertical,
+    })
+}
+
+/// The width of a column in millimetres, converting from the number of characters the same way
+/// Excel does with its default font at 96 DPI.
+fn column_width(worksheet: &Worksheet, column: u32) -> f32 {
+    let characters = match worksheet.get_column_dimension_by_number(&column) {
+        Some(dimension) if *dimension.get_hidden() => return 0.,
+        Some(dimension) if *dimension.get_width() > 0. => *dimension.get_width(),
+        _ => match *worksheet
+            .get_sheet_format_properties()
+            .get_default_column_width()
+        {
+            width if width > 0. => width,
+            _ => DEFAULT_COLUMN_WIDTH,
+        },
+    };
+    let pixels = (characters * 7. + 5.).round();
+    (pixels * 25.4 / 96.) as f32
+}
+
+/// The height of a row in millimetres, or zero if it is hidden.
+fn row_height(worksheet: &Worksheet, row: u32) -> f32 {
+    let points = match worksheet.get_row_dimension(&row) {
+        Some(dimension) if *dimension.get_hidden() => return 0.,
+        Some(dimension) if *dimension.get_height() > 0. => *dimension.get_height(),
+        _ => match *worksheet
+            .get_sheet_format_properties()
+            .get_default_row_height()
+        {
+            height if height > 0. => height,
+            _ => DEFAULT_ROW_HEIGHT,
+        },
+    };
+    (points * 25.4 / 72.) as f32
+}
+
+fn inches_to_mm(inches: f64) -> f32 {
+    (inches * 25.4) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use umya_spreadsheet::reader;
+
+    #[test]
+    fn test_layout_worksheet() {
+        let bytes = include_bytes!("../../../assets/templates/60抽面紙每週訂購單.xlsx");
+        let spreadsheet = reader::xlsx::read_reader(Cursor::new(bytes), true).unwrap();
+        let layout = layout_worksheet(spreadsheet.get_sheet(&0).unwrap());
+
+        assert_eq!(layout.pages.len(), 1);
+        let page = &layout.pages[0];
+
+        // The form is scaled down to the width between the margins
+        let right_edge = page
+            .borders
+            .iter()
+            .map(|border| border.from.0.max(border.to.0))
+            .fold(0., f32::max);
+        assert!((right_edge - (A4_WIDTH - inches_to_mm(0.39375))).abs() < 0.01);
+
+        // The title spans the merged cells A1:F1
+        let title = page
+            .texts
+            .iter()
+            .find(|text_box| text_box.lines[0].contains("訂貨通知單"))
+            .unwrap();
+        assert_eq!(title.horizontal, Horizontal::Center);
+        assert!((title.rect.x + title.rect.width - right_edge).abs() < 0.01);
+
+        // The header 站別 spans the merged cells A3:A4 without a border between them
+        let header = page
+            .texts
+            .iter()
+            .find(|text_box| text_box.lines == ["站別"])
+            .unwrap();
+        let a4 = page
+            .texts
+            .iter()
+            .find(|text_box| text_box.lines == ["潭頂站"])
+            .unwrap();
+        assert!((header.rect.height - 2. * a4.rect.height).abs() < 0.01);
+        let inner_edge_y = header.rect.y + a4.rect.height;
+        assert!(!page.borders.iter().any(|border| {
+            border.from.1 == inner_edge_y
+                && border.to.1 == inner_edge_y
+                && border.from.0 == header.rect.x
+        }));
+        assert_eq!(a4.horizontal, Horizontal::Left);
+        assert_eq!(a4.vertical, Vertical::Center);
+    }
+
+    #[test]
+    fn test_layout_worksheet_page_breaks() {
+        let mut worksheet = Worksheet::default();
+        for row in 1..=100 {
+            worksheet.get_cell_mut((1, row)).set_value_number(row);
+            worksheet.get_row_dimension_mut(&row).set_height(20.);
+        }
+        worksheet.get_row_dimension_mut(&50).set_hidden(true);
+
+        let layout = layout_worksheet(&worksheet);
+        assert!(layout.pages.len() > 1);
+        let texts = layout
+            .pages
+            .iter()
+            .map(|page| page.texts.len())
+            .sum::<usize>();
+        assert_eq!(texts, 99);
+        assert_eq!(
+            layout.pages[0].texts[0].horizontal,
+            Horizontal::Right,
+            "numbers are aligned to the right"
+        );
+    }
+}
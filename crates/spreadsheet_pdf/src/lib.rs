@@ -0,0 +1,6 @@
+mod error;
+mod layout;
+mod render;
+
+pub use crate::error::Error;
+pub use crate::render::{render_spreadsheet, render_worksheet};
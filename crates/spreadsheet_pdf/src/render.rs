@@ -0,0 +1,159 @@
+use crate::Error;
+use crate::layout::{Horizontal, Layout, TextBox, Vertical, layout_worksheet};
+use printpdf::{
+    IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point, TextRenderingMode,
+};
+use std::io::Cursor;
+use ttf_parser::Face;
+use umya_spreadsheet::{Spreadsheet, Worksheet};
+
+/// The space between the text and the edges of its cell, in millimetres.
+const CELL_PADDING: f32 = 0.8;
+
+/// The space between the lines of a cell, relative to the font size.
+const LINE_SPACING: f32 = 1.2;
+
+/// Renders every worksheet of the spreadsheet as a PDF document, each starting on a new page.
+///
+/// The font is embedded whole, so it must be a single TrueType font covering the characters of the
+/// spreadsheet, such as 標楷體 (`kaiu.ttf`) for Chinese.
+pub fn render_spreadsheet(spreadsheet: &Spreadsheet, font: &[u8]) -> Result<Vec<u8>, Error> {
+    render(spreadsheet.get_sheet_collection(), font)
+}
+
+/// Renders the worksheet as a PDF document. See [`render_spreadsheet`] for the font.
+pub fn render_worksheet(worksheet: &Worksheet, font: &[u8]) -> Result<Vec<u8>, Error> {
+    render(std::slice::from_ref(worksheet), font)
+}
+
+fn render(worksheets: &[Worksheet], font: &[u8]) -> Result<Vec<u8>, Error> {
+    let first_worksheet = worksheets.first().ok_or(Error::MissingWorksheet)?;
+    if ttf_parser::fonts_in_collection(font).is_some() {
+        return Err(Error::FontCollection);
+    }
+    let face = Face::parse(font, 0)?;
+
+    let layouts = worksheets.iter().map(layout_worksheet).collect::<Vec<_>>();
+    let (document, page, layer) = PdfDocument::new(
+        first_worksheet.get_name(),
+        Mm(layouts[0].page_width),
+        Mm(layouts[0].page_height),
+        "",
+    );
+    let font_ref = document.add_external_font(Cursor::new(font))?;
+
+    let mut first_page = Some((page, layer));
+    for layout in layouts.iter() {
+        for page_layout in layout.pages.iter() {
+            let (page, layer) = first_page.take().unwrap_or_else(|| {
+                document.add_page(Mm(layout.page_width), Mm(layout.page_height), "")
+            });
+            let layer = document.get_page(page).get_layer(layer);
+
+            for border in page_layout.borders.iter() {
+                layer.set_outline_thickness(border.thickness);
+                layer.add_line(Line {
+                    points: vec![
+                        (point(layout, border.from), false),
+                        (point(layout, border.to), false),
+                    ],
+                    is_closed: false,
+                });
+            }
+            for text_box in page_layout.texts.iter() {
+                draw_text(&layer, layout, text_box, &face, &font_ref);
+            }
+        }
+    }
+
+    Ok(document.save_to_bytes()?)
+}
+
+/// Converts a position from the top left corner of the page to the bottom left one PDF uses.
+fn point(layout: &Layout, (x, y): (f32, f32)) -> Point {
+    Point::new(Mm(x), Mm(layout.page_height - y))
+}
+
+fn draw_text(
+    layer: &PdfLayerReference,
+    layout: &Layout,
+    text_box: &TextBox,
+    face: &Face,
+    font_ref: &IndirectFontRef,
+) {
+    let units_per_em = face.units_per_em() as f32;
+    let font_size = text_box.font_size;
+    let line_height = pt_to_mm(font_size * LINE_SPACING);
+    let ascent = pt_to_mm(font_size * face.ascender() as f32 / units_per_em);
+    let descent = pt_to_mm(font_size * -face.descender() as f32 / units_per_em);
+    let text_height = line_height * (text_box.lines.len() - 1) as f32 + ascent + descent;
+
+    let rect = text_box.rect;
+    let top = match text_box.vertical {
+        Vertical::Top => rect.y + CELL_PADDING,
+        Vertical::Center => rect.y + (rect.height - text_height) / 2.,
+        Vertical::Bottom => rect.y + rect.height - CELL_PADDING - text_height,
+    };
+
+    // Excel has no bold variant of most CJK fonts either, so thicken the outline of the glyphs
+    if text_box.bold {
+        layer.set_text_rendering_mode(TextRenderingMode::FillStroke);
+        layer.set_outline_thickness(font_size / 30.);
+    }
+    for (i, line) in text_box.lines.iter().enumerate() {
+        let width = pt_to_mm(
+            font_size
+                * line
+                    .chars()
+                    .filter_map(|c| face.glyph_index(c))
+                    .filter_map(|glyph| face.glyph_hor_advance(glyph))
+                    .map(|advance| advance as f32)
+                    .sum::<f32>()
+                / units_per_em,
+        );
+        let x = match text_box.horizontal {
+            Horizontal::Left => rect.x + CELL_PADDING,
+            Horizontal::Center => rect.x + (rect.width - width) / 2.,
+            Horizontal::Right => rect.x + rect.width - CELL_PADDING - width,
+        };
+        let baseline = top + ascent + line_height * i as f32;
+        layer.use_text(
+            line,
+            font_size,
+            Mm(x),
+            Mm(layout.page_height - baseline),
+            font_ref,
+        );
+    }
+    if text_box.bold {
+        layer.set_text_rendering_mode(TextRenderingMode::Fill);
+    }
+}
+
+fn pt_to_mm(points: f32) -> f32 {
+    points * 25.4 / 72.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_rejects_invalid_fonts() {
+        let mut spreadsheet = umya_spreadsheet::new_file();
+        spreadsheet
+            .get_sheet_mut(&0)
+            .unwrap()
+            .get_cell_mut("A1")
+            .set_value("站別");
+
+        assert!(matches!(
+            render_spreadsheet(&spreadsheet, b"not a font"),
+            Err(Error::FontParseError(_))
+        ));
+        assert!(matches!(
+            render_spreadsheet(&umya_spreadsheet::new_file_empty_worksheet(), b""),
+            Err(Error::MissingWorksheet)
+        ));
+    }
+}
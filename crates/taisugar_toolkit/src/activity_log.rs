@@ -0,0 +1,208 @@
+use crate::paths::data_file_path;
+use chrono::{DateTime, Local};
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use gpui::Global;
+use log::kv::{Error as KvError, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The crates whose events are logged, leaving out those of the UI framework.
+const LOGGED_CRATES: &[&str] = &["taisugar_toolkit", "tscred", "daily_necessities", "freebie"];
+/// The size of the log file at which it is rotated.
+const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+/// The number of rotated log files kept besides the current one.
+const MAX_ROTATED_FILES: usize = 5;
+
+/// An event logged by the toolkit or its crates, with its key-values such as the status code of
+/// a request or the path of a written file.
+#[derive(Clone, Debug)]
+pub struct LogEvent {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogEvent {
+    /// The message followed by the key-values, such as `received response status=200`.
+    pub fn details(&self) -> String {
+        let mut details = self.message.clone();
+        for (key, value) in self.fields.iter() {
+            details.push_str(&format!(" {}={}", key, value));
+        }
+        details
+    }
+}
+
+impl Display for LogEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {} {}",
+            self.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.details()
+        )
+    }
+}
+
+/// The events logged since the launch, waiting to be taken by the activity log panel.
+pub struct ActivityLog(pub Option<UnboundedReceiver<LogEvent>>);
+
+impl Global for ActivityLog {}
+
+/// Returns the path of the current log file.
+pub fn log_file_path() -> Option<PathBuf> {
+    data_file_path("logs").map(|dir| dir.join("activity.log"))
+}
+
+/// Installs the logger writing the events to the rotating log file, and returns the receiver of
+/// the events for the activity log panel.
+pub fn init() -> UnboundedReceiver<LogEvent> {
+    let (sender, receiver) = unbounded();
+    let file = log_file_path().and_then(|path| match RotatingFile::open(path) {
+        Ok(file) => Some(file),
+        Err(error) => {
+            eprintln!("Unable to open the log file: {}", error);
+            None
+        }
+    });
+    let logger = ActivityLogger {
+        file: Mutex::new(file),
+        sender,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(if cfg!(debug_assertions) {
+            LevelFilter::Debug
+        } else {
+            LevelFilter::Info
+        });
+    }
+    receiver
+}
+
+struct ActivityLogger {
+    file: Mutex<Option<RotatingFile>>,
+    sender: UnboundedSender<LogEvent>,
+}
+
+impl Log for ActivityLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let crate_name = metadata.target().split("::").next().unwrap_or_default();
+        LOGGED_CRATES.contains(&crate_name)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut fields = FieldCollector(vec![]);
+        let _ = record.key_values().visit(&mut fields);
+        let event = LogEvent {
+            time: Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            fields: fields.0,
+        };
+        if let Ok(mut slot) = self.file.lock() {
+            let line = event.to_string();
+            if slot.as_ref().is_some_and(|file| file.is_full(&line)) {
+                *slot = slot.take().and_then(|file| match file.rotate() {
+                    Ok(file) => Some(file),
+                    Err(error) => {
+                        eprintln!("Unable to reopen the log file: {}", error);
+                        None
+                    }
+                });
+            }
+            if let Some(file) = slot.as_mut()
+                && let Err(error) = file.write_line(&line)
+            {
+                eprintln!("Unable to write the log file: {}", error);
+            }
+        }
+        let _ = self.sender.unbounded_send(event);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(file) = file.as_mut()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+struct FieldCollector(Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), KvError> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+/// A log file which is renamed to `activity.log.1` once it grows too large, shifting the older
+/// ones up to `activity.log.5`.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile { path, file, size })
+    }
+
+    /// Whether writing the line would make the file too large.
+    fn is_full(&self, line: &str) -> bool {
+        self.size > 0 && self.size + line.len() as u64 + 1 > MAX_LOG_FILE_SIZE
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Shifts the log files up and starts a new one. The current file is closed before being
+    /// renamed, as open files cannot be renamed on Windows.
+    fn rotate(self) -> io::Result<Self> {
+        let RotatingFile { path, file, .. } = self;
+        drop(file);
+        if let Err(error) = shift_rotated_files(&path) {
+            eprintln!("Unable to rotate the log files: {}", error);
+        }
+        RotatingFile::open(path)
+    }
+}
+
+fn shift_rotated_files(path: &Path) -> io::Result<()> {
+    for index in (1..MAX_ROTATED_FILES).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}", index));
+    path.with_file_name(file_name)
+}
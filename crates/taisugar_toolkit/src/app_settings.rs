@@ -0,0 +1,77 @@
+use crate::http::HttpClient;
+use crate::paths::config_file_path;
+use anyhow::anyhow;
+use chrono::{Days, Local, NaiveDate};
+use gpui::{App, Global, Window};
+use gpui_component::ThemeMode;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{env, fs, io};
+use toolkit_settings::{Settings, Theme};
+
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+/// The settings of the toolkit, configured in the configuration directory.
+pub struct AppSettings(pub Arc<Settings>);
+
+impl Global for AppSettings {}
+
+impl AppSettings {
+    /// Loads the settings, or the default ones if there is no configuration file.
+    pub fn load() -> anyhow::Result<Settings> {
+        match settings_file_path().map(fs::read_to_string) {
+            Some(Ok(s)) => Ok(s.parse()?),
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(Settings::default()),
+        }
+    }
+
+    /// Saves the settings to the configuration file and applies them.
+    pub fn save(settings: Settings, window: &mut Window, cx: &mut App) -> anyhow::Result<()> {
+        let path = settings_file_path().ok_or(anyhow!("Unable to find the config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, settings.to_string())?;
+        Self::apply(settings, Some(window), cx);
+        Ok(())
+    }
+
+    /// Makes the settings take effect at once. The views read them whenever they send a request
+    /// or suggest where to save a file, so nothing waits for a restart.
+    pub fn apply(settings: Settings, window: Option<&mut Window>, cx: &mut App) {
+        gpui_component::set_locale(&settings.appearance.locale);
+        match settings.appearance.theme {
+            Theme::Light => gpui_component::Theme::change(ThemeMode::Light, window, cx),
+            Theme::Dark => gpui_component::Theme::change(ThemeMode::Dark, window, cx),
+            Theme::System => gpui_component::Theme::sync_system_appearance(window, cx),
+        }
+        cx.set_global(HttpClient::new(&settings.http));
+        cx.set_global(AppSettings(Arc::new(settings)));
+    }
+}
+
+pub fn settings_file_path() -> Option<PathBuf> {
+    config_file_path(SETTINGS_FILE_NAME)
+}
+
+/// Returns the folder suggested for saving files, the home folder unless another is set.
+pub fn output_folder(cx: &App) -> PathBuf {
+    cx.global::<AppSettings>()
+        .0
+        .output
+        .folder()
+        .or_else(env::home_dir)
+        .unwrap_or_default()
+}
+
+/// Returns the date range selected for a report by default, the default number of days up to
+/// today.
+pub fn default_report_range(cx: &App) -> (NaiveDate, NaiveDate) {
+    let today = Local::now().naive_local().date();
+    let days = cx.global::<AppSettings>().0.report.default_days;
+    (
+        today.checked_sub_days(Days::new(days)).unwrap_or(today),
+        today,
+    )
+}
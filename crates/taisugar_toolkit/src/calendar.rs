@@ -0,0 +1,64 @@
+use crate::paths::config_file_path;
+use chrono::{Datelike, NaiveDate};
+use gpui::Global;
+use gpui_component::calendar::Matcher;
+use roc_calendar::{RocDate, WorkingDayCalendar};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{fs, io};
+
+/// The file of the typhoon days off and other closures added by the user, in the same format as
+/// the bundled calendar.
+const USER_DAYS_OFF_FILE_NAME: &str = "days-off.csv";
+
+pub struct WorkingDays(pub Arc<WorkingDayCalendar>);
+
+impl Global for WorkingDays {}
+
+impl WorkingDays {
+    /// Loads the bundled calendar, followed by the user days off if there are any.
+    pub fn load() -> anyhow::Result<Self> {
+        let mut calendar = WorkingDayCalendar::bundled();
+        if let Some(path) = user_days_off_path() {
+            match fs::read_to_string(path) {
+                Ok(s) => calendar.extend_from_str(&s)?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(WorkingDays(Arc::new(calendar)))
+    }
+
+    /// A date picker matcher which disables the days off.
+    pub fn days_off_matcher(&self) -> Matcher {
+        let calendar = self.0.clone();
+        Matcher::custom(move |date: &NaiveDate| {
+            to_jiff_date(date).is_some_and(|date| !calendar.is_working_day(&date))
+        })
+    }
+}
+
+pub fn user_days_off_path() -> Option<PathBuf> {
+    config_file_path(USER_DAYS_OFF_FILE_NAME)
+}
+
+/// Tells the user that the holidays of the year of the date are unknown, so what is counted in
+/// working days, such as the proposed delivery dates, only skips the weekends.
+pub fn uncovered_year_description(date: &jiff::civil::Date, counted: &str) -> String {
+    let roc_year = RocDate::try_from(*date)
+        .map(|date| date.roc_year().to_string())
+        .unwrap_or_else(|_| date.year().to_string());
+    format!(
+        "工作日曆沒有民國 {} 年的國定假日，{}只略過週末",
+        roc_year, counted
+    )
+}
+
+pub fn to_jiff_date(date: &NaiveDate) -> Option<jiff::civil::Date> {
+    jiff::civil::Date::new(
+        i16::try_from(date.year()).ok()?,
+        date.month() as i8,
+        date.day() as i8,
+    )
+    .ok()
+}
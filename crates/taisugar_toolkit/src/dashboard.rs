@@ -0,0 +1,280 @@
+use crate::calendar::to_jiff_date;
+use crate::item_needs::{ItemNeedsSource, fetch_item_needs_by_center};
+use chrono::Local;
+use freebie::{Freebie, WeeklyVolume};
+use gpui::prelude::*;
+use gpui::{App, AsyncApp, Entity, Hsla, SharedString, WeakEntity, Window, div, px};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::chart::{BarChart, PieChart};
+use gpui_component::input::{InputState, TextInput};
+use gpui_component::{ActiveTheme, h_flex, v_flex};
+
+/// The number of weeks shown unless the user asks for another.
+const DEFAULT_WEEKS: usize = 8;
+/// The most weeks shown, which keeps the item needs fetched from TSCRED to a year.
+const MAX_WEEKS: usize = 52;
+/// The number of stations ranked by the cases they requested.
+const TOP_STATIONS: usize = 10;
+
+pub struct DashboardView {
+    weeks_input: Entity<InputState>,
+    /// The volume of the last weeks, or `None` until it is fetched.
+    volume: Option<WeeklyVolume>,
+    description: String,
+    loading: bool,
+}
+
+impl DashboardView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let weeks_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .validate(|value, _| value.is_empty() || value.parse::<usize>().is_ok())
+                .default_value(DEFAULT_WEEKS.to_string())
+        });
+        let mut view = DashboardView {
+            weeks_input,
+            volume: None,
+            description: String::new(),
+            loading: false,
+        };
+        // Show the state of things at launch
+        view.refresh(cx);
+
+        view
+    }
+
+    /// Fetches the item needs of the weeks up to today and sums the freebies requested.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let weeks = match self.weeks_input.read(cx).value().trim().parse::<usize>() {
+            Ok(weeks) if (1..=MAX_WEEKS).contains(&weeks) => weeks,
+            _ => {
+                self.description = "請輸入週數，例如 8".to_string();
+                cx.notify();
+                return;
+            }
+        };
+        let Some(today) = to_jiff_date(&Local::now().naive_local().date()) else {
+            return;
+        };
+        let Some((start_date, end_date)) = WeeklyVolume::date_range(&today, weeks) else {
+            return;
+        };
+        let source = ItemNeedsSource::new(cx);
+        self.loading = true;
+        self.description = String::new();
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = fetch_item_needs_by_center(source, start_date, end_date, cx).await;
+            let _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok(regions) => {
+                        this.volume = WeeklyVolume::new(&regions, &end_date, weeks);
+                    }
+                    Err(message) => this.description = message,
+                }
+                this.loading = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn chart_color(index: usize, cx: &App) -> Hsla {
+        let theme = cx.theme();
+        [
+            theme.chart_1,
+            theme.chart_2,
+            theme.chart_3,
+            theme.chart_4,
+            theme.chart_5,
+        ][index % 5]
+    }
+
+    fn render_card(
+        title: impl Into<SharedString>,
+        cx: &App,
+        content: impl IntoElement,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .gap_2()
+            .p_4()
+            .rounded(cx.theme().radius)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(div().text_lg().child(title.into()))
+            .child(content)
+    }
+
+    fn render_weekly_cases(&self, volume: &WeeklyVolume, cx: &App) -> impl IntoElement {
+        h_flex().gap_4().children(
+            Freebie::all()
+                .into_iter()
+                .enumerate()
+                .map(|(index, freebie)| {
+                    let color = Self::chart_color(index, cx);
+                    let weekly_cases = volume
+                        .weekly_cases(&freebie)
+                        .into_iter()
+                        .map(|(monday, cases)| {
+                            (format!("{}/{}", monday.month(), monday.day()), cases)
+                        })
+                        .collect::<Vec<_>>();
+                    Self::render_card(
+                        format!("{} 每週箱數", freebie.short_name()),
+                        cx,
+                        div().h(px(200.)).child(
+                            BarChart::new(weekly_cases)
+                                .x(|(week, _)| week.clone())
+                                .y(|(_, cases)| *cases as f64)
+                                .fill(move |_| color)
+                                .label(|(_, cases)| cases.to_string()),
+                        ),
+                    )
+                }),
+        )
+    }
+
+    fn render_top_stations(&self, volume: &WeeklyVolume, cx: &App) -> impl IntoElement {
+        let color = Self::chart_color(1, cx);
+        Self::render_card(
+            format!("需求前 {} 站", TOP_STATIONS),
+            cx,
+            div().h(px(240.)).child(
+                BarChart::new(volume.top_stations(TOP_STATIONS))
+                    .x(|(station_name, _)| station_name.clone())
+                    .y(|(_, cases)| *cases as f64)
+                    .fill(move |_| color)
+                    .label(|(_, cases)| cases.to_string()),
+            ),
+        )
+    }
+
+    fn render_regions(&self, volume: &WeeklyVolume, cx: &App) -> impl IntoElement {
+        let regions = volume
+            .region_cases()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (region, cases))| (region, cases, Self::chart_color(index, cx)))
+            .collect::<Vec<_>>();
+        let total = regions.iter().map(|(_, cases, _)| cases).sum::<u64>();
+        let legend = regions.iter().map(|(region, cases, color)| {
+            h_flex()
+                .gap_2()
+                .child(div().size_3().bg(*color))
+                .child(div().w_32().child(region.clone()))
+                .child(format!(
+                    "{} 箱（{:.1}%）",
+                    cases,
+                    *cases as f64 / total.max(1) as f64 * 100.
+                ))
+        });
+
+        Self::render_card(
+            "營運中心分布",
+            cx,
+            h_flex()
+                .gap_4()
+                .child(
+                    div().size(px(240.)).child(
+                        PieChart::new(regions.clone())
+                            .value(|(_, cases, _)| *cases as f32)
+                            .color(|(_, _, color)| *color),
+                    ),
+                )
+                .child(v_flex().gap_1().children(legend)),
+        )
+    }
+
+    fn render_this_week(&self, volume: &WeeklyVolume, cx: &App) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_32().child("贈品"))
+            .child(div().w_24().child("本週"))
+            .child(div().w_24().child("前 4 週平均"))
+            .child(div().w_24().child("增減"));
+        let rows = Freebie::all().into_iter().map(|freebie| {
+            let row = h_flex()
+                .gap_2()
+                .py_1()
+                .child(div().w_32().child(freebie.short_name()));
+            match volume.this_week_vs_average(&freebie) {
+                Some((this_week, average)) => row
+                    .child(div().w_24().child(format!("{} 箱", this_week)))
+                    .child(div().w_24().child(format!("{:.1} 箱", average)))
+                    .child(div().w_24().child(if average > 0. {
+                        format!("{:+.1}%", (this_week as f64 / average - 1.) * 100.)
+                    } else {
+                        "—".to_string()
+                    })),
+                None => row.child(
+                    div()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("週數不足 5 週，無法比較"),
+                ),
+            }
+        });
+
+        Self::render_card(
+            "本週與前 4 週平均",
+            cx,
+            v_flex().child(header).children(rows),
+        )
+    }
+}
+
+impl Render for DashboardView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let content = self.volume.as_ref().map(|volume| {
+            v_flex()
+                .gap_4()
+                .child(self.render_weekly_cases(volume, cx))
+                .child(
+                    h_flex()
+                        .gap_4()
+                        .items_start()
+                        .child(self.render_top_stations(volume, cx))
+                        .child(self.render_regions(volume, cx)),
+                )
+                .child(self.render_this_week(volume, cx))
+        });
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().text_2xl().flex_1().child("總覽"))
+                    .child(
+                        div()
+                            .w_24()
+                            .child(TextInput::new(&self.weeks_input).suffix("週")),
+                    )
+                    .child(
+                        Button::new("refresh-dashboard")
+                            .primary()
+                            .label("重新整理")
+                            .loading(self.loading)
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .when(!self.description.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_color(cx.theme().danger)
+                        .child(self.description.clone()),
+                )
+            })
+            .children(content)
+    }
+}
@@ -0,0 +1,517 @@
+use crate::calendar::to_jiff_date;
+use crate::ledger::OrderLedger;
+use chrono::Local;
+use freebie::DefectKind;
+use gpui::prelude::*;
+use gpui::{App, Entity, SharedString, Subscription, Window, div};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::calendar::Date;
+use gpui_component::date_picker::{DatePicker, DatePickerState};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputEvent, InputState, TextInput};
+use gpui_component::notification::NotificationType;
+use gpui_component::{ActiveTheme, ContextModal, Sizable, h_flex, v_flex};
+use log::warn;
+use order_ledger::{
+    Defect, Delivery, DeliveryQuery, Error, NewDelivery, Order, OrderQuery, OrderStatus,
+};
+use roc_calendar::RocDate;
+use std::collections::HashMap;
+
+pub struct DeliveryEntryView {
+    /// The orders still waiting for their deliveries, latest first.
+    orders: Vec<Order>,
+    /// The boxes received so far by each order.
+    received: HashMap<i64, u64>,
+    form: Option<DeliveryForm>,
+    delivery_date_picker: Entity<DatePickerState>,
+    description: String,
+}
+
+/// The deliveries of an order being entered, one row per station.
+struct DeliveryForm {
+    order: Order,
+    rows: Vec<DeliveryRow>,
+    deliveries: Vec<Delivery>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// A station of the order, with the inputs of the boxes it received and rejected.
+struct DeliveryRow {
+    station_name: String,
+    ordered: u64,
+    received_before: u64,
+    quantity_input: Entity<InputState>,
+    defect_inputs: Vec<(DefectKind, Entity<InputState>)>,
+}
+
+impl DeliveryRow {
+    /// The delivery entered in the row, `None` if it is left empty, or the message describing what
+    /// is wrong with it.
+    fn delivery(
+        &self,
+        order_id: i64,
+        delivery_date: jiff::civil::Date,
+        cx: &App,
+    ) -> Result<Option<NewDelivery>, String> {
+        let quantity = self.quantity_input.read(cx).value().trim().to_string();
+        let mut defects = vec![];
+        for (kind, input) in self.defect_inputs.iter() {
+            let value = input.read(cx).value().trim().to_string();
+            if value.is_empty() {
+                continue;
+            }
+            let quantity = value
+                .parse()
+                .map_err(|_| format!("{}的{}數量必須是整數", self.station_name, kind.label()))?;
+            defects.push(Defect {
+                kind: *kind,
+                quantity,
+            });
+        }
+        if quantity.is_empty() {
+            if defects.is_empty() {
+                return Ok(None);
+            }
+            return Err(format!("請填寫{}的實收數量", self.station_name));
+        }
+        let quantity_received = quantity
+            .parse()
+            .map_err(|_| format!("{}的實收數量必須是整數", self.station_name))?;
+
+        Ok(Some(NewDelivery {
+            order_id,
+            station_name: self.station_name.clone(),
+            delivery_date,
+            quantity_received,
+            defects,
+        }))
+    }
+}
+
+impl DeliveryEntryView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let delivery_date_picker = cx.new(|cx| {
+            let mut state = DatePickerState::new(window, cx);
+            state.set_date(Local::now().naive_local().date(), window, cx);
+            state
+        });
+
+        let mut view = DeliveryEntryView {
+            orders: vec![],
+            received: HashMap::new(),
+            form: None,
+            delivery_date_picker,
+            description: String::new(),
+        };
+        view.refresh(cx);
+
+        view
+    }
+
+    /// Reloads the orders not yet accepted and the boxes they received from the ledger.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let ledger = &cx.global::<OrderLedger>().0;
+        let result = ledger
+            .find_orders(&OrderQuery::default())
+            .and_then(|orders| Ok((orders, ledger.find_deliveries(&DeliveryQuery::default())?)));
+        match result {
+            Ok((mut orders, deliveries)) => {
+                orders.retain(|order| order.status < OrderStatus::Accepted);
+                orders.reverse();
+                self.orders = orders;
+                self.received = HashMap::new();
+                for delivery in deliveries {
+                    *self.received.entry(delivery.order_id).or_default() +=
+                        delivery.quantity_received;
+                }
+            }
+            Err(error) => warn!(error:%; "unable to load the deliveries"),
+        }
+        cx.notify();
+    }
+
+    /// Opens the form to enter the deliveries of the order.
+    fn open_form(&mut self, order: Order, window: &mut Window, cx: &mut Context<Self>) {
+        let deliveries = match cx
+            .global::<OrderLedger>()
+            .0
+            .find_deliveries(&DeliveryQuery {
+                order_id: Some(order.id),
+                ..Default::default()
+            }) {
+            Ok(deliveries) => deliveries,
+            Err(error) => {
+                window.push_notification(
+                    (
+                        NotificationType::Error,
+                        SharedString::from(format!("無法讀取交貨紀錄\n{}", error)),
+                    ),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        let mut subscriptions = vec![];
+        let rows = order
+            .stations
+            .iter()
+            .filter(|station| station.quantity > 0)
+            .map(|station| {
+                let received_before = deliveries
+                    .iter()
+                    .filter(|delivery| delivery.station_name == station.station_name)
+                    .map(|delivery| delivery.quantity_received)
+                    .sum();
+                let quantity_input = cx.new(|cx| InputState::new(window, cx));
+                subscriptions.push(cx.subscribe(
+                    &quantity_input,
+                    |_, _, event: &InputEvent, cx| {
+                        if let InputEvent::Change = event {
+                            cx.notify();
+                        }
+                    },
+                ));
+                let defect_inputs = order
+                    .freebie
+                    .defect_kinds()
+                    .iter()
+                    .map(|kind| (*kind, cx.new(|cx| InputState::new(window, cx))))
+                    .collect();
+                DeliveryRow {
+                    station_name: station.station_name.clone(),
+                    ordered: station.quantity,
+                    received_before,
+                    quantity_input,
+                    defect_inputs,
+                }
+            })
+            .collect();
+
+        self.form = Some(DeliveryForm {
+            order,
+            rows,
+            deliveries,
+            _subscriptions: subscriptions,
+        });
+        self.description = String::new();
+        cx.notify();
+    }
+
+    /// Describes why the ledger refused the deliveries.
+    fn describe_error(error: &Error) -> String {
+        match error {
+            Error::ExcessDelivery {
+                station_name,
+                ordered,
+                received,
+            } => format!(
+                "{}累計實收 {} 超過訂購數量 {}",
+                station_name, received, ordered
+            ),
+            Error::ExcessDefects {
+                station_name,
+                defects,
+                received,
+            } => format!(
+                "{}的不合格數量 {} 超過實收數量 {}",
+                station_name, defects, received
+            ),
+            Error::DeliveryBeforeNotification {
+                notification_date, ..
+            } => format!(
+                "交貨日期早於通知日期 {}",
+                Self::format_date(notification_date)
+            ),
+            error => format!("無法登錄交貨\n{}", error),
+        }
+    }
+
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(form) = &self.form else {
+            return;
+        };
+        let Date::Single(Some(delivery_date)) = self.delivery_date_picker.read(cx).date() else {
+            self.description = "請選擇交貨日期".to_string();
+            cx.notify();
+            return;
+        };
+        let delivery_date = to_jiff_date(&delivery_date).unwrap();
+
+        let mut deliveries = vec![];
+        for row in form.rows.iter() {
+            match row.delivery(form.order.id, delivery_date, cx) {
+                Ok(Some(delivery)) => deliveries.push(delivery),
+                Ok(None) => {}
+                Err(message) => {
+                    self.description = message;
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+        if deliveries.is_empty() {
+            self.description = "請填寫至少一站的實收數量".to_string();
+            cx.notify();
+            return;
+        }
+
+        let result =
+            cx.update_global::<OrderLedger, _>(|ledger, _| ledger.0.record_deliveries(&deliveries));
+        match result {
+            Ok(_) => {
+                window.push_notification(
+                    (
+                        NotificationType::Success,
+                        SharedString::from(format!("已登錄 {} 站的交貨", deliveries.len())),
+                    ),
+                    cx,
+                );
+                let order = form.order.clone();
+                self.refresh(cx);
+                self.open_form(order, window, cx);
+            }
+            Err(error) => {
+                self.description = Self::describe_error(&error);
+                cx.notify();
+            }
+        }
+    }
+
+    fn delete_delivery(&mut self, id: i64, window: &mut Window, cx: &mut Context<Self>) {
+        let result = cx.update_global::<OrderLedger, _>(|ledger, _| ledger.0.delete_delivery(id));
+        if let Err(error) = result {
+            window.push_notification(
+                (
+                    NotificationType::Error,
+                    SharedString::from(format!("無法刪除交貨紀錄\n{}", error)),
+                ),
+                cx,
+            );
+        }
+        self.refresh(cx);
+        if let Some(form) = &self.form {
+            let order = form.order.clone();
+            self.open_form(order, window, cx);
+        }
+    }
+
+    fn format_date(date: &jiff::civil::Date) -> String {
+        RocDate::try_from(*date)
+            .map(|date| date.to_string())
+            .unwrap_or_else(|_| date.to_string())
+    }
+
+    fn order_label(order: &Order) -> String {
+        format!(
+            "{} {}{}",
+            order.freebie.short_name(),
+            order.freebie.order_number_prefix(),
+            order.order_number
+        )
+    }
+
+    fn render_orders(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_40().child("訂單"))
+            .child(div().w_24().child("通知日期"))
+            .child(div().w_20().child("狀態"))
+            .child(div().w_32().child("已交/訂購"))
+            .child(div().flex_1());
+        let rows = self.orders.iter().map(|order| {
+            let received = self.received.get(&order.id).copied().unwrap_or_default();
+            let moved_order = order.clone();
+            h_flex()
+                .gap_2()
+                .py_1()
+                .child(div().w_40().child(Self::order_label(order)))
+                .child(
+                    div()
+                        .w_24()
+                        .child(Self::format_date(&order.notification_date)),
+                )
+                .child(div().w_20().child(order.status.label()))
+                .child(
+                    div()
+                        .w_32()
+                        .child(format!("{}/{}", received, order.total_quantity())),
+                )
+                .child(
+                    div().flex_1().child(
+                        Button::new(("enter-deliveries", order.id as usize))
+                            .small()
+                            .label("登錄交貨")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.open_form(moved_order.clone(), window, cx)
+                            })),
+                    ),
+                )
+        });
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().text_lg().child("待交貨訂單"))
+                    .child(
+                        Button::new("refresh-deliveries")
+                            .small()
+                            .ghost()
+                            .label("重新整理")
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .child(v_flex().child(header).children(rows))
+    }
+
+    fn render_form(&self, form: &DeliveryForm, cx: &mut Context<Self>) -> impl IntoElement {
+        let defect_kinds = form.order.freebie.defect_kinds();
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_32().child("站別"))
+            .child(div().w_20().child("訂購數量"))
+            .child(div().w_20().child("已交數量"))
+            .child(div().w_24().child("本次實收"))
+            .children(
+                defect_kinds
+                    .iter()
+                    .map(|kind| div().w_20().child(kind.label())),
+            );
+        let rows = form.rows.iter().map(|row| {
+            let quantity = row
+                .quantity_input
+                .read(cx)
+                .value()
+                .trim()
+                .parse::<u64>()
+                .unwrap_or_default();
+            // Highlight the stations which would receive more than they ordered
+            h_flex()
+                .gap_2()
+                .py_1()
+                .when(row.received_before + quantity > row.ordered, |this| {
+                    this.bg(cx.theme().danger.opacity(0.2))
+                })
+                .child(div().w_32().child(row.station_name.clone()))
+                .child(div().w_20().child(row.ordered.to_string()))
+                .child(div().w_20().child(row.received_before.to_string()))
+                .child(
+                    div()
+                        .w_24()
+                        .child(TextInput::new(&row.quantity_input).small()),
+                )
+                .children(
+                    row.defect_inputs
+                        .iter()
+                        .map(|(_, input)| div().w_20().child(TextInput::new(input).small())),
+                )
+        });
+        let deliveries = form.deliveries.iter().map(|delivery| {
+            let id = delivery.id;
+            h_flex()
+                .gap_2()
+                .py_1()
+                .child(
+                    div()
+                        .w_24()
+                        .child(Self::format_date(&delivery.delivery_date)),
+                )
+                .child(div().w_32().child(delivery.station_name.clone()))
+                .child(div().w_20().child(delivery.quantity_received.to_string()))
+                .child(
+                    div().flex_1().child(
+                        delivery
+                            .defects
+                            .iter()
+                            .map(|defect| format!("{} {}", defect.kind.label(), defect.quantity))
+                            .collect::<Vec<_>>()
+                            .join("、"),
+                    ),
+                )
+                .child(
+                    Button::new(("delete-delivery", id as usize))
+                        .small()
+                        .ghost()
+                        .label("刪除")
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.delete_delivery(id, window, cx)
+                        })),
+                )
+        });
+
+        v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .text_lg()
+                    .child(format!("{}交貨", Self::order_label(&form.order))),
+            )
+            .child(
+                v_form().child(
+                    form_field()
+                        .label("交貨日期")
+                        .required(true)
+                        .child(DatePicker::new(&self.delivery_date_picker).number_of_months(1)),
+                ),
+            )
+            .child(v_flex().child(header).children(rows))
+            .when(!self.description.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_color(cx.theme().danger)
+                        .child(SharedString::from(&self.description)),
+                )
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("save-deliveries")
+                            .primary()
+                            .label("儲存交貨")
+                            .on_click(cx.listener(|this, _, window, cx| this.save(window, cx))),
+                    )
+                    .child(
+                        Button::new("close-deliveries")
+                            .ghost()
+                            .label("返回")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.form = None;
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when(!form.deliveries.is_empty(), |this| {
+                this.child(div().text_lg().child("交貨紀錄"))
+                    .child(v_flex().children(deliveries))
+            })
+    }
+}
+
+impl Render for DeliveryEntryView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(div().text_2xl().child("交貨登錄"))
+            .map(|this| match &self.form {
+                Some(form) => this.child(self.render_form(form, cx)),
+                None => this.child(self.render_orders(cx)),
+            })
+    }
+}
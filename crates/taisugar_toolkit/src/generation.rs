@@ -0,0 +1,183 @@
+use gpui::prelude::*;
+use gpui::{App, div};
+use gpui_component::{ActiveTheme, Icon, IconName, Sizable, h_flex, v_flex};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tscred::ItemNeeds;
+
+/// A step of generating weekly orders, shown to the user as it runs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GenerationStep {
+    /// Fetching the operation centers from TSCRED.
+    OperationCenters,
+    /// Fetching the item needs of every operation center.
+    ItemNeeds,
+    /// Loading the templates of the order forms.
+    Template,
+    /// Filling the order forms with the quantities of the stations.
+    Report,
+    /// Writing the order forms to the chosen file or folder.
+    File,
+}
+
+impl GenerationStep {
+    /// The steps of fetching the item needs to preview an order.
+    pub const FETCH: [GenerationStep; 2] = [Self::OperationCenters, Self::ItemNeeds];
+    /// The steps of saving a previewed order.
+    pub const SAVE: [GenerationStep; 3] = [Self::Template, Self::Report, Self::File];
+    /// The steps of generating the orders of all freebies at once.
+    pub const ALL: [GenerationStep; 5] = [
+        Self::OperationCenters,
+        Self::ItemNeeds,
+        Self::Template,
+        Self::Report,
+        Self::File,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GenerationStep::OperationCenters => "取得營運中心",
+            GenerationStep::ItemNeeds => "取得各營運中心的贈品需求",
+            GenerationStep::Template => "載入訂購單範本",
+            GenerationStep::Report => "產生訂購單",
+            GenerationStep::File => "寫入檔案",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StepState {
+    Pending,
+    Running,
+    Done,
+    /// Failed with the message shown beside the step.
+    Failed(String),
+    Cancelled,
+}
+
+struct StepProgress {
+    step: GenerationStep,
+    state: StepState,
+    /// What the step is doing or did, such as `3/5` centers fetched.
+    detail: String,
+}
+
+/// The steps of generating weekly orders, kept after a step fails or is cancelled to show what
+/// is left to retry.
+pub struct GenerationProgress {
+    steps: Vec<StepProgress>,
+}
+
+impl GenerationProgress {
+    pub fn new(steps: &[GenerationStep]) -> Self {
+        GenerationProgress {
+            steps: steps
+                .iter()
+                .map(|step| StepProgress {
+                    step: *step,
+                    state: StepState::Pending,
+                    detail: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn update(&mut self, step: GenerationStep, state: StepState, detail: Option<String>) {
+        if let Some(progress) = self.steps.iter_mut().find(|progress| progress.step == step) {
+            progress.state = state;
+            if let Some(detail) = detail {
+                progress.detail = detail;
+            }
+        }
+    }
+
+    pub fn start(&mut self, step: GenerationStep) {
+        self.update(step, StepState::Running, Some(String::new()));
+    }
+
+    pub fn set_detail(&mut self, step: GenerationStep, detail: impl Into<String>) {
+        if let Some(progress) = self.steps.iter_mut().find(|progress| progress.step == step) {
+            progress.detail = detail.into();
+        }
+    }
+
+    pub fn finish(&mut self, step: GenerationStep) {
+        self.update(step, StepState::Done, None);
+    }
+
+    /// Marks the step as done by reusing what it got before a later step failed.
+    pub fn reuse(&mut self, step: GenerationStep) {
+        self.update(step, StepState::Done, Some("沿用上次結果".to_string()));
+    }
+
+    pub fn fail(&mut self, step: GenerationStep, message: impl Into<String>) {
+        self.update(step, StepState::Failed(message.into()), None);
+    }
+
+    /// Marks the running steps as cancelled.
+    pub fn cancel(&mut self) {
+        for progress in self.steps.iter_mut() {
+            if progress.state == StepState::Running {
+                progress.state = StepState::Cancelled;
+            }
+        }
+    }
+
+    /// Whether a step failed or was cancelled, leaving it to be retried.
+    pub fn can_retry(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|progress| matches!(progress.state, StepState::Failed(_) | StepState::Cancelled))
+    }
+
+    pub fn render(&self, cx: &App) -> impl IntoElement {
+        v_flex().gap_1().children(self.steps.iter().map(|progress| {
+            let (icon, color) = match &progress.state {
+                StepState::Pending => (IconName::Dash, cx.theme().muted_foreground),
+                StepState::Running => (IconName::LoaderCircle, cx.theme().foreground),
+                StepState::Done => (IconName::CircleCheck, cx.theme().success),
+                StepState::Failed(_) => (IconName::CircleX, cx.theme().danger),
+                StepState::Cancelled => (IconName::Minus, cx.theme().warning),
+            };
+            let detail = match &progress.state {
+                StepState::Failed(message) => message.clone(),
+                StepState::Cancelled => "已取消".to_string(),
+                _ => progress.detail.clone(),
+            };
+            h_flex()
+                .gap_2()
+                .child(Icon::new(icon).small().text_color(color))
+                .child(div().w_48().child(progress.step.label()))
+                .child(div().flex_1().text_color(color).child(detail))
+        }))
+    }
+}
+
+/// The results of the steps of saving which succeeded, reused when retrying after a later step
+/// failed or was cancelled as long as what they were generated from is unchanged.
+pub struct SavedSteps<K, T, R> {
+    pub key: K,
+    pub item_needs: Option<Arc<Vec<ItemNeeds>>>,
+    pub template: Option<Arc<T>>,
+    pub report: Option<Arc<R>>,
+    pub path: Option<PathBuf>,
+    /// The files written before saving the rest failed, which are left as they are on retrying.
+    pub written: Vec<PathBuf>,
+}
+
+impl<K: PartialEq, T, R> SavedSteps<K, T, R> {
+    /// Takes the saved steps if they were generated from the key, or starts over.
+    pub fn take_or_new(saved: &mut Option<Self>, key: K) -> Self {
+        match saved.take() {
+            Some(saved) if saved.key == key => saved,
+            _ => SavedSteps {
+                key,
+                item_needs: None,
+                template: None,
+                report: None,
+                path: None,
+                written: Vec::new(),
+            },
+        }
+    }
+}
@@ -1,6 +1,18 @@
 use gpui::Global;
+use std::time::Duration;
+use toolkit_settings::HttpSettings;
 use ureq::Agent;
 
 pub struct HttpClient(pub Agent);
 
 impl Global for HttpClient {}
+
+impl HttpClient {
+    pub fn new(settings: &HttpSettings) -> Self {
+        let config = Agent::config_builder()
+            .user_agent(&settings.user_agent)
+            .timeout_global(Some(Duration::from_secs(settings.timeout_secs)))
+            .build();
+        HttpClient(Agent::new_with_config(config))
+    }
+}
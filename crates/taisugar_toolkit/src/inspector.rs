@@ -0,0 +1,450 @@
+use crate::app_settings::{AppSettings, default_report_range, output_folder};
+use crate::item_needs::ItemNeedsSource;
+use chrono::{Days, Local};
+use gpui::prelude::*;
+use gpui::{
+    AnyWindowHandle, App, AsyncApp, Entity, SharedString, Subscription, WeakEntity, Window, div,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::calendar::Matcher;
+use gpui_component::date_picker::{DatePicker, DatePickerState};
+use gpui_component::dropdown::{Dropdown, DropdownState};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputEvent, InputState, TextInput};
+use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::tab::{Tab, TabBar};
+use gpui_component::{ActiveTheme, ContextModal, IndexPath, Sizable, h_flex, v_flex};
+use log::{error, info};
+use std::fs;
+use tscred::{Column, DisplayMode, GetItemNeedsOptions, ItemNeeds, OperationCenter};
+
+/// The rows shown in the grid at most, the rest being left to the exports.
+const MAX_SHOWN_ROWS: usize = 500;
+
+/// The response of an item needs query, kept as received for the exports.
+struct QueryResult {
+    raw: String,
+    item_needs: ItemNeeds,
+    file_stem: String,
+}
+
+pub struct InspectorView {
+    display_mode_index: usize,
+    operation_centers: Vec<OperationCenter>,
+    operation_center_dropdown: Entity<DropdownState<Vec<String>>>,
+    date_picker: Entity<DatePickerState>,
+    station_filter_input: Entity<InputState>,
+    item_filter_input: Entity<InputState>,
+    description: String,
+    result: Option<QueryResult>,
+    button_loading: bool,
+    /// The default number of days of the date range, to select the new default when it changes.
+    default_days: u64,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl InspectorView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let now = Local::now().naive_local().date();
+        let date_picker = cx.new(|cx| {
+            let mut state = DatePickerState::range(window, cx).disabled_matcher(Matcher::range(
+                Some(now.checked_add_days(Days::new(1)).unwrap()),
+                None,
+            ));
+            state.set_date(default_report_range(cx), window, cx);
+            state
+        });
+        let operation_center_dropdown =
+            cx.new(|cx| DropdownState::new(Vec::<String>::new(), None, window, cx));
+        let station_filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("油站代號或名稱"));
+        let item_filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("品項代號或名稱"));
+
+        // Filter the grid as the user types
+        let mut subscriptions = [&station_filter_input, &item_filter_input]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe(input, |_, _, event: &InputEvent, cx| {
+                    if let InputEvent::Change = event {
+                        cx.notify();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Select the new default date range when it is changed in the settings
+        subscriptions.push(cx.observe_global_in::<AppSettings>(
+            window,
+            |this: &mut Self, window, cx| {
+                let default_days = cx.global::<AppSettings>().0.report.default_days;
+                if this.default_days != default_days {
+                    this.default_days = default_days;
+                    let range = default_report_range(cx);
+                    this.date_picker
+                        .update(cx, |state, cx| state.set_date(range, window, cx));
+                }
+            },
+        ));
+
+        let view = InspectorView {
+            // The details, which the weekly orders are generated from
+            display_mode_index: 2,
+            operation_centers: vec![],
+            operation_center_dropdown,
+            date_picker,
+            station_filter_input,
+            item_filter_input,
+            description: String::new(),
+            result: None,
+            button_loading: false,
+            default_days: cx.global::<AppSettings>().0.report.default_days,
+            _subscriptions: subscriptions,
+        };
+        view.load_operation_centers(window, cx);
+
+        view
+    }
+
+    fn load_operation_centers(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let window_handle = window.window_handle();
+        let source = ItemNeedsSource::new(cx);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = cx
+                .background_spawn(async move { source.client.get_operation_centers() })
+                .await;
+            let default_center_id = source.settings.operation_centers.first().cloned();
+            let _ = cx.update_window(window_handle, |_, window, cx| {
+                let _ = this.update(cx, |this, cx| {
+                    match result {
+                        Ok(operation_centers) => {
+                            let names = operation_centers
+                                .iter()
+                                .map(|center| center.name.clone())
+                                .collect::<Vec<_>>();
+                            // Start with the first operation center set in the settings
+                            let selected_index = operation_centers
+                                .iter()
+                                .position(|center| Some(&center.id) == default_center_id.as_ref())
+                                .unwrap_or_default();
+                            this.operation_center_dropdown.update(cx, |dropdown, cx| {
+                                dropdown.set_items(names, window, cx);
+                                dropdown.set_selected_index(
+                                    Some(IndexPath::new(selected_index)),
+                                    window,
+                                    cx,
+                                );
+                            });
+                            this.operation_centers = operation_centers;
+                        }
+                        Err(error) => {
+                            this.description = format!("無法從紅網取得營運中心資料\n{}", error)
+                        }
+                    }
+                    cx.notify();
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// Runs the query and keeps the response as received.
+    fn query(&mut self, cx: &mut Context<Self>) {
+        let Some(center) = self
+            .operation_center_dropdown
+            .read(cx)
+            .selected_index(cx)
+            .and_then(|index| self.operation_centers.get(index.row))
+        else {
+            self.description = "請選擇營運中心".to_string();
+            cx.notify();
+            return;
+        };
+        let date = self.date_picker.read(cx).date();
+        let (Some(start_date), Some(end_date)) = (date.start(), date.end()) else {
+            self.description = "請選擇需求日期區間".to_string();
+            cx.notify();
+            return;
+        };
+        let start_date: jiff::civil::Date = start_date.to_string().parse().unwrap();
+        let end_date: jiff::civil::Date = end_date.to_string().parse().unwrap();
+        let display_mode = DisplayMode::all()[self.display_mode_index];
+        let center_id = center.id.clone();
+        let file_stem = format!(
+            "{}_{}_{}_{}",
+            center.name,
+            display_mode.label(),
+            start_date.strftime("%Y%m%d"),
+            end_date.strftime("%Y%m%d")
+        );
+        let source = ItemNeedsSource::new(cx);
+        self.button_loading = true;
+        self.description = String::new();
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = cx
+                .background_spawn(async move {
+                    let raw = source.client.get_item_needs_raw(GetItemNeedsOptions {
+                        operation_center_id: &center_id,
+                        start_date: &start_date,
+                        end_date: &end_date,
+                        display_mode: &display_mode,
+                        department_id: &source.settings.department_id,
+                    })?;
+                    let item_needs = ItemNeeds::from_json(&raw)?;
+                    Ok::<_, tscred::Error>((raw, item_needs))
+                })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok((raw, item_needs)) => {
+                        this.result = Some(QueryResult {
+                            raw,
+                            item_needs,
+                            file_stem,
+                        })
+                    }
+                    Err(error) => {
+                        this.result = None;
+                        this.description = format!("無法從紅網取得贈品需求資料\n{}", error);
+                    }
+                }
+                this.button_loading = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn export(&mut self, csv: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(result) = &self.result else {
+            return;
+        };
+        let (file_name, contents) = if csv {
+            // Start with a byte order mark for Excel to read the Chinese
+            (
+                format!("{}.csv", result.file_stem),
+                format!("\u{feff}{}", result.item_needs.to_csv()),
+            )
+        } else {
+            (format!("{}.json", result.file_stem), result.raw.clone())
+        };
+        let window_handle = window.window_handle();
+        let paths_receiver = cx.prompt_for_new_path(&output_folder(cx), Some(&file_name));
+
+        cx.spawn(async move |_, cx: &mut AsyncApp| {
+            if let Ok(Ok(Some(path))) = cx.background_spawn(paths_receiver).await {
+                let path_string = path.to_string_lossy().to_string();
+                let write_result = cx
+                    .background_spawn(async move { fs::write(&path, contents) })
+                    .await;
+                Self::notify_export(write_result.is_ok(), path_string, window_handle, cx);
+            }
+        })
+        .detach();
+    }
+
+    fn notify_export(
+        is_written: bool,
+        path_string: String,
+        window_handle: AnyWindowHandle,
+        cx: &mut AsyncApp,
+    ) {
+        if is_written {
+            info!(path = path_string.as_str(); "wrote item needs");
+        } else {
+            error!(path = path_string.as_str(); "unable to write item needs");
+        }
+        let _ = cx.update_window(window_handle, |_, window, cx| {
+            let notification = if is_written {
+                Notification::new()
+                    .with_type(NotificationType::Success)
+                    .message(format!("已將原始資料儲存到 {}", path_string))
+            } else {
+                Notification::new()
+                    .with_type(NotificationType::Error)
+                    .message(format!("無法將原始資料儲存到 {}", path_string))
+            };
+            window.push_notification(notification, cx);
+        });
+    }
+
+    /// The item columns are those TSCRED prefixes with `A_`, the others describe the station.
+    fn is_item_column(column: &Column) -> bool {
+        column.field.starts_with("A_")
+    }
+
+    fn render_grid(&self, result: &QueryResult, cx: &App) -> impl IntoElement {
+        let station_filter = self
+            .station_filter_input
+            .read(cx)
+            .value()
+            .trim()
+            .to_string();
+        let item_filter = self.item_filter_input.read(cx).value().trim().to_string();
+        let columns = result
+            .item_needs
+            .columns()
+            .into_iter()
+            .filter(|column| {
+                !Self::is_item_column(column)
+                    || column.field.contains(&item_filter)
+                    || column.title.contains(&item_filter)
+            })
+            .collect::<Vec<_>>();
+        let rows = result
+            .item_needs
+            .rows()
+            .into_iter()
+            .filter(|row| {
+                station_filter.is_empty()
+                    || row.iter().any(|(field, value)| {
+                        !field.starts_with("A_") && value.contains(&station_filter)
+                    })
+            })
+            .collect::<Vec<_>>();
+        let row_count = rows.len();
+
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .children(columns.iter().map(|column| {
+                v_flex()
+                    .w_32()
+                    .flex_none()
+                    .child(column.title.clone())
+                    .child(div().text_xs().child(column.field.clone()))
+            }));
+        let rows = rows.into_iter().take(MAX_SHOWN_ROWS).map(|row| {
+            h_flex()
+                .gap_2()
+                .py_1()
+                .children(columns.iter().map(|column| {
+                    div()
+                        .w_32()
+                        .flex_none()
+                        .child(row.get(column.field.as_str()).cloned().unwrap_or_default())
+                }))
+        });
+
+        v_flex()
+            .gap_2()
+            .child(div().text_color(cx.theme().muted_foreground).child(
+                if row_count > MAX_SHOWN_ROWS {
+                    format!(
+                        "共 {} 列，只顯示前 {} 列，請匯出查看全部",
+                        row_count, MAX_SHOWN_ROWS
+                    )
+                } else {
+                    format!("共 {} 列", row_count)
+                },
+            ))
+            .child(
+                div()
+                    .id("inspector-grid")
+                    .overflow_scroll()
+                    .child(v_flex().child(header).children(rows)),
+            )
+    }
+}
+
+impl Render for InspectorView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let grid = self
+            .result
+            .as_ref()
+            .map(|result| self.render_grid(result, cx));
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(div().text_2xl().child("原始資料"))
+            .child(
+                v_form()
+                    .child(
+                        form_field().label("顯示方式").child(
+                            TabBar::new("inspector-display-mode-tab-bar")
+                                .segmented()
+                                .small()
+                                .selected_index(self.display_mode_index)
+                                .on_click(cx.listener(|this, index, _, cx| {
+                                    this.display_mode_index = *index;
+                                    cx.notify();
+                                }))
+                                .children(
+                                    DisplayMode::all()
+                                        .iter()
+                                        .map(|display_mode| Tab::new(display_mode.label())),
+                                ),
+                        ),
+                    )
+                    .child(
+                        form_field()
+                            .label("營運中心")
+                            .required(true)
+                            .child(Dropdown::new(&self.operation_center_dropdown)),
+                    )
+                    .child(
+                        form_field()
+                            .label("需求日期區間")
+                            .required(true)
+                            .child(DatePicker::new(&self.date_picker)),
+                    )
+                    .child(
+                        form_field()
+                            .no_label_indent()
+                            .when(!self.description.is_empty(), |this| {
+                                this.description(SharedString::from(&self.description))
+                            })
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("query-item-needs")
+                                            .primary()
+                                            .label("查詢")
+                                            .loading(self.button_loading)
+                                            .on_click(cx.listener(|this, _, _, cx| this.query(cx))),
+                                    )
+                                    .when(self.result.is_some(), |this| {
+                                        this.child(
+                                            Button::new("export-item-needs-json")
+                                                .label("匯出 JSON")
+                                                .on_click(cx.listener(|this, _, window, cx| {
+                                                    this.export(false, window, cx)
+                                                })),
+                                        )
+                                        .child(
+                                            Button::new("export-item-needs-csv")
+                                                .label("匯出 CSV")
+                                                .on_click(cx.listener(|this, _, window, cx| {
+                                                    this.export(true, window, cx)
+                                                })),
+                                        )
+                                    }),
+                            ),
+                    ),
+            )
+            .when(self.result.is_some(), |this| {
+                this.child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            div()
+                                .w_64()
+                                .child(TextInput::new(&self.station_filter_input)),
+                        )
+                        .child(div().w_64().child(TextInput::new(&self.item_filter_input))),
+                )
+            })
+            .children(grid)
+    }
+}
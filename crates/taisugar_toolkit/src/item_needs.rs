@@ -0,0 +1,133 @@
+use crate::app_settings::AppSettings;
+use crate::http::HttpClient;
+use futures::future::try_join_all;
+use gpui::{App, AppContext, AsyncApp};
+use std::sync::Arc;
+use toolkit_settings::TscredSettings;
+use tscred::{
+    ChunkOptions, ChunkSize, Client, DisplayMode, GetItemNeedsOptions, ItemNeeds, OperationCenter,
+    Progress,
+};
+
+/// The TSCRED client along with the department and operation centers to fetch the item needs of,
+/// as set when a fetch starts.
+#[derive(Clone)]
+pub struct ItemNeedsSource {
+    pub client: Arc<Client>,
+    pub settings: TscredSettings,
+}
+
+impl ItemNeedsSource {
+    pub fn new(cx: &App) -> Self {
+        let settings = cx.global::<AppSettings>().0.tscred.clone();
+        let agent = cx.global::<HttpClient>().0.clone();
+        ItemNeedsSource {
+            client: Arc::new(Client::with_base_url(agent, settings.base_url.clone())),
+            settings,
+        }
+    }
+
+    /// Gets the operation centers whose item needs are fetched.
+    pub fn get_operation_centers(&self) -> Result<Vec<OperationCenter>, tscred::Error> {
+        let centers = self.client.get_operation_centers()?;
+        Ok(centers
+            .into_iter()
+            .filter(|center| self.settings.includes_operation_center(&center.id))
+            .collect())
+    }
+}
+
+/// Fetches the item needs of the operation centers in the date range along with the names of the
+/// centers, or returns the message to show when it fails.
+pub async fn fetch_item_needs_by_center(
+    source: ItemNeedsSource,
+    start_date: jiff::civil::Date,
+    end_date: jiff::civil::Date,
+    cx: &mut AsyncApp,
+) -> Result<Vec<(String, ItemNeeds)>, String> {
+    let moved_source = source.clone();
+
+    // Get the operation centers from tscred
+    let operation_centers = cx
+        .background_spawn(async move { moved_source.get_operation_centers() })
+        .await
+        .map_err(|error| format!("無法從紅網取得營運中心資料\n{}", error))?;
+
+    // Get the item needs for each operation center
+    let mut tasks = vec![];
+    for center in operation_centers {
+        let moved_source = source.clone();
+        tasks.push(cx.background_spawn(async move {
+            // The dashboard shows no progress short of the whole fetch
+            fetch_center_item_needs(&moved_source, &center.id, &start_date, &end_date, |_| {})
+                .map(|item_needs| (center.name, item_needs))
+        }));
+    }
+    try_join_all(tasks)
+        .await
+        .map_err(|error| format!("無法從紅網取得贈品需求資料\n{:?}", error))
+}
+
+/// Fetches the item needs of the operation center in the date range, reporting the weeks fetched
+/// so far.
+pub fn fetch_center_item_needs(
+    source: &ItemNeedsSource,
+    operation_center_id: &str,
+    start_date: &jiff::civil::Date,
+    end_date: &jiff::civil::Date,
+    on_progress: impl Fn(Progress) + Sync,
+) -> Result<ItemNeeds, tscred::Error> {
+    // Fetch long date ranges week by week to stay within the request timeout
+    source.client.get_item_needs_chunked(
+        GetItemNeedsOptions {
+            operation_center_id,
+            start_date,
+            end_date,
+            display_mode: &DisplayMode::Details,
+            department_id: &source.settings.department_id,
+        },
+        &ChunkOptions {
+            chunk_size: ChunkSize::Week,
+            max_concurrency: 2,
+        },
+        on_progress,
+    )
+}
+
+/// The item needs of the operation centers fetched so far for a date range, kept when some of
+/// them fail so that retrying only fetches those.
+pub struct FetchedItemNeeds {
+    pub start_date: jiff::civil::Date,
+    pub end_date: jiff::civil::Date,
+    pub centers: Vec<OperationCenter>,
+    /// The item needs of each center, `None` until it is fetched.
+    pub item_needs: Vec<Option<ItemNeeds>>,
+}
+
+impl FetchedItemNeeds {
+    pub fn new(
+        start_date: jiff::civil::Date,
+        end_date: jiff::civil::Date,
+        centers: Vec<OperationCenter>,
+    ) -> Self {
+        let item_needs = centers.iter().map(|_| None).collect();
+        FetchedItemNeeds {
+            start_date,
+            end_date,
+            centers,
+            item_needs,
+        }
+    }
+
+    /// The indices of the centers whose item needs are yet to be fetched.
+    pub fn missing_centers(&self) -> Vec<usize> {
+        (0..self.centers.len())
+            .filter(|index| self.item_needs[*index].is_none())
+            .collect()
+    }
+
+    /// The item needs of all centers, or `None` if some are yet to be fetched.
+    pub fn into_item_needs(self) -> Option<Vec<ItemNeeds>> {
+        self.item_needs.into_iter().collect()
+    }
+}
@@ -0,0 +1,88 @@
+use crate::order_number::OrderNumbers;
+use crate::paths::data_file_path;
+use freebie::IssuedOrderNumber;
+use gpui::prelude::*;
+use gpui::{AnyWindowHandle, AsyncApp, Global, SharedString};
+use gpui_component::ContextModal;
+use gpui_component::notification::NotificationType;
+use log::warn;
+use order_ledger::{Ledger, NewOrder};
+use std::fs;
+
+const LEDGER_FILE_NAME: &str = "ledger.sqlite3";
+
+/// The record of the generated purchase orders.
+pub struct OrderLedger(pub Ledger);
+
+impl Global for OrderLedger {}
+
+impl OrderLedger {
+    /// Opens the ledger in the data directory, or keeps the records in memory if there is none.
+    pub fn open() -> anyhow::Result<Self> {
+        let Some(path) = data_file_path(LEDGER_FILE_NAME) else {
+            return Ok(OrderLedger(Ledger::open_in_memory()?));
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(OrderLedger(Ledger::open(path)?))
+    }
+}
+
+/// Records the order in the ledger and then remembers its order number, warning the user about
+/// whatever could not be saved. Returns the ID of the order in the ledger if it was recorded.
+pub fn record_order(
+    new_order: &NewOrder,
+    window_handle: AnyWindowHandle,
+    cx: &mut AsyncApp,
+) -> Option<i64> {
+    let issued_order_number = IssuedOrderNumber {
+        freebie: new_order.freebie,
+        notification_date: new_order.notification_date,
+        order_number: new_order.order_number,
+    };
+    let ledger_result =
+        cx.update_global::<OrderLedger, _>(|ledger, _| ledger.0.record_order(new_order));
+    // Remember the order number only once the order is in the ledger, so the two never disagree
+    let save_result = match &ledger_result {
+        Ok(Ok(_)) => Some(cx.update_global::<OrderNumbers, _>(|order_numbers, _| {
+            order_numbers.registry.record(issued_order_number)?;
+            order_numbers.save()
+        })),
+        _ => None,
+    };
+    let order_id = match &ledger_result {
+        Ok(Ok(order_id)) => Some(*order_id),
+        _ => None,
+    };
+    if let Ok(Err(error)) = &ledger_result {
+        warn!(order_number:% = new_order.order_number, error:%; "unable to record the order");
+    }
+    if let Some(Ok(Err(error))) = &save_result {
+        warn!(error:%; "unable to save the used order numbers");
+    }
+    let _ = cx.update_window(window_handle, |_, window, cx| {
+        if let Ok(Err(error)) = ledger_result {
+            window.push_notification(
+                (
+                    NotificationType::Warning,
+                    SharedString::from(format!(
+                        "無法將訂單記錄到訂購紀錄，訂單編號仍可再使用\n{}",
+                        error
+                    )),
+                ),
+                cx,
+            );
+        }
+        if let Some(Ok(Err(error))) = save_result {
+            window.push_notification(
+                (
+                    NotificationType::Warning,
+                    SharedString::from(format!("無法儲存已使用的訂單編號\n{}", error)),
+                ),
+                cx,
+            );
+        }
+    });
+    order_id
+}
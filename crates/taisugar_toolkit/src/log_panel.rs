@@ -0,0 +1,133 @@
+use crate::activity_log::{ActivityLog, LogEvent, log_file_path};
+use futures::StreamExt;
+use gpui::prelude::*;
+use gpui::{App, AsyncApp, Entity, WeakEntity, Window, div};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::{ActiveTheme, IconName, Sizable, h_flex, v_flex};
+use log::Level;
+use std::collections::VecDeque;
+
+/// The events kept in the panel, the older ones being left to the log file.
+const MAX_EVENTS: usize = 1000;
+
+pub struct LogPanel {
+    /// The events logged since the launch, newest first.
+    events: VecDeque<LogEvent>,
+}
+
+impl LogPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+        // Show the events as they are logged
+        if let Some(mut receiver) = cx.global_mut::<ActivityLog>().0.take() {
+            cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                while let Some(event) = receiver.next().await {
+                    let result = this.update(cx, |this, cx| {
+                        this.events.push_front(event);
+                        this.events.truncate(MAX_EVENTS);
+                        cx.notify();
+                    });
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        }
+
+        LogPanel {
+            events: VecDeque::new(),
+        }
+    }
+
+    /// The number of errors logged since the launch or the panel was cleared.
+    pub fn error_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| event.level == Level::Error)
+            .count()
+    }
+
+    fn render_event(&self, event: &LogEvent, cx: &App) -> impl IntoElement {
+        let level_color = match event.level {
+            Level::Error => cx.theme().danger,
+            Level::Warn => cx.theme().warning,
+            _ => cx.theme().muted_foreground,
+        };
+        h_flex()
+            .gap_2()
+            .items_start()
+            .child(
+                div()
+                    .w_24()
+                    .flex_none()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(event.time.format("%H:%M:%S%.3f").to_string()),
+            )
+            .child(
+                div()
+                    .w_12()
+                    .flex_none()
+                    .text_color(level_color)
+                    .child(event.level.to_string()),
+            )
+            .child(
+                div()
+                    .w_40()
+                    .flex_none()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(event.target.clone()),
+            )
+            .child(div().flex_1().child(event.details()))
+    }
+}
+
+impl Render for LogPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_1()
+            .p_2()
+            .text_xs()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().text_sm().flex_1().child("活動記錄"))
+                    .when_some(log_file_path(), |this, path| {
+                        this.child(
+                            Button::new("reveal-log-file")
+                                .xsmall()
+                                .ghost()
+                                .icon(IconName::FolderOpen)
+                                .label("開啟記錄檔位置")
+                                .on_click(move |_, _, cx| cx.reveal_path(&path)),
+                        )
+                    })
+                    .child(
+                        Button::new("clear-log-panel")
+                            .xsmall()
+                            .ghost()
+                            .icon(IconName::Delete)
+                            .label("清除")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.events.clear();
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .id("log-panel-events")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .child(
+                        v_flex()
+                            .gap_1()
+                            .children(self.events.iter().map(|event| self.render_event(event, cx))),
+                    ),
+            )
+    }
+}
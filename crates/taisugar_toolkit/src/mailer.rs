@@ -0,0 +1,191 @@
+use crate::ledger::OrderLedger;
+use crate::order_preview::PreviewedOrder;
+use crate::paths::config_file_path;
+use gpui::prelude::*;
+use gpui::{AnyWindowHandle, App, AsyncApp, Global, SharedString, div};
+use gpui_component::modal::ModalButtonProps;
+use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::{ActiveTheme, ContextModal, h_flex, v_flex};
+use log::{error, info};
+use order_mailer::{AttachmentFormat, Mailer, MailerConfig, OrderDocument, OrderMail};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+const MAILER_CONFIG_FILE_NAME: &str = "mailer.toml";
+
+/// The mailer sending the order forms to the vendors, configured in the configuration directory.
+pub struct OrderMailer(pub Arc<Mailer>);
+
+impl Global for OrderMailer {}
+
+impl OrderMailer {
+    /// Loads the settings of the mailer, or the default ones without an SMTP server if there is no
+    /// configuration file.
+    pub fn load() -> anyhow::Result<Self> {
+        let config = match mailer_config_path().map(fs::read_to_string) {
+            Some(Ok(s)) => s.parse()?,
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => MailerConfig::default(),
+        };
+        Ok(OrderMailer(Arc::new(Mailer::new(config))))
+    }
+}
+
+pub fn mailer_config_path() -> Option<PathBuf> {
+    config_file_path(MAILER_CONFIG_FILE_NAME)
+}
+
+/// Composes the mail of the saved order form and asks the user to confirm its recipients and
+/// subject before sending it.
+pub fn confirm_and_send(
+    order_id: Option<i64>,
+    order: &PreviewedOrder,
+    format: AttachmentFormat,
+    path: &Path,
+    window_handle: AnyWindowHandle,
+    cx: &mut AsyncApp,
+) {
+    let Ok(mailer) = cx.update(|cx| cx.global::<OrderMailer>().0.clone()) else {
+        return;
+    };
+    let mail_result = fs::read(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| {
+            Ok(mailer.compose(OrderDocument {
+                order_id,
+                freebie: order.freebie,
+                order_number: order.order_number,
+                notification_date: order.notification_date,
+                format,
+                content,
+            })?)
+        });
+    let _ = cx.update_window(window_handle, |_, window, cx| {
+        let mail = match mail_result {
+            Ok(mail) => Arc::new(mail),
+            Err(error) => {
+                window.push_notification(
+                    (
+                        NotificationType::Error,
+                        SharedString::from(format!(
+                            "無法寄出{}每週訂購單\n{}",
+                            order.freebie.name(),
+                            error
+                        )),
+                    ),
+                    cx,
+                );
+                return;
+            }
+        };
+        window.open_modal(cx, move |modal, _, cx| {
+            let mailer = mailer.clone();
+            let sent_mail = mail.clone();
+            let field = |label: &'static str, value: String| {
+                h_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(
+                        div()
+                            .w_16()
+                            .flex_none()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(label),
+                    )
+                    .child(div().flex_1().child(value))
+            };
+            modal
+                .title("寄送訂購單")
+                .confirm()
+                .button_props(
+                    ModalButtonProps::default()
+                        .ok_text("寄出")
+                        .cancel_text("取消"),
+                )
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(field("收件者", mail.recipients.join(", ")))
+                        .when(!mail.cc.is_empty(), |this| {
+                            this.child(field("副本", mail.cc.join(", ")))
+                        })
+                        .child(field("主旨", mail.subject.clone()))
+                        .child(field("附件", mail.attachment_name.clone()))
+                        .child(
+                            v_flex()
+                                .p_2()
+                                .border_1()
+                                .border_color(cx.theme().border)
+                                .children(
+                                    mail.body.lines().map(|line| div().child(line.to_string())),
+                                ),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    send_mail(
+                        mailer.clone(),
+                        sent_mail.clone(),
+                        window.window_handle(),
+                        cx,
+                    );
+                    true
+                })
+        });
+    });
+}
+
+/// Sends the mail in the background and records it in the ledger.
+fn send_mail(
+    mailer: Arc<Mailer>,
+    mail: Arc<OrderMail>,
+    window_handle: AnyWindowHandle,
+    cx: &mut App,
+) {
+    let freebie_name = mail.freebie.name();
+    let recipients = mail.recipients.join(", ");
+    cx.spawn(async move |cx: &mut AsyncApp| {
+        let send_result = cx.background_spawn(async move { mailer.send(&mail) }).await;
+        match &send_result {
+            Ok(_) => info!(
+                freebie = freebie_name,
+                recipients = recipients.as_str();
+                "sent purchase order"
+            ),
+            Err(error) => error!(
+                freebie = freebie_name,
+                recipients = recipients.as_str(),
+                error:%;
+                "unable to send purchase order"
+            ),
+        }
+        let ledger_result = send_result.as_ref().ok().map(|sent_mail| {
+            cx.update_global::<OrderLedger, _>(|ledger, _| ledger.0.record_sent_mail(sent_mail))
+        });
+
+        let _ = cx.update_window(window_handle, |_, window, cx| {
+            let notification = match send_result {
+                Ok(_) => Notification::new()
+                    .with_type(NotificationType::Success)
+                    .message(format!("已將{}每週訂購單寄給 {}", freebie_name, recipients)),
+                Err(error) => Notification::new()
+                    .with_type(NotificationType::Error)
+                    .message(format!(
+                        "無法將{}每週訂購單寄給 {}\nError: {}",
+                        freebie_name, recipients, error
+                    )),
+            };
+            window.push_notification(notification, cx);
+            if let Some(Ok(Err(error))) = ledger_result {
+                window.push_notification(
+                    (
+                        NotificationType::Warning,
+                        SharedString::from(format!("無法將寄件記錄到訂購紀錄\n{}", error)),
+                    ),
+                    cx,
+                );
+            }
+        });
+    })
+    .detach();
+}
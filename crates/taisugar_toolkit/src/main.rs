@@ -3,41 +3,100 @@
     windows_subsystem = "windows"
 )]
 
+mod activity_log;
+mod app_settings;
 mod assets;
+mod calendar;
+mod dashboard;
+mod delivery_entry;
 mod delivery_record;
+mod generation;
 mod http;
+mod inspector;
+mod item_needs;
+mod ledger;
+mod log_panel;
+mod mailer;
+mod monthly_report;
+mod order_file;
+mod order_number;
+mod order_preview;
+mod order_status;
+mod paths;
 mod purchase_order;
+mod settings;
+mod vendor_evaluation;
 mod view;
 
+use crate::activity_log::ActivityLog;
+use crate::app_settings::AppSettings;
 use crate::assets::Assets;
-use crate::http::HttpClient;
+use crate::calendar::WorkingDays;
+use crate::ledger::OrderLedger;
+use crate::mailer::OrderMailer;
+use crate::order_number::OrderNumbers;
 use gpui::prelude::*;
 use gpui::{
     Application, AsyncApp, Bounds, KeyBinding, TitlebarOptions, WindowBounds, WindowOptions,
     actions, px, size,
 };
 use gpui_component::Root;
-use std::time::Duration;
-use ureq::Agent;
+use log::{info, warn};
+use order_ledger::Ledger;
+use order_mailer::{Mailer, MailerConfig};
+use roc_calendar::WorkingDayCalendar;
+use std::sync::Arc;
+use toolkit_settings::Settings;
 use view::ToolkitView;
 
 actions!(window, [Quit]);
 
 fn main() {
+    let log_events = activity_log::init();
+    info!(version = env!("CARGO_PKG_VERSION"); "started");
+
     let application = Application::new().with_assets(Assets);
     application.run(|cx| {
         // Initialize GPUI component
         gpui_component::init(cx);
 
-        // Set locale
-        gpui_component::set_locale("zh-HK");
-
         // Set global states
-        let config = Agent::config_builder()
-            .user_agent("")
-            .timeout_global(Some(Duration::from_secs(5)))
-            .build();
-        cx.set_global(HttpClient(Agent::new_with_config(config)));
+        cx.set_global(ActivityLog(Some(log_events)));
+
+        // Fall back to the default settings if they cannot be read
+        let settings = AppSettings::load().unwrap_or_else(|e| {
+            warn!(error:% = e; "unable to load the settings");
+            Settings::default()
+        });
+        AppSettings::apply(settings, None, cx);
+
+        // Fall back to the bundled calendar if the user days off cannot be read
+        let working_days = WorkingDays::load().unwrap_or_else(|e| {
+            warn!(error:% = e; "unable to load the user days off");
+            WorkingDays(Arc::new(WorkingDayCalendar::bundled()))
+        });
+        cx.set_global(working_days);
+
+        // Keep the order numbers in memory only if they cannot be read, to not overwrite the file
+        let order_numbers = OrderNumbers::load().unwrap_or_else(|e| {
+            warn!(error:% = e; "unable to load the used order numbers");
+            OrderNumbers::default()
+        });
+        cx.set_global(order_numbers);
+
+        // Keep the ledger in memory if the database cannot be opened
+        let ledger = OrderLedger::open().unwrap_or_else(|e| {
+            warn!(error:% = e; "unable to open the order ledger");
+            OrderLedger(Ledger::open_in_memory().expect("unable to open in-memory ledger"))
+        });
+        cx.set_global(ledger);
+
+        // Leave the SMTP server unconfigured if the mailer settings cannot be read
+        let mailer = OrderMailer::load().unwrap_or_else(|e| {
+            warn!(error:% = e; "unable to load the mailer settings");
+            OrderMailer(Arc::new(Mailer::new(MailerConfig::default())))
+        });
+        cx.set_global(mailer);
 
         // Configure window options
         let bounds = Bounds::centered(None, size(px(1280.), px(720.)), cx);
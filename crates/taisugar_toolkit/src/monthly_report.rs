@@ -0,0 +1,279 @@
+use crate::app_settings::output_folder;
+use crate::calendar::to_jiff_date;
+use crate::http::HttpClient;
+use anyhow::anyhow;
+use chrono::Local;
+use daily_necessities::{Client, PurchaseList};
+use gpui::prelude::*;
+use gpui::{
+    AnyWindowHandle, App, AsyncApp, Entity, PathPromptOptions, SharedString, WeakEntity, Window,
+    div,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputState, TextInput};
+use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::{ContextModal, h_flex, v_flex};
+use log::{error, info};
+use purchase_report::{ReturnsReconciliation, SettlementStatement};
+use roc_calendar::RocYearMonth;
+use std::path::Path;
+use umya_spreadsheet::writer;
+use ureq::Agent;
+
+/// The month and the account of the daily necessities site to fetch the purchase list with, as
+/// set when a report is requested.
+struct PurchaseListSource {
+    agent: Agent,
+    username: String,
+    password: String,
+    month: RocYearMonth,
+}
+
+impl PurchaseListSource {
+    fn get_purchase_list(self) -> anyhow::Result<PurchaseList> {
+        let mut client = Client::new(self.agent, self.username, self.password);
+        Ok(client.get_purchase_list(
+            &self.month.first_day().into(),
+            &self.month.last_day().into(),
+        )?)
+    }
+}
+
+pub struct MonthlyReportView {
+    month_input: Entity<InputState>,
+    month_description: String,
+    username_input: Entity<InputState>,
+    password_input: Entity<InputState>,
+    button_loading: bool,
+}
+
+impl MonthlyReportView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        // Settle the last month by default, as the reports are made at the month-end
+        let last_month = to_jiff_date(&Local::now().naive_local().date())
+            .and_then(|date| date.first_of_month().yesterday().ok())
+            .and_then(|date| RocYearMonth::try_from(date).ok())
+            .map(|month| month.to_string())
+            .unwrap_or_default();
+        let month_input = cx.new(|cx| InputState::new(window, cx).default_value(last_month));
+        let username_input = cx.new(|cx| InputState::new(window, cx));
+        let password_input = cx.new(|cx| InputState::new(window, cx).masked(true));
+
+        MonthlyReportView {
+            month_input,
+            month_description: String::new(),
+            username_input,
+            password_input,
+            button_loading: false,
+        }
+    }
+
+    fn get_source(&mut self, cx: &mut Context<Self>) -> Option<PurchaseListSource> {
+        let month = match self.month_input.read(cx).value().parse::<RocYearMonth>() {
+            Ok(month) => {
+                self.month_description = String::new();
+                month
+            }
+            Err(_) => {
+                self.month_description = "請輸入結算月份，例如 114年09月".to_string();
+                cx.notify();
+                return None;
+            }
+        };
+        Some(PurchaseListSource {
+            agent: cx.global::<HttpClient>().0.clone(),
+            username: self.username_input.read(cx).value().trim().to_string(),
+            password: self.password_input.read(cx).value().to_string(),
+            month,
+        })
+    }
+
+    fn save_settlement_statements(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(source) = self.get_source(cx) else {
+            return;
+        };
+        let window_handle = window.window_handle();
+        let paths_receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("選擇儲存結算表的資料夾".into()),
+        });
+        self.button_loading = true;
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let folder = cx
+                .background_spawn(paths_receiver)
+                .await
+                .ok()
+                .and_then(|paths| paths.ok().flatten())
+                .and_then(|paths| paths.into_iter().next());
+            if let Some(folder) = folder {
+                let path_string = folder.to_string_lossy().to_string();
+                let result = cx
+                    .background_spawn(async move {
+                        let purchase_list = source.get_purchase_list()?;
+                        Self::write_settlement_statements(&purchase_list, &folder)
+                    })
+                    .await;
+                Self::notify_saved(result, "結算表", path_string, window_handle, cx);
+            }
+            let _ = this.update(cx, |this, cx| {
+                this.button_loading = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Writes the statement of every supplier in the purchase list into the folder and returns the
+    /// number of statements written.
+    fn write_settlement_statements(
+        purchase_list: &PurchaseList,
+        folder: &Path,
+    ) -> anyhow::Result<usize> {
+        let statements = SettlementStatement::from_purchase_list(purchase_list)?;
+        for statement in &statements {
+            let spreadsheet = purchase_report::generate_settlement_statement_report(statement)?;
+            let path = folder.join(statement.file_name());
+            writer::xlsx::write(&spreadsheet, &path)
+                .map_err(|error| anyhow!("{}: {}", path.display(), error))?;
+        }
+        Ok(statements.len())
+    }
+
+    fn save_returns_reconciliation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(source) = self.get_source(cx) else {
+            return;
+        };
+        let window_handle = window.window_handle();
+        let file_name = format!("{}退貨對帳表.xlsx", source.month);
+        let paths_receiver = cx.prompt_for_new_path(&output_folder(cx), Some(&file_name));
+        self.button_loading = true;
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            if let Ok(Ok(Some(path))) = cx.background_spawn(paths_receiver).await {
+                let path_string = path.to_string_lossy().to_string();
+                let result = cx
+                    .background_spawn(async move {
+                        let purchase_list = source.get_purchase_list()?;
+                        Self::write_returns_reconciliation(&purchase_list, &path)
+                    })
+                    .await;
+                Self::notify_saved(result, "退貨對帳表", path_string, window_handle, cx);
+            }
+            let _ = this.update(cx, |this, cx| {
+                this.button_loading = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Writes the reconciliation of the returns in the purchase list, unless it is empty, and
+    /// returns the number of workbooks written.
+    fn write_returns_reconciliation(
+        purchase_list: &PurchaseList,
+        path: &Path,
+    ) -> anyhow::Result<usize> {
+        if purchase_list.iter().next().is_none() {
+            return Ok(0);
+        }
+        let reconciliation = ReturnsReconciliation::new(purchase_list)?;
+        let spreadsheet = purchase_report::generate_returns_reconciliation_report(&reconciliation)?;
+        writer::xlsx::write(&spreadsheet, path)?;
+        Ok(1)
+    }
+
+    fn notify_saved(
+        result: anyhow::Result<usize>,
+        report: &str,
+        path_string: String,
+        window_handle: AnyWindowHandle,
+        cx: &mut AsyncApp,
+    ) {
+        match &result {
+            Ok(count) => info!(report, count, path = path_string.as_str(); "wrote monthly report"),
+            Err(error) => {
+                error!(report, error:%, path = path_string.as_str(); "unable to write monthly report")
+            }
+        }
+        let _ = cx.update_window(window_handle, |_, window, cx| {
+            let notification = match result {
+                Ok(0) => Notification::new()
+                    .with_type(NotificationType::Warning)
+                    .message("該月份沒有進貨資料"),
+                Ok(count) => Notification::new()
+                    .with_type(NotificationType::Success)
+                    .message(format!("已將 {} 份{}儲存到 {}", count, report, path_string)),
+                Err(error) => Notification::new()
+                    .with_type(NotificationType::Error)
+                    .message(format!("無法產生{}\n{}", report, error)),
+            };
+            window.push_notification(notification, cx);
+        });
+    }
+}
+
+impl Render for MonthlyReportView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(div().text_2xl().child("月結報表"))
+            .child(
+                v_form()
+                    .child(
+                        form_field()
+                            .label("結算月份")
+                            .required(true)
+                            .when(!self.month_description.is_empty(), |this| {
+                                this.description(SharedString::from(&self.month_description))
+                            })
+                            .child(TextInput::new(&self.month_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("日用品系統帳號")
+                            .required(true)
+                            .child(TextInput::new(&self.username_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("日用品系統密碼")
+                            .required(true)
+                            .child(TextInput::new(&self.password_input).mask_toggle()),
+                    )
+                    .child(
+                        form_field().no_label_indent().child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("save-settlement-statements")
+                                        .primary()
+                                        .label("產生廠商結算表")
+                                        .loading(self.button_loading)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.save_settlement_statements(window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("save-returns-reconciliation")
+                                        .label("產生退貨對帳表")
+                                        .loading(self.button_loading)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.save_returns_reconciliation(window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
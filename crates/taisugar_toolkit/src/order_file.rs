@@ -0,0 +1,193 @@
+use crate::assets::Assets;
+use crate::paths::cjk_font_path;
+use anyhow::anyhow;
+use freebie::{BatchOutcome, BatchReport, Freebie, OrderNumber};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use toolkit_settings::{Archive, Settings};
+use umya_spreadsheet::{Spreadsheet, reader, writer};
+
+/// Where the generated weekly order is saved.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum SaveMode {
+    /// A new workbook holding only the weekly order.
+    NewWorkbook,
+    /// A new sheet of the yearly workbook of the freebie.
+    YearlyWorkbook,
+    /// A PDF document of the weekly order, ready to be faxed or mailed.
+    Pdf,
+}
+
+/// Where the weekly orders of all freebies generated at once are saved.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum BatchOutput {
+    /// One workbook with a sheet per freebie.
+    Workbook,
+    /// A workbook per freebie in a folder.
+    Folder,
+    /// A workbook per freebie filed in the archive.
+    Archive,
+}
+
+/// Reads the bundled template of the order form of the freebie.
+pub fn load_template(freebie: &Freebie) -> anyhow::Result<Spreadsheet> {
+    let template_path = match freebie {
+        Freebie::Tissue60 => "templates/60抽面紙每週訂購單.xlsx",
+        Freebie::Tissue110 => "templates/110抽面紙每週訂購單.xlsx",
+        Freebie::MineralWater => "templates/礦泉水每週訂購單.xlsx",
+    };
+    let template_file = Assets::get(template_path).ok_or(anyhow!("Failed to get template file"))?;
+    reader::xlsx::read_reader(Cursor::new(template_file.data), true)
+        .map_err(|error| anyhow!("Failed to read template: {}", error))
+}
+
+/// Saves the weekly order at the path in the save mode and returns the path it was saved to,
+/// never overwriting an order form filed in the archive before.
+pub fn write_order(
+    purchase_order: &Spreadsheet,
+    path: PathBuf,
+    save_mode: SaveMode,
+    freebie: &Freebie,
+    order_number: &OrderNumber,
+    is_archived: bool,
+) -> anyhow::Result<PathBuf> {
+    if is_archived {
+        Archive::prepare(&path)?;
+    }
+    match save_mode {
+        SaveMode::NewWorkbook => writer::xlsx::write(purchase_order, &path)
+            .map(|_| path)
+            .map_err(|error| anyhow!("{}", error)),
+        SaveMode::YearlyWorkbook => {
+            append_to_yearly_workbook(purchase_order, &path, freebie, order_number)
+        }
+        SaveMode::Pdf => write_pdf(purchase_order, &path).map(|_| path),
+    }
+}
+
+/// Adds the weekly order to the yearly workbook at the path, converting a legacy `.xls` or
+/// `.ods` workbook into an `.xlsx` one beside it, and returns the path it was saved to.
+fn append_to_yearly_workbook(
+    purchase_order: &Spreadsheet,
+    path: &Path,
+    freebie: &Freebie,
+    order_number: &OrderNumber,
+) -> anyhow::Result<PathBuf> {
+    let is_xlsx = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("xlsx"));
+    let (mut yearly_workbook, output_path) = if is_xlsx {
+        let yearly_workbook = reader::xlsx::read(path)
+            .map_err(|error| anyhow!("Failed to read yearly workbook: {}", error))?;
+        (yearly_workbook, path.to_path_buf())
+    } else {
+        // Never overwrite a workbook converted before, which may hold newer orders
+        let output_path = path.with_extension("xlsx");
+        if output_path.exists() {
+            return Err(anyhow!(
+                "{} already exists, choose it instead",
+                output_path.display()
+            ));
+        }
+        (legacy_import::convert_workbook(path)?, output_path)
+    };
+
+    freebie::append_purchase_order(&mut yearly_workbook, purchase_order, freebie, order_number)?;
+    writer::xlsx::write(&yearly_workbook, &output_path)
+        .map_err(|error| anyhow!("Failed to write yearly workbook: {}", error))?;
+
+    Ok(output_path)
+}
+
+/// Renders the weekly order as a PDF document with the Chinese font found on the system.
+fn write_pdf(purchase_order: &Spreadsheet, path: &Path) -> anyhow::Result<()> {
+    let font_path = cjk_font_path().ok_or(anyhow!(
+        "找不到可嵌入 PDF 的中文字型，請將 TrueType 字型複製為設定資料夾中的 font.ttf"
+    ))?;
+    let font = fs::read(&font_path)
+        .map_err(|error| anyhow!("Failed to read {}: {}", font_path.display(), error))?;
+    let pdf = spreadsheet_pdf::render_spreadsheet(purchase_order, &font)?;
+
+    // Write a temporary file first as the xlsx writer does, so a failed write leaves no
+    // partial document behind to block saving it again
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push("tmp");
+    if let Err(error) = fs::write(&temp_path, pdf).and_then(|_| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Writes the generated weekly orders of a batch and returns the path of each freebie, naming
+/// the workbooks of a folder or the archive after the patterns in the settings. The workbooks
+/// are added to `written` as they are written, and the ones in it already are kept as written
+/// by an earlier try.
+pub fn write_batch(
+    reports: &[BatchReport],
+    output: BatchOutput,
+    path: &Path,
+    settings: &Settings,
+    notification_date: &jiff::civil::Date,
+    written: &mut Vec<PathBuf>,
+) -> anyhow::Result<Vec<(Freebie, PathBuf)>> {
+    let generated = reports
+        .iter()
+        .filter_map(|report| match &report.outcome {
+            BatchOutcome::Generated { spreadsheet, .. } => Some((report, spreadsheet)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    match output {
+        BatchOutput::Workbook => {
+            writer::xlsx::write(&freebie::combine_batch_reports(reports), path)
+                .map_err(|error| anyhow!("{}", error))?;
+            Ok(generated
+                .iter()
+                .map(|(report, _)| (report.freebie, path.to_path_buf()))
+                .collect())
+        }
+        BatchOutput::Folder | BatchOutput::Archive => {
+            let archive = Archive::new(path, &settings.archive.layout);
+            let paths = generated
+                .iter()
+                .map(|(report, _)| match output {
+                    BatchOutput::Archive => archive.order_path(
+                        &report.freebie,
+                        &report.order_number,
+                        notification_date,
+                        "xlsx",
+                    ),
+                    _ => path.join(settings.output.order_file_name(
+                        &report.freebie,
+                        &report.order_number,
+                        notification_date,
+                        "xlsx",
+                    )),
+                })
+                .collect::<Vec<_>>();
+
+            // Check every file before writing any of them to leave no order half saved
+            if let Some(path) = paths
+                .iter()
+                .find(|path| !written.contains(path) && path.exists())
+            {
+                return Err(anyhow!("{} already exists", path.display()));
+            }
+            for ((_, spreadsheet), path) in generated.iter().zip(paths.iter()) {
+                if written.contains(path) {
+                    continue;
+                }
+                Archive::prepare(path)?;
+                writer::xlsx::write(spreadsheet, path).map_err(|error| anyhow!("{}", error))?;
+                written.push(path.clone());
+            }
+            Ok(generated
+                .iter()
+                .zip(paths)
+                .map(|((report, _), path)| (report.freebie, path))
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::paths::config_file_path;
+use freebie::OrderNumberRegistry;
+use gpui::Global;
+use std::path::PathBuf;
+use std::{fs, io};
+
+const ORDER_NUMBERS_FILE_NAME: &str = "order-numbers.csv";
+
+/// The order numbers used so far, saved in the configuration directory.
+#[derive(Default)]
+pub struct OrderNumbers {
+    pub registry: OrderNumberRegistry,
+    path: Option<PathBuf>,
+}
+
+impl Global for OrderNumbers {}
+
+impl OrderNumbers {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = config_file_path(ORDER_NUMBERS_FILE_NAME);
+        let registry = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(s)) => s.parse()?,
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => OrderNumberRegistry::default(),
+        };
+        Ok(OrderNumbers { registry, path })
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.registry.to_string())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use freebie::{Freebie, OrderNumber, QuantityOverride, StationQuantity};
+use gpui::prelude::*;
+use gpui::{App, Entity, SharedString, Subscription, Window, div};
+use gpui_component::input::{InputEvent, InputState, TextInput};
+use gpui_component::{ActiveTheme, Sizable, h_flex, v_flex};
+use std::sync::Arc;
+use tscred::ItemNeeds;
+
+/// The weekly order being previewed.
+#[derive(Copy, Clone)]
+pub struct PreviewedOrder {
+    pub freebie: Freebie,
+    pub notification_date: jiff::civil::Date,
+    pub order_number: OrderNumber,
+    pub report_start_date: jiff::civil::Date,
+    pub report_end_date: jiff::civil::Date,
+}
+
+/// A generated weekly order shown for review before saving, whose quantities the user may adjust.
+pub struct OrderPreview {
+    pub order: PreviewedOrder,
+    pub item_needs: Arc<Vec<ItemNeeds>>,
+    rows: Vec<PreviewRow>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// A station of the preview, with the inputs of its quantity and the reason to adjust it.
+struct PreviewRow {
+    station: StationQuantity,
+    quantity_input: Entity<InputState>,
+    reason_input: Entity<InputState>,
+}
+
+impl PreviewRow {
+    /// The quantity in the input, or `None` if it is not a whole number.
+    fn quantity(&self, cx: &App) -> Option<u64> {
+        self.quantity_input.read(cx).value().trim().parse().ok()
+    }
+
+    fn is_adjusted(&self, cx: &App) -> bool {
+        self.quantity(cx) != Some(self.station.quantity)
+    }
+}
+
+impl OrderPreview {
+    /// Creates the preview of the stations, notifying the view to update the totals whenever a
+    /// quantity changes.
+    pub fn new<V: 'static>(
+        order: PreviewedOrder,
+        item_needs: Arc<Vec<ItemNeeds>>,
+        stations: Vec<StationQuantity>,
+        window: &mut Window,
+        cx: &mut Context<V>,
+    ) -> Self {
+        let mut subscriptions = vec![];
+        let rows = stations
+            .into_iter()
+            .map(|station| {
+                let quantity = station.quantity.to_string();
+                let quantity_input =
+                    cx.new(|cx| InputState::new(window, cx).default_value(quantity));
+                let reason_input = cx.new(|cx| InputState::new(window, cx).placeholder("調整原因"));
+                subscriptions.push(cx.subscribe(
+                    &quantity_input,
+                    |_, _, event: &InputEvent, cx| {
+                        if let InputEvent::Change = event {
+                            cx.notify();
+                        }
+                    },
+                ));
+                PreviewRow {
+                    station,
+                    quantity_input,
+                    reason_input,
+                }
+            })
+            .collect();
+
+        OrderPreview {
+            order,
+            item_needs,
+            rows,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// The sum of the quantities in the inputs, counting the invalid ones as zero.
+    pub fn total_quantity(&self, cx: &App) -> u64 {
+        self.rows
+            .iter()
+            .map(|row| row.quantity(cx).unwrap_or_default())
+            .sum()
+    }
+
+    /// Collects the adjusted quantities, or returns the message describing the first invalid row.
+    pub fn overrides(&self, cx: &App) -> Result<Vec<QuantityOverride>, String> {
+        let mut overrides = vec![];
+        for row in self.rows.iter().filter(|row| row.is_adjusted(cx)) {
+            let station_name = &row.station.station_name;
+            let Some(quantity) = row.quantity(cx) else {
+                return Err(format!("{}的數量必須是整數", station_name));
+            };
+            let reason = row.reason_input.read(cx).value().trim().to_string();
+            if reason.is_empty() {
+                return Err(format!("請填寫{}調整數量的原因", station_name));
+            }
+            overrides.push(QuantityOverride {
+                station_name: station_name.clone(),
+                quantity,
+                reason,
+            });
+        }
+        Ok(overrides)
+    }
+
+    pub fn render(&self, cx: &App) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_32().child("站別"))
+            .child(div().w_24().child("需求數量"))
+            .child(div().w_32().child("訂購數量"))
+            .child(div().flex_1().child("調整原因"));
+        let rows = self.rows.iter().map(|row| {
+            // Highlight the adjusted quantities
+            h_flex()
+                .gap_2()
+                .py_1()
+                .when(row.is_adjusted(cx), |this| {
+                    this.bg(cx.theme().warning.opacity(0.2))
+                })
+                .child(div().w_32().child(row.station.station_name.clone()))
+                .child(div().w_24().child(row.station.quantity.to_string()))
+                .child(
+                    div()
+                        .w_32()
+                        .child(TextInput::new(&row.quantity_input).small()),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .child(TextInput::new(&row.reason_input).small()),
+                )
+        });
+        let total = h_flex()
+            .gap_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .child(div().w_32().child("合計"))
+            .child(
+                div().w_24().child(
+                    self.rows
+                        .iter()
+                        .map(|row| row.station.quantity)
+                        .sum::<u64>()
+                        .to_string(),
+                ),
+            )
+            .child(
+                div()
+                    .w_32()
+                    .child(SharedString::from(self.total_quantity(cx).to_string())),
+            );
+
+        v_flex().child(header).children(rows).child(total)
+    }
+}
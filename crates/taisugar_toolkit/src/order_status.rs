@@ -0,0 +1,525 @@
+use crate::calendar::{WorkingDays, to_jiff_date};
+use crate::ledger::OrderLedger;
+use crate::paths::data_file_path;
+use anyhow::anyhow;
+use chrono::Local;
+use gpui::prelude::*;
+use gpui::{
+    App, AsyncApp, Entity, PathPromptOptions, SharedString, Subscription, WeakEntity, Window, div,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::calendar::Date;
+use gpui_component::date_picker::{DatePicker, DatePickerState};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputEvent, InputState, TextInput};
+use gpui_component::notification::NotificationType;
+use gpui_component::{ActiveTheme, ContextModal, Sizable, h_flex, v_flex};
+use log::warn;
+use order_ledger::{Confirmation, Order, OrderQuery, OrderStatus};
+use roc_calendar::RocDate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The folder in the data directory keeping the scans of the confirmed orders.
+const SCANS_DIR_NAME: &str = "scans";
+
+/// The working days to wait for the confirmation of a sent order before listing it as overdue.
+const DEFAULT_OVERDUE_WORKING_DAYS: i32 = 3;
+
+pub struct OrderStatusView {
+    orders: Vec<Order>,
+    overdue_days_input: Entity<InputState>,
+    /// The order whose confirmation is being recorded.
+    confirming_order: Option<Order>,
+    confirmation_date_picker: Entity<DatePickerState>,
+    confirmer_input: Entity<InputState>,
+    scan_path: Option<PathBuf>,
+    confirmation_description: String,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl OrderStatusView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let overdue_days_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .validate(|value, _| value.is_empty() || value.parse::<u32>().is_ok())
+                .default_value(DEFAULT_OVERDUE_WORKING_DAYS.to_string())
+        });
+        let confirmation_date_picker = cx.new(|cx| DatePickerState::new(window, cx));
+        let confirmer_input = cx.new(|cx| InputState::new(window, cx).placeholder("供貨商確認人"));
+
+        // List the overdue orders again whenever the working days change
+        let subscriptions =
+            vec![
+                cx.subscribe(&overdue_days_input, |_, _, event: &InputEvent, cx| {
+                    if let InputEvent::Change = event {
+                        cx.notify();
+                    }
+                }),
+            ];
+
+        let mut view = OrderStatusView {
+            orders: vec![],
+            overdue_days_input,
+            confirming_order: None,
+            confirmation_date_picker,
+            confirmer_input,
+            scan_path: None,
+            confirmation_description: String::new(),
+            _subscriptions: subscriptions,
+        };
+        view.refresh(cx);
+
+        view
+    }
+
+    /// Reloads the orders from the ledger, latest first.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        match cx
+            .global::<OrderLedger>()
+            .0
+            .find_orders(&OrderQuery::default())
+        {
+            Ok(mut orders) => {
+                orders.reverse();
+                self.orders = orders;
+            }
+            Err(error) => warn!(error:%; "unable to load the orders"),
+        }
+        cx.notify();
+    }
+
+    fn today() -> jiff::civil::Date {
+        to_jiff_date(&Local::now().naive_local().date()).unwrap()
+    }
+
+    /// The sent orders left unconfirmed for more than the working days in the input, with the
+    /// working days they have been waiting.
+    fn overdue_orders(&self, cx: &App) -> Vec<(&Order, i32)> {
+        let overdue_days = self
+            .overdue_days_input
+            .read(cx)
+            .value()
+            .parse()
+            .unwrap_or(DEFAULT_OVERDUE_WORKING_DAYS);
+        let calendar = &cx.global::<WorkingDays>().0;
+        let today = Self::today();
+        self.orders
+            .iter()
+            .filter_map(|order| {
+                order
+                    .unconfirmed_working_days(calendar, &today)
+                    .filter(|days| *days > overdue_days)
+                    .map(|days| (order, days))
+            })
+            .collect()
+    }
+
+    fn push_notification(
+        message: String,
+        notification_type: NotificationType,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.push_notification((notification_type, SharedString::from(message)), cx);
+    }
+
+    /// Moves the order to its next status as of today.
+    fn advance(
+        &mut self,
+        id: i64,
+        status: OrderStatus,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let today = Self::today();
+        let result =
+            cx.update_global::<OrderLedger, _>(|ledger, _| ledger.0.set_status(id, status, &today));
+        if let Err(error) = result {
+            Self::push_notification(
+                format!("無法更新訂單狀態\n{}", error),
+                NotificationType::Error,
+                window,
+                cx,
+            );
+        }
+        self.refresh(cx);
+    }
+
+    /// Opens the form to record the confirmation of the order, filled in with the recorded one.
+    fn start_confirming(&mut self, order: Order, window: &mut Window, cx: &mut Context<Self>) {
+        let (date, confirmer, scan_path) = match &order.confirmation {
+            Some(confirmation) => (
+                confirmation.confirmation_date,
+                confirmation.confirmer.clone(),
+                confirmation.scan_path.clone(),
+            ),
+            None => (Self::today(), String::new(), None),
+        };
+        if let Ok(date) = date.to_string().parse::<chrono::NaiveDate>() {
+            self.confirmation_date_picker
+                .update(cx, |state, cx| state.set_date(date, window, cx));
+        }
+        self.confirmer_input
+            .update(cx, |state, cx| state.set_value(confirmer, window, cx));
+        self.scan_path = scan_path;
+        self.confirmation_description = String::new();
+        self.confirming_order = Some(order);
+        cx.notify();
+    }
+
+    fn choose_scan(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        let paths_receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("選擇訂購單確認掃描檔".into()),
+        });
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let Ok(Ok(Some(paths))) = paths_receiver.await else {
+                return;
+            };
+            let _ = this.update(cx, |this, cx| {
+                this.scan_path = paths.into_iter().next();
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Copies the scan into the data directory so it stays with the ledger, unless it is already
+    /// there, and returns the path of the copy.
+    fn keep_scan(order: &Order, path: &Path) -> anyhow::Result<PathBuf> {
+        let dir = data_file_path(SCANS_DIR_NAME).ok_or(anyhow!("找不到資料資料夾"))?;
+        if path.starts_with(&dir) {
+            return Ok(path.to_path_buf());
+        }
+        fs::create_dir_all(&dir)?;
+        let mut file_name = format!(
+            "{}-{}{}",
+            order.id,
+            order.freebie.short_name(),
+            order.order_number
+        );
+        if let Some(extension) = path.extension() {
+            file_name.push('.');
+            file_name.push_str(&extension.to_string_lossy());
+        }
+        let scan_path = dir.join(file_name);
+        fs::copy(path, &scan_path)?;
+        Ok(scan_path)
+    }
+
+    fn save_confirmation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(order) = &self.confirming_order else {
+            return;
+        };
+        let Date::Single(Some(date)) = self.confirmation_date_picker.read(cx).date() else {
+            self.confirmation_description = "請選擇確認日期".to_string();
+            cx.notify();
+            return;
+        };
+        let confirmer = self.confirmer_input.read(cx).value().trim().to_string();
+        if confirmer.is_empty() {
+            self.confirmation_description = "請輸入確認人".to_string();
+            cx.notify();
+            return;
+        }
+        let scan_path = match self
+            .scan_path
+            .as_ref()
+            .map(|path| Self::keep_scan(order, path))
+            .transpose()
+        {
+            Ok(scan_path) => scan_path,
+            Err(error) => {
+                self.confirmation_description = format!("無法保存掃描檔\n{}", error);
+                cx.notify();
+                return;
+            }
+        };
+
+        let id = order.id;
+        let confirmation = Confirmation {
+            confirmation_date: to_jiff_date(&date).unwrap(),
+            confirmer,
+            scan_path,
+        };
+        let result = cx
+            .update_global::<OrderLedger, _>(|ledger, _| ledger.0.confirm_order(id, &confirmation));
+        match result {
+            Ok(()) => {
+                self.confirming_order = None;
+                self.confirmation_description = String::new();
+                Self::push_notification(
+                    "已登錄供貨商確認".to_string(),
+                    NotificationType::Success,
+                    window,
+                    cx,
+                );
+            }
+            Err(error) => self.confirmation_description = format!("無法登錄確認\n{}", error),
+        }
+        self.refresh(cx);
+    }
+
+    fn format_date(date: &jiff::civil::Date) -> String {
+        RocDate::try_from(*date)
+            .map(|date| date.to_string())
+            .unwrap_or_else(|_| date.to_string())
+    }
+
+    fn order_label(order: &Order) -> String {
+        format!(
+            "{} {}{}",
+            order.freebie.short_name(),
+            order.freebie.order_number_prefix(),
+            order.order_number
+        )
+    }
+
+    fn render_overdue(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let overdue_orders = self.overdue_orders(cx);
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().text_lg().child("逾期未確認"))
+                    .child(
+                        div().w_32().child(
+                            TextInput::new(&self.overdue_days_input)
+                                .small()
+                                .prefix("超過")
+                                .suffix("工作天"),
+                        ),
+                    ),
+            )
+            .map(|this| {
+                if overdue_orders.is_empty() {
+                    this.child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("沒有逾期未確認的訂單"),
+                    )
+                } else {
+                    this.children(overdue_orders.into_iter().map(|(order, days)| {
+                        h_flex()
+                            .gap_2()
+                            .py_1()
+                            .bg(cx.theme().warning.opacity(0.2))
+                            .child(div().w_40().child(Self::order_label(order)))
+                            .child(div().w_32().child(format!(
+                                "寄出 {}",
+                                Self::format_date(
+                                    &order.sent_date.unwrap_or(order.notification_date)
+                                )
+                            )))
+                            .child(div().flex_1().child(format!("已等待 {} 個工作天", days)))
+                    }))
+                }
+            })
+    }
+
+    fn render_orders(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_40().child("訂單"))
+            .child(div().w_24().child("通知日期"))
+            .child(div().w_24().child("寄出日期"))
+            .child(div().w_20().child("狀態"))
+            .child(div().w_40().child("確認"))
+            .child(div().flex_1());
+        let rows = self.orders.iter().map(|order| {
+            let id = order.id;
+            let next_status = order.status.next();
+            h_flex()
+                .gap_2()
+                .py_1()
+                .child(div().w_40().child(Self::order_label(order)))
+                .child(
+                    div()
+                        .w_24()
+                        .child(Self::format_date(&order.notification_date)),
+                )
+                .child(
+                    div().w_24().child(
+                        order
+                            .sent_date
+                            .map(|date| Self::format_date(&date))
+                            .unwrap_or_default(),
+                    ),
+                )
+                .child(div().w_20().child(order.status.label()))
+                .child(
+                    div().w_40().child(
+                        order
+                            .confirmation
+                            .as_ref()
+                            .map(|confirmation| {
+                                format!(
+                                    "{} {}",
+                                    Self::format_date(&confirmation.confirmation_date),
+                                    confirmation.confirmer
+                                )
+                            })
+                            .unwrap_or_default(),
+                    ),
+                )
+                .child(
+                    h_flex()
+                        .flex_1()
+                        .gap_1()
+                        .when(order.status <= OrderStatus::Confirmed, |this| {
+                            let order = order.clone();
+                            this.child(
+                                Button::new(("confirm-order", id as usize))
+                                    .small()
+                                    .label("登錄確認")
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.start_confirming(order.clone(), window, cx)
+                                    })),
+                            )
+                        })
+                        .when_some(
+                            next_status.filter(|status| *status != OrderStatus::Confirmed),
+                            |this, status| {
+                                this.child(
+                                    Button::new(("advance-order", id as usize))
+                                        .small()
+                                        .label(format!("標記{}", status.label()))
+                                        .on_click(cx.listener(move |this, _, window, cx| {
+                                            this.advance(id, status, window, cx)
+                                        })),
+                                )
+                            },
+                        )
+                        .when_some(
+                            order
+                                .confirmation
+                                .as_ref()
+                                .and_then(|confirmation| confirmation.scan_path.clone()),
+                            |this, scan_path| {
+                                this.child(
+                                    Button::new(("open-scan", id as usize))
+                                        .small()
+                                        .ghost()
+                                        .label("開啟掃描檔")
+                                        .on_click(move |_, _, cx| cx.open_with_system(&scan_path)),
+                                )
+                            },
+                        ),
+                )
+        });
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().text_lg().child("訂單狀態"))
+                    .child(
+                        Button::new("refresh-orders")
+                            .small()
+                            .ghost()
+                            .label("重新整理")
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .child(v_flex().child(header).children(rows))
+    }
+
+    fn render_confirmation_form(&self, order: &Order, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .text_lg()
+                    .child(format!("登錄{}確認", Self::order_label(order))),
+            )
+            .child(
+                v_form()
+                    .column(2)
+                    .child(
+                        form_field().label("確認日期").required(true).child(
+                            DatePicker::new(&self.confirmation_date_picker).number_of_months(1),
+                        ),
+                    )
+                    .child(
+                        form_field()
+                            .label("確認人")
+                            .required(true)
+                            .child(TextInput::new(&self.confirmer_input)),
+                    )
+                    .child(
+                        form_field().label("掃描檔").col_span(2).child(
+                            h_flex()
+                                .gap_2()
+                                .child(Button::new("choose-scan").label("選擇檔案").on_click(
+                                    cx.listener(|this, _, window, cx| this.choose_scan(window, cx)),
+                                ))
+                                .child(
+                                    self.scan_path
+                                        .as_ref()
+                                        .map(|path| path.to_string_lossy().to_string())
+                                        .unwrap_or_default(),
+                                ),
+                        ),
+                    )
+                    .when(!self.confirmation_description.is_empty(), |this| {
+                        this.child(
+                            form_field().no_label_indent().col_span(2).child(
+                                div()
+                                    .text_color(cx.theme().danger)
+                                    .child(SharedString::from(&self.confirmation_description)),
+                            ),
+                        )
+                    })
+                    .child(
+                        form_field().no_label_indent().col_span(2).child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("save-confirmation")
+                                        .primary()
+                                        .label("儲存確認")
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.save_confirmation(window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("cancel-confirmation")
+                                        .ghost()
+                                        .label("取消")
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.confirming_order = None;
+                                            cx.notify();
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+impl Render for OrderStatusView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(div().text_2xl().child("訂單追蹤"))
+            .child(self.render_overdue(cx))
+            .when_some(self.confirming_order.clone(), |this, order| {
+                this.child(self.render_confirmation_form(&order, cx))
+            })
+            .child(self.render_orders(cx))
+    }
+}
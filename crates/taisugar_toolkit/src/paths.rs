@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+/// Returns the path of a file in the configuration directory of the toolkit.
+pub fn config_file_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(file_name))
+}
+
+/// Returns the path of a file in the data directory of the toolkit.
+pub fn data_file_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(file_name))
+}
+
+/// The TrueType fonts with Chinese glyphs to embed in PDF documents, in order of preference. Font
+/// collections (`.ttc`) such as 微軟正黑體 cannot be embedded.
+const CJK_FONT_PATHS: &[&str] = &[
+    "C:\\Windows\\Fonts\\kaiu.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/arphic/ukai.ttf",
+    "/usr/share/fonts/truetype/arphic/uming.ttf",
+];
+
+/// Returns the path of the font to embed in PDF documents, preferring `font.ttf` in the
+/// configuration directory over the fonts of the system.
+pub fn cjk_font_path() -> Option<PathBuf> {
+    config_file_path("font.ttf")
+        .into_iter()
+        .chain(CJK_FONT_PATHS.iter().map(PathBuf::from))
+        .find(|path| path.is_file())
+}
@@ -1,35 +1,94 @@
-use crate::assets::Assets;
-use crate::http::HttpClient;
-use anyhow::anyhow;
-use chrono::{Datelike, Days, Local};
-use freebie::Freebie;
-use futures::future::try_join_all;
+use crate::app_settings::{AppSettings, default_report_range, output_folder};
+use crate::calendar::{WorkingDays, uncovered_year_description};
+use crate::generation::{GenerationProgress, GenerationStep, SavedSteps};
+use crate::item_needs::{FetchedItemNeeds, ItemNeedsSource, fetch_center_item_needs};
+use crate::ledger::record_order;
+use crate::mailer::{OrderMailer, confirm_and_send, mailer_config_path};
+use crate::order_file::{BatchOutput, SaveMode, load_template, write_batch, write_order};
+use crate::order_number::OrderNumbers;
+use crate::order_preview::{OrderPreview, PreviewedOrder};
+use chrono::{Days, Local};
+use freebie::{BatchOrder, BatchOutcome, BatchReport, Freebie, OrderNumber, QuantityOverride};
+use futures::StreamExt;
+use futures::channel::mpsc;
+use futures::future::Either;
+use futures::stream::{self, FuturesUnordered};
 use gpui::prelude::*;
-use gpui::{AnyWindowHandle, App, AsyncApp, Entity, SharedString, WeakEntity, Window, div};
+use gpui::{
+    AnyWindowHandle, App, AsyncApp, Entity, PathPromptOptions, SharedString, Subscription, Task,
+    WeakEntity, Window, div,
+};
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::calendar::{Date, Matcher};
-use gpui_component::date_picker::{DatePicker, DatePickerState};
+use gpui_component::checkbox::Checkbox;
+use gpui_component::date_picker::{DatePicker, DatePickerEvent, DatePickerState};
 use gpui_component::form::{form_field, v_form};
 use gpui_component::input::{InputState, TextInput};
 use gpui_component::notification::{Notification, NotificationType};
 use gpui_component::tab::{Tab, TabBar};
-use gpui_component::{ContextModal, Sizable, v_flex};
-use std::env;
-use std::io::Cursor;
+use gpui_component::{ActiveTheme, ContextModal, Disableable, Sizable, h_flex, v_flex};
+use log::{error, info};
+use order_ledger::NewOrder;
+use order_mailer::AttachmentFormat;
+use roc_calendar::RocDate;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tscred::{Client, DisplayMode, GetItemNeedsOptions};
-use umya_spreadsheet::{reader, writer};
+use tscred::{ItemNeeds, Progress};
+use umya_spreadsheet::Spreadsheet;
+
+/// What the user asked to generate, done again when retrying after a step failed or was
+/// cancelled.
+#[derive(Copy, Clone)]
+enum Generation {
+    /// Fetch the item needs and preview the order of the active freebie.
+    Preview,
+    /// Save the previewed order, then ask to mail it to the vendor if `send` is set.
+    Save { save_mode: SaveMode, send: bool },
+    /// Generate and save the orders of all freebies.
+    Batch(BatchOutput),
+}
+
+/// The previewed order, save mode and adjusted quantities a saved order form was generated from.
+type OrderKey = (Freebie, OrderNumber, SaveMode, Vec<QuantityOverride>);
+/// The date range, notification date, output and order numbers a batch was generated from.
+type BatchKey = (
+    jiff::civil::Date,
+    jiff::civil::Date,
+    jiff::civil::Date,
+    BatchOutput,
+    Vec<(Freebie, OrderNumber)>,
+);
 
 pub struct PurchaseOrderView {
     active_tab: usize,
     report_date_picker: Entity<DatePickerState>,
     report_date_description: String,
+    /// The default number of days of the report date range, to select the new default when it
+    /// changes.
+    default_days: u64,
     notification_date_picker: Entity<DatePickerState>,
     notification_date_description: String,
     order_number_input: Entity<InputState>,
     order_number_description: String,
+    /// The used order number which the user chose to reuse after being warned.
+    reused_order_number: Option<String>,
     submit_button_loading: bool,
-    tscred: Arc<Client>,
+    /// Whether to ask where to save an order form instead of filing it in the archive.
+    choose_path: bool,
+    /// The generated order of the active freebie waiting to be reviewed and saved.
+    preview: Option<OrderPreview>,
+    preview_description: String,
+    /// The steps of the running generation, or of the last one if it failed or was cancelled.
+    progress: Option<GenerationProgress>,
+    /// The running generation, cancelled when dropped.
+    generation_task: Option<Task<()>>,
+    last_generation: Option<Generation>,
+    /// The item needs fetched before the last generation failed or was cancelled.
+    fetched_item_needs: Option<FetchedItemNeeds>,
+    saved_order: Option<SavedSteps<OrderKey, Spreadsheet, Spreadsheet>>,
+    saved_batch: Option<SavedSteps<BatchKey, Vec<Spreadsheet>, Vec<BatchReport>>>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl PurchaseOrderView {
@@ -40,48 +99,73 @@ impl PurchaseOrderView {
     fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let now = Local::now().naive_local().date();
         let report_date_picker = cx.new(|cx| {
-            let start = now.checked_sub_days(Days::new(7)).unwrap();
-
             // Disable the dates after today
             let mut state = DatePickerState::range(window, cx).disabled_matcher(Matcher::range(
                 Some(now.checked_add_days(Days::new(1)).unwrap()),
                 None,
             ));
 
-            // Set the default date range to the last 7 days
-            state.set_date((start, now), window, cx);
+            // Set the default date range to the last days set in the settings
+            state.set_date(default_report_range(cx), window, cx);
 
             state
         });
+        let days_off_matcher = cx.global::<WorkingDays>().days_off_matcher();
         let notification_date_picker = cx.new(|cx| {
-            let mut state = DatePickerState::new(window, cx);
+            // Disable the weekends, holidays and days off
+            let mut state = DatePickerState::new(window, cx).disabled_matcher(days_off_matcher);
 
             // Set the default date to today
             state.set_date(now, window, cx);
 
             state
         });
-        let order_number_input = cx.new(|cx| {
-            let n_week_in_month = (now.day() - 1) / 7 + 1;
-            InputState::new(window, cx).default_value(format!(
-                "{}-{}",
-                now.month(),
-                n_week_in_month
-            ))
-        });
+        let order_number_input = cx.new(|cx| InputState::new(window, cx));
+
+        // Suggest the next order number whenever the notification date changes
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &notification_date_picker,
+                window,
+                |this, _, _: &DatePickerEvent, window, cx| this.suggest_order_number(window, cx),
+            ),
+            // Select the new default date range when it is changed in the settings
+            cx.observe_global_in::<AppSettings>(window, |this, window, cx| {
+                let default_days = cx.global::<AppSettings>().0.report.default_days;
+                if this.default_days != default_days {
+                    this.default_days = default_days;
+                    let range = default_report_range(cx);
+                    this.report_date_picker
+                        .update(cx, |state, cx| state.set_date(range, window, cx));
+                }
+            }),
+        ];
 
-        let agent = cx.global::<HttpClient>().0.clone();
-        PurchaseOrderView {
+        let mut view = PurchaseOrderView {
             active_tab: 0,
             report_date_picker,
             report_date_description: String::new(),
+            default_days: cx.global::<AppSettings>().0.report.default_days,
             notification_date_picker,
             notification_date_description: String::new(),
             order_number_input,
             order_number_description: String::new(),
+            reused_order_number: None,
             submit_button_loading: false,
-            tscred: Arc::new(Client::new(agent)),
-        }
+            choose_path: false,
+            preview: None,
+            preview_description: String::new(),
+            progress: None,
+            generation_task: None,
+            last_generation: None,
+            fetched_item_needs: None,
+            saved_order: None,
+            saved_batch: None,
+            _subscriptions: subscriptions,
+        };
+        view.suggest_order_number(window, cx);
+
+        view
     }
 
     fn get_active_freebie(&self) -> Option<Freebie> {
@@ -93,6 +177,44 @@ impl PurchaseOrderView {
         }
     }
 
+    fn get_notification_date(&self, cx: &App) -> Option<jiff::civil::Date> {
+        match self.notification_date_picker.read(cx).date() {
+            Date::Single(Some(date)) => date.to_string().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The next order number of each freebie in the month of the notification date.
+    fn get_batch_order_numbers(&self, cx: &App) -> Option<Vec<(Freebie, OrderNumber)>> {
+        let notification_date = self.get_notification_date(cx)?;
+        let registry = &cx.global::<OrderNumbers>().registry;
+        Some(
+            Freebie::all()
+                .into_iter()
+                .map(|freebie| (freebie, registry.next(&freebie, &notification_date)))
+                .collect(),
+        )
+    }
+
+    /// Fills in the next order number of the active freebie in the month of the notification date.
+    fn suggest_order_number(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(freebie), Some(notification_date)) =
+            (self.get_active_freebie(), self.get_notification_date(cx))
+        else {
+            return;
+        };
+        let order_number = cx
+            .global::<OrderNumbers>()
+            .registry
+            .next(&freebie, &notification_date);
+        self.order_number_input.update(cx, |state, cx| {
+            state.set_value(order_number.to_string(), window, cx)
+        });
+        self.order_number_description = String::new();
+        self.reused_order_number = None;
+        cx.notify();
+    }
+
     fn validate(&mut self, cx: &mut Context<Self>) -> bool {
         let report_date = self.report_date_picker.read(cx).date();
         let notification_date = self.notification_date_picker.read(cx).date();
@@ -115,51 +237,335 @@ impl PurchaseOrderView {
             }
         }
 
-        // Validate notification date
+        // Validate notification date, warning when the holidays of its year are unknown
         if let Date::Single(notification_date) = notification_date
             && notification_date.is_none()
         {
             self.notification_date_description = "請選擇通知日期".to_string();
             is_valid = false;
+        } else if let Some(notification_date) = self.get_notification_date(cx)
+            && !cx.global::<WorkingDays>().0.covers(&notification_date)
+        {
+            self.notification_date_description =
+                uncovered_year_description(&notification_date, "擬交貨日期");
         } else {
             self.notification_date_description = String::new();
         }
 
-        // Validate order number
-        if order_number.is_empty() {
+        // Validate order number, warning once before reusing a used one. The orders of all freebies
+        // take the next order numbers instead.
+        if self.get_active_freebie().is_none() {
+            self.order_number_description = String::new();
+        } else if order_number.is_empty() {
             self.order_number_description = "請輸入訂單編號".to_string();
             is_valid = false;
-        } else {
+        } else if let Ok(parsed_order_number) = order_number.parse::<OrderNumber>() {
             self.order_number_description = String::new();
+            if let (Some(freebie), Some(notification_date)) =
+                (self.get_active_freebie(), self.get_notification_date(cx))
+                && let Some(issued) = cx.global::<OrderNumbers>().registry.find(
+                    &freebie,
+                    &notification_date,
+                    &parsed_order_number,
+                )
+                && self.reused_order_number.as_deref() != Some(order_number.as_ref())
+            {
+                self.order_number_description = format!(
+                    "訂單編號 {}{} 已於通知日期 {} 使用過，再按一次預覽訂購單以重複使用",
+                    freebie.order_number_prefix(),
+                    parsed_order_number,
+                    RocDate::try_from(issued.notification_date)
+                        .map(|date| date.to_string())
+                        .unwrap_or_default()
+                );
+                self.reused_order_number = Some(order_number.to_string());
+                is_valid = false;
+            }
+        } else {
+            self.order_number_description = "訂單編號格式應為「月-序號」，例如 10-2".to_string();
+            is_valid = false;
         }
 
         is_valid
     }
 
-    fn get_template_path(freebie: &Freebie) -> &'static str {
-        match freebie {
-            Freebie::Tissue60 => "templates/60抽面紙每週訂購單.xlsx",
-            Freebie::Tissue110 => "templates/110抽面紙每週訂購單.xlsx",
-            Freebie::MineralWater => "templates/礦泉水每週訂購單.xlsx",
+    /// Fetches the item needs of all operation centers in the date range, showing the progress of
+    /// each step. The item needs fetched before a failure or cancellation are kept, so retrying
+    /// the same date range only fetches the centers which failed. Returns `None` once the failure
+    /// is shown to the user.
+    async fn fetch_item_needs(
+        this: &WeakEntity<Self>,
+        source: ItemNeedsSource,
+        start_date: jiff::civil::Date,
+        end_date: jiff::civil::Date,
+        window_handle: AnyWindowHandle,
+        cx: &mut AsyncApp,
+    ) -> Option<Vec<ItemNeeds>> {
+        let has_centers = this
+            .update(cx, |this, _| {
+                let has_centers = this.fetched_item_needs.as_ref().is_some_and(|fetched| {
+                    fetched.start_date == start_date && fetched.end_date == end_date
+                });
+                if !has_centers {
+                    this.fetched_item_needs = None;
+                }
+                has_centers
+            })
+            .ok()?;
+
+        // Get all operation centers from tscred
+        if has_centers {
+            Self::update_progress(this, cx, |progress| {
+                progress.reuse(GenerationStep::OperationCenters)
+            })?;
+        } else {
+            Self::update_progress(this, cx, |progress| {
+                progress.start(GenerationStep::OperationCenters)
+            })?;
+            let moved_source = source.clone();
+            let centers = match cx
+                .background_spawn(async move { moved_source.get_operation_centers() })
+                .await
+            {
+                Ok(centers) => centers,
+                Err(error) => {
+                    Self::fail_step(
+                        this,
+                        GenerationStep::OperationCenters,
+                        format!("無法從紅網取得營運中心資料\n{}", error),
+                        window_handle,
+                        cx,
+                    );
+                    return None;
+                }
+            };
+            let detail = format!("{} 個營運中心", centers.len());
+            Self::finish_step(this, GenerationStep::OperationCenters, detail, cx, |this| {
+                this.fetched_item_needs =
+                    Some(FetchedItemNeeds::new(start_date, end_date, centers));
+            })?;
         }
+
+        // Get the item needs of each operation center not fetched yet
+        let (missing_centers, total) = this
+            .read_with(cx, |this, _| {
+                let fetched = this.fetched_item_needs.as_ref()?;
+                let missing_centers = fetched
+                    .missing_centers()
+                    .into_iter()
+                    .map(|index| {
+                        let center = &fetched.centers[index];
+                        (index, center.id.clone(), center.name.clone())
+                    })
+                    .collect::<Vec<_>>();
+                Some((missing_centers, fetched.centers.len()))
+            })
+            .ok()??;
+        let mut fetched_count = total - missing_centers.len();
+        Self::update_progress(this, cx, |progress| {
+            progress.start(GenerationStep::ItemNeeds);
+            progress.set_detail(
+                GenerationStep::ItemNeeds,
+                format!("{}/{} 個營運中心", fetched_count, total),
+            );
+        })?;
+        // Each center reports the weeks it fetched over the channel, which closes once every
+        // task has finished and dropped its sender
+        let (progress_sender, progress_receiver) = mpsc::unbounded::<(usize, Progress)>();
+        let tasks = missing_centers
+            .into_iter()
+            .map(|(index, center_id, center_name)| {
+                let moved_source = source.clone();
+                let progress_sender = progress_sender.clone();
+                cx.background_spawn(async move {
+                    let result = fetch_center_item_needs(
+                        &moved_source,
+                        &center_id,
+                        &start_date,
+                        &end_date,
+                        |chunk_progress| {
+                            let _ = progress_sender.unbounded_send((index, chunk_progress));
+                        },
+                    );
+                    (index, center_name, result)
+                })
+            })
+            .collect::<FuturesUnordered<_>>();
+        drop(progress_sender);
+        let mut events = stream::select(
+            tasks.map(Either::Left),
+            progress_receiver.map(Either::Right),
+        );
+        let mut chunk_progress = HashMap::new();
+        let mut failures = vec![];
+        while let Some(event) = events.next().await {
+            match event {
+                Either::Left((index, _, Ok(item_needs))) => {
+                    fetched_count += 1;
+                    this.update(cx, |this, _| {
+                        if let Some(fetched) = this.fetched_item_needs.as_mut() {
+                            fetched.item_needs[index] = Some(item_needs);
+                        }
+                    })
+                    .ok()?;
+                }
+                Either::Left((_, center_name, Err(error))) => {
+                    failures.push(format!("{}：{}", center_name, error))
+                }
+                Either::Right((index, progress)) => {
+                    chunk_progress.insert(index, progress);
+                }
+            }
+            let (completed, chunks) =
+                chunk_progress
+                    .values()
+                    .fold((0, 0), |(completed, chunks), progress: &Progress| {
+                        (completed + progress.completed, chunks + progress.total)
+                    });
+            let detail = format!(
+                "{}/{} 個營運中心，{}/{} 週",
+                fetched_count, total, completed, chunks
+            );
+            Self::update_progress(this, cx, |progress| {
+                progress.set_detail(GenerationStep::ItemNeeds, detail)
+            })?;
+        }
+        if !failures.is_empty() {
+            Self::fail_step(
+                this,
+                GenerationStep::ItemNeeds,
+                format!("無法從紅網取得贈品需求資料\n{}", failures.join("\n")),
+                window_handle,
+                cx,
+            );
+            return None;
+        }
+
+        this.update(cx, |this, cx| {
+            if let Some(progress) = this.progress.as_mut() {
+                progress.finish(GenerationStep::ItemNeeds);
+            }
+            cx.notify();
+            this.fetched_item_needs
+                .take()
+                .and_then(FetchedItemNeeds::into_item_needs)
+        })
+        .ok()?
+    }
+
+    /// Updates the progress shown to the user, or returns `None` if the view was closed.
+    fn update_progress(
+        this: &WeakEntity<Self>,
+        cx: &mut AsyncApp,
+        update: impl FnOnce(&mut GenerationProgress),
+    ) -> Option<()> {
+        this.update(cx, |this, cx| {
+            if let Some(progress) = this.progress.as_mut() {
+                update(progress);
+                cx.notify();
+            }
+        })
+        .ok()
+    }
+
+    /// Marks the step as done with what it did, keeping its result with `keep` for retrying the
+    /// later steps. Returns `None` if the view was closed.
+    fn finish_step(
+        this: &WeakEntity<Self>,
+        step: GenerationStep,
+        detail: impl Into<String>,
+        cx: &mut AsyncApp,
+        keep: impl FnOnce(&mut Self),
+    ) -> Option<()> {
+        this.update(cx, |this, cx| {
+            keep(this);
+            if let Some(progress) = this.progress.as_mut() {
+                progress.set_detail(step, detail);
+                progress.finish(step);
+            }
+            cx.notify();
+        })
+        .ok()
     }
 
-    fn push_error_notification_and_turnoff_button_loading(
-        this: WeakEntity<Self>,
+    /// Marks the step as failed, leaving it to be retried, and shows the error to the user.
+    fn fail_step(
+        this: &WeakEntity<Self>,
+        step: GenerationStep,
         message: String,
         window_handle: AnyWindowHandle,
         cx: &mut AsyncApp,
     ) {
+        error!("{}", message.replace('\n', ": "));
+        let _ = this.update(cx, |this, cx| {
+            if let Some(progress) = this.progress.as_mut() {
+                // The label of the step already tells what failed
+                let reason = message.lines().skip(1).collect::<Vec<_>>().join("；");
+                progress.fail(step, if reason.is_empty() { &message } else { &reason });
+            }
+            this.submit_button_loading = false;
+            cx.notify();
+        });
         let _ = cx.update_window(window_handle, |_, window, cx| {
             window.push_notification((NotificationType::Error, SharedString::from(message)), cx);
         });
+    }
 
+    /// Ends the generation once all steps are done, dropping the results kept for retrying.
+    fn finish_generation(this: &WeakEntity<Self>, cx: &mut AsyncApp) {
         let _ = this.update(cx, |this, cx| {
+            this.progress = None;
+            this.saved_order = None;
+            this.saved_batch = None;
             this.submit_button_loading = false;
             cx.notify();
         });
     }
 
+    /// Starts generating what the user asked for from the first step.
+    fn generate(&mut self, generation: Generation, window: &mut Window, cx: &mut Context<Self>) {
+        self.fetched_item_needs = None;
+        self.saved_order = None;
+        self.saved_batch = None;
+        self.run_generation(generation, window, cx);
+    }
+
+    /// Generates again what failed or was cancelled, reusing the results of the steps which
+    /// succeeded.
+    fn retry_generation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(generation) = self.last_generation {
+            info!("retrying the failed steps");
+            self.run_generation(generation, window, cx);
+        }
+    }
+
+    fn run_generation(
+        &mut self,
+        generation: Generation,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.last_generation = Some(generation);
+        match generation {
+            Generation::Preview => self.submit(window, cx),
+            Generation::Save { save_mode, send } => self.save_preview(save_mode, send, window, cx),
+            Generation::Batch(output) => self.submit_batch(output, window, cx),
+        }
+    }
+
+    /// Stops the running generation. The requests already sent to TSCRED run to their end in the
+    /// background, but their responses are dropped.
+    fn cancel_generation(&mut self, cx: &mut Context<Self>) {
+        self.generation_task = None;
+        if let Some(progress) = self.progress.as_mut() {
+            progress.cancel();
+        }
+        self.submit_button_loading = false;
+        info!("cancelled the generation");
+        cx.notify();
+    }
+
+    /// Fetches the item needs and shows the quantities of the stations for review before saving.
     fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         // Show the loading state of submit button
         self.submit_button_loading = true;
@@ -172,145 +578,954 @@ impl PurchaseOrderView {
         }
 
         let report_date = self.report_date_picker.read(cx).date();
-        let notification_date = self.notification_date_picker.read(cx).date();
         let order_number = self.order_number_input.read(cx).value();
 
         // Create variables for the async tasks
         let window_handle = window.window_handle();
-        let tscred = self.tscred.clone();
+        let source = ItemNeedsSource::new(cx);
         let start_date = report_date.start().unwrap().to_string().parse().unwrap();
         let end_date = report_date.end().unwrap().to_string().parse().unwrap();
         let active_freebie = self.get_active_freebie().unwrap();
+        let notification_date = self.get_notification_date(cx).unwrap();
+        let parsed_order_number: OrderNumber = order_number.parse().unwrap();
+        self.progress = Some(GenerationProgress::new(&GenerationStep::FETCH));
+
+        self.generation_task = Some(cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                let Some(item_needs) =
+                    Self::fetch_item_needs(&this, source, start_date, end_date, window_handle, cx)
+                        .await
+                else {
+                    return;
+                };
+                let stations = match freebie::station_quantities(&item_needs, &active_freebie) {
+                    Ok(stations) => stations,
+                    Err(error) => {
+                        Self::fail_step(
+                            &this,
+                            GenerationStep::ItemNeeds,
+                            format!("無法產生{}訂購單\n{}", active_freebie.name(), error),
+                            window_handle,
+                            cx,
+                        );
+                        return;
+                    }
+                };
+
+                // Show the preview in place of the form
+                let _ = cx.update_window(window_handle, |_, window, cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        let order = PreviewedOrder {
+                            freebie: active_freebie,
+                            notification_date,
+                            order_number: parsed_order_number,
+                            report_start_date: start_date,
+                            report_end_date: end_date,
+                        };
+                        this.preview = Some(OrderPreview::new(
+                            order,
+                            Arc::new(item_needs),
+                            stations,
+                            window,
+                            cx,
+                        ));
+                        this.preview_description = String::new();
+                    });
+                });
+                Self::finish_generation(&this, cx);
+            },
+        ));
+    }
+
+    /// Generates the previewed order with the adjusted quantities and saves it, then asks to mail it
+    /// to the vendor if `send` is set.
+    fn save_preview(
+        &mut self,
+        save_mode: SaveMode,
+        send: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(preview) = &self.preview else {
+            return;
+        };
+        let overrides = match preview.overrides(cx) {
+            Ok(overrides) => overrides,
+            Err(message) => {
+                self.preview_description = message;
+                cx.notify();
+                return;
+            }
+        };
+
+        // Show the loading state of submit button
+        self.preview_description = String::new();
+        self.submit_button_loading = true;
+        cx.notify();
+
+        // Create variables for the async tasks
+        let window_handle = window.window_handle();
+        let order = preview.order;
+        let active_freebie = preview.order.freebie;
         let active_freebie_name = active_freebie.name();
-        let template_path = Self::get_template_path(&active_freebie);
-        let notification_date = notification_date.to_string().parse().unwrap();
-
-        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
-            let moved_tscred = tscred.clone();
-
-            // Get all operation centers from tscred
-            let operation_centers = cx
-                .background_spawn(async move { moved_tscred.get_operation_centers() })
-                .await;
-            let operation_centers = match operation_centers {
-                Ok(operation_centers) => operation_centers,
-                Err(error) => {
-                    Self::push_error_notification_and_turnoff_button_loading(
-                        this,
-                        format!("無法從紅網取得營運中心資料\n{}", error),
-                        window_handle,
-                        cx,
-                    );
+        let notification_date = preview.order.notification_date;
+        let parsed_order_number = preview.order.order_number;
+        let start_date = preview.order.report_start_date;
+        let end_date = preview.order.report_end_date;
+        let item_needs = preview.item_needs.clone();
+        let calendar = cx.global::<WorkingDays>().0.clone();
+        let archive = match save_mode {
+            SaveMode::NewWorkbook | SaveMode::Pdf if !self.choose_path => {
+                cx.global::<AppSettings>().0.archive.archive()
+            }
+            _ => None,
+        };
+        let is_archived = archive.is_some();
+
+        // Reuse what was generated before saving it failed, unless the order changed since
+        let saved = SavedSteps::take_or_new(
+            &mut self.saved_order,
+            (
+                active_freebie,
+                parsed_order_number,
+                save_mode,
+                overrides.clone(),
+            ),
+        );
+        let saved_template = saved.template.clone();
+        let saved_report = saved.report.clone();
+        let saved_path = saved.path.clone();
+        self.saved_order = Some(saved);
+        self.progress = Some(GenerationProgress::new(&GenerationStep::SAVE));
+
+        self.generation_task = Some(cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                // Load the template of the order form
+                let template = match saved_template {
+                    Some(template) => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.reuse(GenerationStep::Template)
+                        });
+                        template
+                    }
+                    None => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.start(GenerationStep::Template)
+                        });
+                        let template = match cx
+                            .background_spawn(async move { load_template(&active_freebie) })
+                            .await
+                        {
+                            Ok(template) => Arc::new(template),
+                            Err(error) => {
+                                Self::fail_step(
+                                    &this,
+                                    GenerationStep::Template,
+                                    format!("無法載入{}訂購單範本\n{}", active_freebie_name, error),
+                                    window_handle,
+                                    cx,
+                                );
+                                return;
+                            }
+                        };
+                        Self::finish_step(&this, GenerationStep::Template, "", cx, |this| {
+                            if let Some(saved) = this.saved_order.as_mut() {
+                                saved.template = Some(template.clone());
+                            }
+                        });
+                        template
+                    }
+                };
+
+                // Generate the purchase order report
+                let spreadsheet = match saved_report {
+                    Some(spreadsheet) => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.reuse(GenerationStep::Report)
+                        });
+                        spreadsheet
+                    }
+                    None => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.start(GenerationStep::Report)
+                        });
+                        let moved_item_needs = item_needs.clone();
+                        let moved_overrides = overrides.clone();
+                        let spreadsheet_result = cx
+                            .background_spawn(async move {
+                                freebie::generate_purchase_order_report(
+                                    &template,
+                                    &moved_item_needs,
+                                    &active_freebie,
+                                    &notification_date,
+                                    parsed_order_number.to_string(),
+                                    &calendar,
+                                    &moved_overrides,
+                                )
+                            })
+                            .await;
+                        let spreadsheet = match spreadsheet_result {
+                            Ok(spreadsheet) => Arc::new(spreadsheet),
+                            Err(error) => {
+                                Self::fail_step(
+                                    &this,
+                                    GenerationStep::Report,
+                                    format!("無法產生{}訂購單\n{}", active_freebie_name, error),
+                                    window_handle,
+                                    cx,
+                                );
+                                return;
+                            }
+                        };
+                        Self::finish_step(&this, GenerationStep::Report, "", cx, |this| {
+                            if let Some(saved) = this.saved_order.as_mut() {
+                                saved.report = Some(spreadsheet.clone());
+                            }
+                        });
+                        spreadsheet
+                    }
+                };
+
+                // Retrieve the path to save the report, or of the yearly workbook to add it to
+                let path_buf_option = match (saved_path, save_mode) {
+                    (Some(path_buf), _) => Some(path_buf),
+                    (None, SaveMode::NewWorkbook | SaveMode::Pdf) => {
+                        let extension = if save_mode == SaveMode::Pdf {
+                            "pdf"
+                        } else {
+                            "xlsx"
+                        };
+                        // File the order form in the archive without asking
+                        if let Some(archive) = &archive {
+                            Some(archive.order_path(
+                                &active_freebie,
+                                &parsed_order_number,
+                                &notification_date,
+                                extension,
+                            ))
+                        } else {
+                            let paths_receiver = cx
+                                .update(|cx| {
+                                    let file_name =
+                                        cx.global::<AppSettings>().0.output.order_file_name(
+                                            &active_freebie,
+                                            &parsed_order_number,
+                                            &notification_date,
+                                            extension,
+                                        );
+                                    cx.prompt_for_new_path(&output_folder(cx), Some(&file_name))
+                                })
+                                .unwrap();
+                            cx.background_spawn(paths_receiver).await.unwrap().unwrap()
+                        }
+                    }
+                    (None, SaveMode::YearlyWorkbook) => {
+                        let paths_receiver = cx
+                            .update(|cx| {
+                                cx.prompt_for_paths(PathPromptOptions {
+                                    files: true,
+                                    directories: false,
+                                    multiple: false,
+                                    prompt: Some(
+                                        format!("選擇{}年度訂購簿", active_freebie_name).into(),
+                                    ),
+                                })
+                            })
+                            .unwrap();
+                        cx.background_spawn(paths_receiver)
+                            .await
+                            .unwrap()
+                            .unwrap()
+                            .and_then(|paths| paths.into_iter().next())
+                    }
+                };
+                let Some(path_buf) = path_buf_option else {
+                    // Nothing is left to retry once the user closes the dialog
+                    Self::finish_generation(&this, cx);
                     return;
-                }
-            };
+                };
+                let path_string = path_buf.to_string_lossy().to_string();
+                let _ = this.update(cx, |this, cx| {
+                    if let Some(saved) = this.saved_order.as_mut() {
+                        saved.path = Some(path_buf.clone());
+                    }
+                    if let Some(progress) = this.progress.as_mut() {
+                        progress.start(GenerationStep::File);
+                        progress.set_detail(GenerationStep::File, path_string.clone());
+                    }
+                    cx.notify();
+                });
 
-            // Get the item needs for each operation center
-            let mut tasks = vec![];
-            for center in operation_centers {
-                let moved_tscred = tscred.clone();
-                tasks.push(cx.background_spawn(async move {
-                    moved_tscred.get_item_needs(GetItemNeedsOptions {
-                        operation_center_id: &center.id,
-                        start_date: &start_date,
-                        end_date: &end_date,
-                        display_mode: &DisplayMode::Details,
-                        department_id: "2",
+                // Save the generated report to the specified path
+                let write_result = cx
+                    .background_spawn(async move {
+                        write_order(
+                            &spreadsheet,
+                            path_buf,
+                            save_mode,
+                            &active_freebie,
+                            &parsed_order_number,
+                            is_archived,
+                        )
                     })
-                }));
-            }
-            let item_needs = match try_join_all(tasks).await {
-                Ok(item_needs) => item_needs,
-                Err(error) => {
-                    Self::push_error_notification_and_turnoff_button_loading(
-                        this,
-                        format!("無法從紅網取得贈品需求資料\n{:?}", error),
-                        window_handle,
+                    .await;
+                let output_path = match write_result {
+                    Ok(output_path) => output_path,
+                    Err(error) => {
+                        Self::fail_step(
+                            &this,
+                            GenerationStep::File,
+                            format!(
+                                "無法將{}每週訂購單儲存到 {}\nError: {}",
+                                active_freebie_name, path_string, error
+                            ),
+                            window_handle,
+                            cx,
+                        );
+                        return;
+                    }
+                };
+                info!(
+                    freebie = active_freebie_name,
+                    path:% = output_path.display();
+                    "saved purchase order"
+                );
+
+                // Record the order with the adjusted quantities in the ledger, remember the used
+                // order number, close the preview and suggest the next order number
+                let stations =
+                    freebie::adjusted_station_quantities(&item_needs, &active_freebie, &overrides)
+                        .unwrap_or_default();
+                let new_order = NewOrder {
+                    freebie: active_freebie,
+                    order_number: parsed_order_number,
+                    notification_date,
+                    report_start_date: start_date,
+                    report_end_date: end_date,
+                    stations: &stations,
+                    item_needs: &item_needs,
+                    output_path: &output_path,
+                };
+                let order_id = record_order(&new_order, window_handle, cx);
+                let _ = cx.update_window(window_handle, |_, window, cx| {
+                    let _ = this.update(cx, |this, cx| {
+                        this.preview = None;
+                        this.suggest_order_number(window, cx);
+                    });
+                });
+                Self::finish_generation(&this, cx);
+
+                // Show the notification to the user about the result of the save operation
+                let _ = cx.update_window(window_handle, |_this, window, cx| {
+                    window.push_notification(
+                        Self::saved_notification(
+                            Notification::new()
+                                .with_type(NotificationType::Success)
+                                .message(format!(
+                                    "已將{}每週訂購單儲存到 {}",
+                                    active_freebie_name,
+                                    output_path.display()
+                                )),
+                            output_path.clone(),
+                        ),
                         cx,
                     );
-                    return;
+                });
+
+                if send {
+                    let format = match save_mode {
+                        SaveMode::Pdf => AttachmentFormat::Pdf,
+                        _ => AttachmentFormat::Xlsx,
+                    };
+                    confirm_and_send(order_id, &order, format, &output_path, window_handle, cx);
                 }
-            };
+            },
+        ));
+    }
+
+    /// Saves the previewed order in the format of the mailer settings, then asks to mail it to the
+    /// vendor.
+    fn send_preview(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(preview) = &self.preview else {
+            return;
+        };
+        let config = cx.global::<OrderMailer>().0.config();
+        let config_path = mailer_config_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        if config.smtp.host.trim().is_empty() {
+            self.preview_description = format!("請先在 {} 設定 SMTP 伺服器", config_path);
+            cx.notify();
+            return;
+        }
+        if config.recipients(&preview.order.freebie).is_empty() {
+            self.preview_description = format!(
+                "請先在 {} 設定{}廠商的電子郵件",
+                config_path,
+                preview.order.freebie.short_name()
+            );
+            cx.notify();
+            return;
+        }
+
+        let save_mode = match config.attachment {
+            AttachmentFormat::Xlsx => SaveMode::NewWorkbook,
+            AttachmentFormat::Pdf => SaveMode::Pdf,
+        };
+        self.generate(
+            Generation::Save {
+                save_mode,
+                send: true,
+            },
+            window,
+            cx,
+        );
+    }
+
+    /// Adds buttons to open the saved file or folder, or reveal it in its folder, to the
+    /// notification, which stays until closed.
+    fn saved_notification(notification: Notification, path: PathBuf) -> Notification {
+        notification.autohide(false).content(move |_, _| {
+            let open_path = path.clone();
+            let reveal_path = path.clone();
+            h_flex()
+                .gap_2()
+                .mt_2()
+                .child(
+                    Button::new("open-saved-path")
+                        .small()
+                        .label("開啟")
+                        .on_click(move |_, _, cx| cx.open_with_system(&open_path)),
+                )
+                .child(
+                    Button::new("reveal-saved-path")
+                        .small()
+                        .label("在資料夾中顯示")
+                        .on_click(move |_, _, cx| cx.reveal_path(&reveal_path)),
+                )
+                .into_any_element()
+        })
+    }
 
-            // Generate the purchase order report
-            let spreadsheet_result = cx
-                .background_spawn(async move {
-                    let template_file =
-                        Assets::get(template_path).ok_or(anyhow!("Failed to get template file"))?;
-                    let template = reader::xlsx::read_reader(Cursor::new(template_file.data), true)
-                        .map_err(|error| anyhow!("Failed to read template: {}", error))?;
-
-                    freebie::generate_purchase_order_report(
-                        &template,
-                        &item_needs,
-                        &active_freebie,
-                        &notification_date,
-                        &order_number,
+    /// Describes what became of each freebie of a batch, such as `60抽面紙 10-3：1240 箱`.
+    fn describe_batch(reports: &[BatchReport]) -> String {
+        reports
+            .iter()
+            .map(|report| match &report.outcome {
+                BatchOutcome::Generated { .. } => format!(
+                    "{} {}{}：{} 箱",
+                    report.freebie.short_name(),
+                    report.freebie.order_number_prefix(),
+                    report.order_number,
+                    report.total_quantity()
+                ),
+                BatchOutcome::ZeroQuantity => {
+                    format!(
+                        "{}：沒有站別訂購，未產生訂購單",
+                        report.freebie.short_name()
                     )
-                    .map_err(|error| anyhow!("Failed to generate purchase order report: {}", error))
-                })
-                .await;
-            let spreadsheet = match spreadsheet_result {
-                Ok(spreadsheet) => spreadsheet,
-                Err(error) => {
-                    Self::push_error_notification_and_turnoff_button_loading(
-                        this,
-                        format!("無法產生{}訂購單\n{:?}", active_freebie_name, error),
-                        window_handle,
-                        cx,
-                    );
+                }
+                BatchOutcome::Skipped(error) => {
+                    format!("{}：略過，{}", report.freebie.short_name(), error)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn submit_batch(&mut self, output: BatchOutput, window: &mut Window, cx: &mut Context<Self>) {
+        // Show the loading state of submit button
+        self.submit_button_loading = true;
+        cx.notify();
+
+        if !self.validate(cx) {
+            self.submit_button_loading = false;
+            cx.notify();
+            return;
+        }
+
+        let report_date = self.report_date_picker.read(cx).date();
+
+        // Create variables for the async tasks
+        let window_handle = window.window_handle();
+        let source = ItemNeedsSource::new(cx);
+        let start_date = report_date.start().unwrap().to_string().parse().unwrap();
+        let end_date = report_date.end().unwrap().to_string().parse().unwrap();
+        let notification_date = self.get_notification_date(cx).unwrap();
+        let order_numbers = self.get_batch_order_numbers(cx).unwrap();
+        let calendar = cx.global::<WorkingDays>().0.clone();
+
+        // Reuse what was generated before saving it failed, unless the orders changed since
+        let saved = SavedSteps::take_or_new(
+            &mut self.saved_batch,
+            (
+                start_date,
+                end_date,
+                notification_date,
+                output,
+                order_numbers.clone(),
+            ),
+        );
+        let saved_item_needs = saved.item_needs.clone();
+        let saved_templates = saved.template.clone();
+        let saved_reports = saved.report.clone();
+        let saved_path = saved.path.clone();
+        let saved_written = saved.written.clone();
+        self.saved_batch = Some(saved);
+        self.progress = Some(GenerationProgress::new(&GenerationStep::ALL));
+
+        self.generation_task = Some(cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                // Fetch the item needs once for all freebies
+                let item_needs = match saved_item_needs {
+                    Some(item_needs) => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.reuse(GenerationStep::OperationCenters);
+                            progress.reuse(GenerationStep::ItemNeeds);
+                        });
+                        item_needs
+                    }
+                    None => {
+                        let Some(item_needs) = Self::fetch_item_needs(
+                            &this,
+                            source,
+                            start_date,
+                            end_date,
+                            window_handle,
+                            cx,
+                        )
+                        .await
+                        else {
+                            return;
+                        };
+                        let item_needs = Arc::new(item_needs);
+                        let _ = this.update(cx, |this, _| {
+                            if let Some(saved) = this.saved_batch.as_mut() {
+                                saved.item_needs = Some(item_needs.clone());
+                            }
+                        });
+                        item_needs
+                    }
+                };
+
+                // Load the templates of the order forms
+                let templates = match saved_templates {
+                    Some(templates) => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.reuse(GenerationStep::Template)
+                        });
+                        templates
+                    }
+                    None => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.start(GenerationStep::Template)
+                        });
+                        let freebies = order_numbers
+                            .iter()
+                            .map(|(freebie, _)| *freebie)
+                            .collect::<Vec<_>>();
+                        let templates = match cx
+                            .background_spawn(async move {
+                                freebies
+                                    .iter()
+                                    .map(load_template)
+                                    .collect::<anyhow::Result<Vec<_>>>()
+                            })
+                            .await
+                        {
+                            Ok(templates) => Arc::new(templates),
+                            Err(error) => {
+                                Self::fail_step(
+                                    &this,
+                                    GenerationStep::Template,
+                                    format!("無法載入訂購單範本\n{}", error),
+                                    window_handle,
+                                    cx,
+                                );
+                                return;
+                            }
+                        };
+                        let detail = format!("{} 份範本", templates.len());
+                        Self::finish_step(&this, GenerationStep::Template, detail, cx, |this| {
+                            if let Some(saved) = this.saved_batch.as_mut() {
+                                saved.template = Some(templates.clone());
+                            }
+                        });
+                        templates
+                    }
+                };
+
+                // Generate the purchase order reports
+                let reports = match saved_reports {
+                    Some(reports) => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.reuse(GenerationStep::Report)
+                        });
+                        reports
+                    }
+                    None => {
+                        Self::update_progress(&this, cx, |progress| {
+                            progress.start(GenerationStep::Report)
+                        });
+                        let moved_item_needs = item_needs.clone();
+                        let moved_order_numbers = order_numbers.clone();
+                        let reports = cx
+                            .background_spawn(async move {
+                                let orders = moved_order_numbers
+                                    .iter()
+                                    .zip(templates.iter())
+                                    .map(|((freebie, order_number), template)| BatchOrder {
+                                        freebie: *freebie,
+                                        template,
+                                        order_number: *order_number,
+                                    })
+                                    .collect::<Vec<_>>();
+                                freebie::generate_batch_purchase_order_reports(
+                                    &orders,
+                                    &moved_item_needs,
+                                    &notification_date,
+                                    &calendar,
+                                )
+                            })
+                            .await;
+                        let reports = Arc::new(reports);
+                        let generated_count = reports
+                            .iter()
+                            .filter(|report| {
+                                matches!(report.outcome, BatchOutcome::Generated { .. })
+                            })
+                            .count();
+                        let detail = format!("{}/{} 份訂購單", generated_count, reports.len());
+                        Self::finish_step(&this, GenerationStep::Report, detail, cx, |this| {
+                            if let Some(saved) = this.saved_batch.as_mut() {
+                                saved.report = Some(reports.clone());
+                            }
+                        });
+                        reports
+                    }
+                };
+                let description = Self::describe_batch(&reports);
+                let all_generated = reports
+                    .iter()
+                    .all(|report| matches!(report.outcome, BatchOutcome::Generated { .. }));
+                if !reports
+                    .iter()
+                    .any(|report| matches!(report.outcome, BatchOutcome::Generated { .. }))
+                {
+                    let _ = cx.update_window(window_handle, |_, window, cx| {
+                        window.push_notification(
+                            (
+                                NotificationType::Warning,
+                                SharedString::from(format!("沒有可儲存的訂購單\n{}", description)),
+                            ),
+                            cx,
+                        );
+                    });
+                    Self::finish_generation(&this, cx);
                     return;
                 }
-            };
 
-            // Retrieve the path to save the report
-            let paths_receiver = cx
-                .update(|cx| {
-                    cx.prompt_for_new_path(
-                        env::home_dir().unwrap_or_default().as_path(),
-                        Some("活頁簿.xlsx"),
-                    )
-                })
-                .unwrap();
-            let path_buf_option = cx.background_spawn(paths_receiver).await.unwrap().unwrap();
-            if let Some(path_buf) = path_buf_option {
+                // Retrieve the path to save the workbook, or the folder to save the workbooks in
+                let path_buf_option = match (saved_path, output) {
+                    (Some(path_buf), _) => Some(path_buf),
+                    (None, BatchOutput::Workbook) => {
+                        let paths_receiver = cx
+                            .update(|cx| {
+                                cx.prompt_for_new_path(&output_folder(cx), Some("每週訂購單.xlsx"))
+                            })
+                            .unwrap();
+                        cx.background_spawn(paths_receiver).await.unwrap().unwrap()
+                    }
+                    (None, BatchOutput::Folder) => {
+                        let paths_receiver = cx
+                            .update(|cx| {
+                                cx.prompt_for_paths(PathPromptOptions {
+                                    files: false,
+                                    directories: true,
+                                    multiple: false,
+                                    prompt: Some("選擇儲存訂購單的資料夾".into()),
+                                })
+                            })
+                            .unwrap();
+                        cx.background_spawn(paths_receiver)
+                            .await
+                            .unwrap()
+                            .unwrap()
+                            .and_then(|paths| paths.into_iter().next())
+                    }
+                    (None, BatchOutput::Archive) => cx
+                        .update(|cx| {
+                            let archive = cx.global::<AppSettings>().0.archive.archive();
+                            archive.map(|archive| archive.root().to_path_buf())
+                        })
+                        .ok()
+                        .flatten(),
+                };
+                let Some(path_buf) = path_buf_option else {
+                    // Nothing is left to retry once the user closes the dialog
+                    Self::finish_generation(&this, cx);
+                    return;
+                };
                 let path_string = path_buf.to_string_lossy().to_string();
+                let _ = this.update(cx, |this, cx| {
+                    if let Some(saved) = this.saved_batch.as_mut() {
+                        saved.path = Some(path_buf.clone());
+                    }
+                    if let Some(progress) = this.progress.as_mut() {
+                        progress.start(GenerationStep::File);
+                        progress.set_detail(GenerationStep::File, path_string.clone());
+                    }
+                    cx.notify();
+                });
 
-                // Save the generated repor to the specified path
-                let write_result = cx
-                    .background_spawn(async move { writer::xlsx::write(&spreadsheet, path_buf) })
+                // Save the generated reports to the specified path
+                let moved_reports = reports.clone();
+                let settings = cx
+                    .update(|cx| cx.global::<AppSettings>().0.clone())
+                    .unwrap_or_default();
+                let (written, write_result) = cx
+                    .background_spawn(async move {
+                        let mut written = saved_written;
+                        let write_result = write_batch(
+                            &moved_reports,
+                            output,
+                            &path_buf,
+                            &settings,
+                            &notification_date,
+                            &mut written,
+                        );
+                        (written, write_result)
+                    })
                     .await;
+                let _ = this.update(cx, |this, _| {
+                    if let Some(saved) = this.saved_batch.as_mut() {
+                        saved.written = written;
+                    }
+                });
+                let output_paths = match write_result {
+                    Ok(output_paths) => output_paths,
+                    Err(error) => {
+                        Self::fail_step(
+                            &this,
+                            GenerationStep::File,
+                            format!("無法將每週訂購單儲存到 {}\nError: {}", path_string, error),
+                            window_handle,
+                            cx,
+                        );
+                        return;
+                    }
+                };
+                for (freebie, output_path) in output_paths.iter() {
+                    info!(
+                        freebie = freebie.name(),
+                        path:% = output_path.display();
+                        "saved purchase order"
+                    );
+                }
+
+                // Record the orders in the ledger, remember the used order numbers and suggest
+                // the next one
+                for report in reports.iter() {
+                    let (BatchOutcome::Generated { stations, .. }, Some((_, output_path))) = (
+                        &report.outcome,
+                        output_paths
+                            .iter()
+                            .find(|(freebie, _)| *freebie == report.freebie),
+                    ) else {
+                        continue;
+                    };
+                    let new_order = NewOrder {
+                        freebie: report.freebie,
+                        order_number: report.order_number,
+                        notification_date,
+                        report_start_date: start_date,
+                        report_end_date: end_date,
+                        stations,
+                        item_needs: &item_needs,
+                        output_path,
+                    };
+                    record_order(&new_order, window_handle, cx);
+                }
+                let _ = cx.update_window(window_handle, |_, window, cx| {
+                    let _ = this.update(cx, |this, cx| this.suggest_order_number(window, cx));
+                });
+                Self::finish_generation(&this, cx);
 
                 // Show the notification to the user about the result of the save operation
                 let _ = cx.update_window(window_handle, |_this, window, cx| {
-                    let notification = match write_result {
-                        Ok(_) => Notification::new()
-                            .with_type(NotificationType::Success)
-                            .message(format!(
-                                "已將{}每週訂購單儲存到 {}",
-                                active_freebie_name, path_string
-                            )),
-                        Err(error) => Notification::new()
-                            .with_type(NotificationType::Error)
-                            .message(format!(
-                                "無法將{}每週訂購單儲存到 {}\nError: {}",
-                                active_freebie_name, path_string, error
-                            )),
-                    };
-                    window.push_notification(notification, cx);
+                    window.push_notification(
+                        Self::saved_notification(
+                            Notification::new()
+                                .with_type(if all_generated {
+                                    NotificationType::Success
+                                } else {
+                                    NotificationType::Warning
+                                })
+                                .message(format!(
+                                    "已將每週訂購單儲存到 {}\n{}",
+                                    path_string, description
+                                )),
+                            PathBuf::from(&path_string),
+                        ),
+                        cx,
+                    );
                 });
-            }
+            },
+        ));
+    }
 
-            // Reset the submit button loading state
-            let _ = this.update(cx, |this, cx| {
-                this.submit_button_loading = false;
-                cx.notify();
-            });
-        })
-        .detach();
+    fn render_preview(&self, preview: &OrderPreview, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_archive = cx.global::<AppSettings>().0.archive.archive().is_some();
+        v_flex()
+            .gap_2()
+            .child(div().text_lg().child(format!(
+                "{}訂單編號 {}{} 預覽",
+                preview.order.freebie.short_name(),
+                preview.order.freebie.order_number_prefix(),
+                preview.order.order_number
+            )))
+            .child(preview.render(cx))
+            .when(!self.preview_description.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_color(cx.theme().danger)
+                        .child(SharedString::from(&self.preview_description)),
+                )
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("generate-report")
+                            .primary()
+                            .label("儲存訂購單")
+                            .loading(self.submit_button_loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.generate(
+                                    Generation::Save {
+                                        save_mode: SaveMode::NewWorkbook,
+                                        send: false,
+                                    },
+                                    window,
+                                    cx,
+                                )
+                            })),
+                    )
+                    .child(
+                        Button::new("append-to-yearly-workbook")
+                            .label("加入年度訂購簿")
+                            .loading(self.submit_button_loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.generate(
+                                    Generation::Save {
+                                        save_mode: SaveMode::YearlyWorkbook,
+                                        send: false,
+                                    },
+                                    window,
+                                    cx,
+                                )
+                            })),
+                    )
+                    .child(
+                        Button::new("save-as-pdf")
+                            .label("儲存為 PDF")
+                            .loading(self.submit_button_loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.generate(
+                                    Generation::Save {
+                                        save_mode: SaveMode::Pdf,
+                                        send: false,
+                                    },
+                                    window,
+                                    cx,
+                                )
+                            })),
+                    )
+                    .child(
+                        Button::new("send-preview")
+                            .label("產生並寄出")
+                            .loading(self.submit_button_loading)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.send_preview(window, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("cancel-preview")
+                            .ghost()
+                            .label("取消")
+                            .disabled(self.submit_button_loading)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.preview = None;
+                                cx.notify();
+                            })),
+                    )
+                    .when(has_archive, |this| {
+                        this.child(
+                            Checkbox::new("choose-save-path")
+                                .label("自行選擇儲存位置")
+                                .checked(self.choose_path)
+                                .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                    this.choose_path = *checked;
+                                    cx.notify();
+                                })),
+                        )
+                    }),
+            )
+    }
+
+    fn render_progress(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let progress = self.progress.as_ref()?;
+        Some(
+            v_flex()
+                .gap_2()
+                .p_3()
+                .rounded(cx.theme().radius)
+                .border_1()
+                .border_color(cx.theme().border)
+                .child(progress.render(cx))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .when(self.submit_button_loading, |this| {
+                            this.child(
+                                Button::new("cancel-generation")
+                                    .small()
+                                    .label("取消")
+                                    .on_click(
+                                        cx.listener(|this, _, _, cx| this.cancel_generation(cx)),
+                                    ),
+                            )
+                        })
+                        .when(
+                            !self.submit_button_loading && progress.can_retry(),
+                            |this| {
+                                this.child(
+                                    Button::new("retry-generation")
+                                        .small()
+                                        .primary()
+                                        .label("重試")
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.retry_generation(window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("dismiss-generation")
+                                        .small()
+                                        .ghost()
+                                        .label("關閉")
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.progress = None;
+                                            cx.notify();
+                                        })),
+                                )
+                            },
+                        ),
+                ),
+        )
     }
 
     fn render_tab_content(&self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_archive = cx.global::<AppSettings>().0.archive.archive().is_some();
         div().py_2().child(
             v_form()
                 .column(2)
@@ -334,25 +1549,105 @@ impl PurchaseOrderView {
                         })
                         .child(DatePicker::new(&self.notification_date_picker).number_of_months(1)),
                 )
-                .child(
-                    form_field()
-                        .label("訂單編號")
-                        .required(true)
-                        .col_span(2)
-                        .when(!self.order_number_description.is_empty(), |this| {
-                            this.description(SharedString::from(&self.order_number_description))
-                        })
-                        .child(TextInput::new(&self.order_number_input)),
-                )
-                .child(
-                    form_field().no_label_indent().col_span(2).child(
-                        Button::new("generate-report")
-                            .primary()
-                            .label("產生訂購單")
-                            .loading(self.submit_button_loading)
-                            .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
-                    ),
-                ),
+                .map(|this| match self.get_active_freebie() {
+                    Some(_) => this
+                        .child(
+                            form_field()
+                                .label("訂單編號")
+                                .required(true)
+                                .col_span(2)
+                                .when(!self.order_number_description.is_empty(), |this| {
+                                    this.description(SharedString::from(
+                                        &self.order_number_description,
+                                    ))
+                                })
+                                .child(TextInput::new(&self.order_number_input)),
+                        )
+                        .child(
+                            form_field()
+                                .no_label_indent()
+                                .col_span(2)
+                                .child(match &self.preview {
+                                    Some(preview) => {
+                                        self.render_preview(preview, cx).into_any_element()
+                                    }
+                                    None => Button::new("preview-report")
+                                        .primary()
+                                        .label("預覽訂購單")
+                                        .loading(self.submit_button_loading)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.generate(Generation::Preview, window, cx)
+                                        }))
+                                        .into_any_element(),
+                                }),
+                        ),
+                    // Every freebie takes its next order number
+                    None => this
+                        .child(
+                            form_field().label("訂單編號").col_span(2).child(
+                                div().child(
+                                    self.get_batch_order_numbers(cx)
+                                        .unwrap_or_default()
+                                        .into_iter()
+                                        .map(|(freebie, order_number)| {
+                                            format!(
+                                                "{} {}{}",
+                                                freebie.short_name(),
+                                                freebie.order_number_prefix(),
+                                                order_number
+                                            )
+                                        })
+                                        .collect::<Vec<_>>()
+                                        .join("、"),
+                                ),
+                            ),
+                        )
+                        .child(
+                            form_field().no_label_indent().col_span(2).child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("generate-batch-workbook")
+                                            .primary()
+                                            .label("產生單一活頁簿")
+                                            .loading(self.submit_button_loading)
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.generate(
+                                                    Generation::Batch(BatchOutput::Workbook),
+                                                    window,
+                                                    cx,
+                                                )
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new("generate-batch-folder")
+                                            .label("產生到資料夾")
+                                            .loading(self.submit_button_loading)
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.generate(
+                                                    Generation::Batch(BatchOutput::Folder),
+                                                    window,
+                                                    cx,
+                                                )
+                                            })),
+                                    )
+                                    .when(has_archive, |this| {
+                                        this.child(
+                                            Button::new("generate-batch-archive")
+                                                .label("存入封存資料夾")
+                                                .loading(self.submit_button_loading)
+                                                .on_click(cx.listener(|this, _, window, cx| {
+                                                    this.generate(
+                                                        Generation::Batch(BatchOutput::Archive),
+                                                        window,
+                                                        cx,
+                                                    )
+                                                })),
+                                        )
+                                    }),
+                            ),
+                        ),
+                }),
         )
     }
 }
@@ -368,14 +1663,22 @@ impl Render for PurchaseOrderView {
                     .underline()
                     .large()
                     .selected_index(self.active_tab)
-                    .on_click(cx.listener(|this, i, _, cx| {
+                    .on_click(cx.listener(|this, i, window, cx| {
+                        // Drop the generation of the previous tab, which would show its stations
+                        // in this one once done
+                        this.generation_task = None;
+                        this.progress = None;
+                        this.submit_button_loading = false;
                         this.active_tab = *i;
-                        cx.notify();
+                        this.preview = None;
+                        this.suggest_order_number(window, cx);
                     }))
                     .child(Tab::new("60抽面紙"))
                     .child(Tab::new("110抽面紙"))
-                    .child(Tab::new("礦泉水")),
+                    .child(Tab::new("礦泉水"))
+                    .child(Tab::new("全部贈品")),
             )
             .child(div().flex_1().child(self.render_tab_content(window, cx)))
+            .children(self.render_progress(cx))
     }
 }
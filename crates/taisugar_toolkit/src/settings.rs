@@ -0,0 +1,480 @@
+use crate::app_settings::{AppSettings, settings_file_path};
+use crate::item_needs::ItemNeedsSource;
+use gpui::prelude::*;
+use gpui::{App, AsyncApp, Entity, PathPromptOptions, SharedString, WeakEntity, Window, div};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::checkbox::Checkbox;
+use gpui_component::dropdown::{Dropdown, DropdownState};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputState, TextInput};
+use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::tab::{Tab, TabBar};
+use gpui_component::{ContextModal, IndexPath, Sizable, h_flex, v_flex};
+use log::{error, info};
+use toolkit_settings::{
+    AppearanceSettings, ArchiveSettings, HttpSettings, OutputSettings, ReportSettings, Settings,
+    Theme, TscredSettings,
+};
+use tscred::OperationCenter;
+
+/// The locales of the components along with their names.
+const LOCALES: [(&str, &str); 3] = [
+    ("zh-HK", "繁體中文（香港）"),
+    ("zh-CN", "简体中文"),
+    ("en", "English"),
+];
+
+pub struct SettingsView {
+    base_url_input: Entity<InputState>,
+    department_id_input: Entity<InputState>,
+    /// The operation centers of TSCRED, to choose the ones to fetch the item needs of.
+    operation_centers: Vec<OperationCenter>,
+    /// The ids of the chosen operation centers, or empty for all of them.
+    selected_centers: Vec<String>,
+    operation_centers_description: String,
+    timeout_input: Entity<InputState>,
+    user_agent_input: Entity<InputState>,
+    default_days_input: Entity<InputState>,
+    output_folder_input: Entity<InputState>,
+    order_file_name_input: Entity<InputState>,
+    archive_root_input: Entity<InputState>,
+    archive_layout_input: Entity<InputState>,
+    locale_dropdown: Entity<DropdownState<Vec<String>>>,
+    theme: Theme,
+    description: String,
+}
+
+impl SettingsView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let is_number = |value: &str, _: &mut Context<InputState>| {
+            value.is_empty() || value.parse::<u64>().is_ok()
+        };
+        let locale_names = LOCALES
+            .iter()
+            .map(|(_, name)| name.to_string())
+            .collect::<Vec<_>>();
+
+        let mut view = SettingsView {
+            base_url_input: cx.new(|cx| InputState::new(window, cx)),
+            department_id_input: cx.new(|cx| InputState::new(window, cx)),
+            operation_centers: vec![],
+            selected_centers: vec![],
+            operation_centers_description: String::new(),
+            timeout_input: cx.new(|cx| InputState::new(window, cx).validate(is_number)),
+            user_agent_input: cx.new(|cx| InputState::new(window, cx)),
+            default_days_input: cx.new(|cx| InputState::new(window, cx).validate(is_number)),
+            output_folder_input: cx.new(|cx| InputState::new(window, cx)),
+            order_file_name_input: cx.new(|cx| InputState::new(window, cx)),
+            archive_root_input: cx.new(|cx| InputState::new(window, cx)),
+            archive_layout_input: cx.new(|cx| InputState::new(window, cx)),
+            locale_dropdown: cx.new(|cx| DropdownState::new(locale_names, None, window, cx)),
+            theme: Theme::Light,
+            description: String::new(),
+        };
+        let settings = cx.global::<AppSettings>().0.clone();
+        view.fill(&settings, window, cx);
+        view.load_operation_centers(window, cx);
+
+        view
+    }
+
+    /// Shows the settings in the form.
+    fn fill(&mut self, settings: &Settings, window: &mut Window, cx: &mut Context<Self>) {
+        let values = [
+            (&self.base_url_input, settings.tscred.base_url.clone()),
+            (
+                &self.department_id_input,
+                settings.tscred.department_id.clone(),
+            ),
+            (&self.timeout_input, settings.http.timeout_secs.to_string()),
+            (&self.user_agent_input, settings.http.user_agent.clone()),
+            (
+                &self.default_days_input,
+                settings.report.default_days.to_string(),
+            ),
+            (&self.output_folder_input, settings.output.folder.clone()),
+            (
+                &self.order_file_name_input,
+                settings.output.order_file_name.clone(),
+            ),
+            (&self.archive_root_input, settings.archive.root.clone()),
+            (&self.archive_layout_input, settings.archive.layout.clone()),
+        ];
+        for (input, value) in values {
+            input.update(cx, |state, cx| state.set_value(value, window, cx));
+        }
+        let locale_index = LOCALES
+            .iter()
+            .position(|(locale, _)| *locale == settings.appearance.locale)
+            .unwrap_or_default();
+        self.locale_dropdown.update(cx, |dropdown, cx| {
+            dropdown.set_selected_index(Some(IndexPath::new(locale_index)), window, cx)
+        });
+        self.selected_centers = settings.tscred.operation_centers.clone();
+        self.theme = settings.appearance.theme;
+        self.description = String::new();
+        cx.notify();
+    }
+
+    /// Loads the operation centers of the saved TSCRED site to choose from.
+    fn load_operation_centers(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let window_handle = window.window_handle();
+        let source = ItemNeedsSource::new(cx);
+        self.operation_centers_description = "正在從紅網取得營運中心…".to_string();
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = cx
+                .background_spawn(async move { source.client.get_operation_centers() })
+                .await;
+            let _ = cx.update_window(window_handle, |_, _, cx| {
+                let _ = this.update(cx, |this, cx| {
+                    match result {
+                        Ok(operation_centers) => {
+                            this.operation_centers = operation_centers;
+                            this.operation_centers_description = String::new();
+                        }
+                        Err(error) => {
+                            this.operation_centers_description =
+                                format!("無法從紅網取得營運中心資料\n{}", error)
+                        }
+                    }
+                    cx.notify();
+                });
+            });
+        })
+        .detach();
+    }
+
+    fn toggle_operation_center(&mut self, id: &str, checked: bool, cx: &mut Context<Self>) {
+        self.selected_centers.retain(|selected| selected != id);
+        if checked {
+            self.selected_centers.push(id.to_string());
+        }
+        cx.notify();
+    }
+
+    /// Fills in the input with the folder the user chooses.
+    fn choose_folder(
+        input: Entity<InputState>,
+        prompt: &'static str,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let window_handle = window.window_handle();
+        let paths_receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(prompt.into()),
+        });
+
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let Ok(Ok(Some(paths))) = cx.background_spawn(paths_receiver).await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = cx.update_window(window_handle, |_, window, cx| {
+                input.update(cx, |state, cx| {
+                    state.set_value(path.to_string_lossy().to_string(), window, cx)
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// Reads the settings from the form, or shows what is wrong with them.
+    fn collect_settings(&mut self, cx: &mut Context<Self>) -> Option<Settings> {
+        let value =
+            |input: &Entity<InputState>, cx: &App| input.read(cx).value().trim().to_string();
+
+        let base_url = value(&self.base_url_input, cx);
+        let department_id = value(&self.department_id_input, cx);
+        let timeout_secs = value(&self.timeout_input, cx).parse::<u64>().ok();
+        let default_days = value(&self.default_days_input, cx).parse::<u64>().ok();
+        let order_file_name = value(&self.order_file_name_input, cx);
+        let archive_layout = value(&self.archive_layout_input, cx);
+        self.description = if !base_url.starts_with("http://") && !base_url.starts_with("https://")
+        {
+            "請輸入紅網網址，例如 http://192.168.41.30/TSCRED".to_string()
+        } else if department_id.is_empty() {
+            "請輸入部門代碼".to_string()
+        } else if timeout_secs.is_none_or(|secs| secs == 0) {
+            "請輸入大於 0 的逾時秒數".to_string()
+        } else if default_days.is_none_or(|days| days == 0) {
+            "請輸入大於 0 的預設統計天數".to_string()
+        } else if order_file_name.is_empty() {
+            "請輸入訂購單檔名格式".to_string()
+        } else if archive_layout.is_empty() {
+            "請輸入封存結構".to_string()
+        } else {
+            String::new()
+        };
+        cx.notify();
+        if !self.description.is_empty() {
+            return None;
+        }
+
+        let locale = self
+            .locale_dropdown
+            .read(cx)
+            .selected_index(cx)
+            .and_then(|index| LOCALES.get(index.row))
+            .map(|(locale, _)| locale.to_string())
+            .unwrap_or_default();
+        Some(Settings {
+            tscred: TscredSettings {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                department_id,
+                operation_centers: self.selected_centers.clone(),
+            },
+            http: HttpSettings {
+                timeout_secs: timeout_secs.unwrap_or_default(),
+                user_agent: value(&self.user_agent_input, cx),
+            },
+            report: ReportSettings {
+                default_days: default_days.unwrap_or_default(),
+            },
+            output: OutputSettings {
+                folder: value(&self.output_folder_input, cx),
+                order_file_name,
+            },
+            archive: ArchiveSettings {
+                root: value(&self.archive_root_input, cx),
+                layout: archive_layout,
+            },
+            appearance: AppearanceSettings {
+                locale,
+                theme: self.theme,
+            },
+        })
+    }
+
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(settings) = self.collect_settings(cx) else {
+            return;
+        };
+        let is_base_url_changed =
+            settings.tscred.base_url != cx.global::<AppSettings>().0.tscred.base_url;
+        let notification = match AppSettings::save(settings, window, cx) {
+            Ok(()) => {
+                info!("saved settings");
+                Notification::new()
+                    .with_type(NotificationType::Success)
+                    .message("已儲存設定")
+            }
+            Err(e) => {
+                error!(error:% = e; "unable to save settings");
+                Notification::new()
+                    .with_type(NotificationType::Error)
+                    .message(format!("無法儲存設定\n{}", e))
+            }
+        };
+        window.push_notification(notification, cx);
+
+        // The operation centers to choose from belong to the site
+        if is_base_url_changed {
+            self.load_operation_centers(window, cx);
+        }
+    }
+
+    /// Renders the input of a folder along with a button to choose it.
+    fn render_folder_input(
+        id: &'static str,
+        input: &Entity<InputState>,
+        prompt: &'static str,
+    ) -> impl IntoElement {
+        let moved_input = input.clone();
+        h_flex()
+            .gap_2()
+            .child(div().flex_1().child(TextInput::new(input)))
+            .child(
+                Button::new(id)
+                    .label("選擇…")
+                    .on_click(move |_, window, cx| {
+                        Self::choose_folder(moved_input.clone(), prompt, window, cx)
+                    }),
+            )
+    }
+}
+
+impl Render for SettingsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let operation_centers =
+            h_flex()
+                .flex_wrap()
+                .gap_x_4()
+                .gap_y_1()
+                .children(self.operation_centers.iter().map(|center| {
+                    let id = center.id.clone();
+                    Checkbox::new(SharedString::from(format!(
+                        "operation-center-{}",
+                        center.id
+                    )))
+                    .label(center.name.clone())
+                    .checked(self.selected_centers.contains(&center.id))
+                    .on_click(cx.listener(
+                        move |this, checked: &bool, _, cx| {
+                            this.toggle_operation_center(&id, *checked, cx)
+                        },
+                    ))
+                }));
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(div().text_2xl().child("設定"))
+            .child(
+                v_form()
+                    .column(2)
+                    .child(
+                        form_field()
+                            .label("紅網網址")
+                            .required(true)
+                            .child(TextInput::new(&self.base_url_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("部門代碼")
+                            .required(true)
+                            .description("2 為油品事業部")
+                            .child(TextInput::new(&self.department_id_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("預設營運中心")
+                            .col_span(2)
+                            .description(if self.operation_centers_description.is_empty() {
+                                SharedString::from("全不勾選時取得所有營運中心的贈品需求")
+                            } else {
+                                SharedString::from(&self.operation_centers_description)
+                            })
+                            .child(operation_centers),
+                    )
+                    .child(
+                        form_field()
+                            .label("逾時秒數")
+                            .required(true)
+                            .child(TextInput::new(&self.timeout_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("User-Agent")
+                            .description("留空時不送出")
+                            .child(TextInput::new(&self.user_agent_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("預設統計天數")
+                            .required(true)
+                            .description("訂貨通知單與原始資料預設選取到今天為止的天數")
+                            .child(TextInput::new(&self.default_days_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("輸出資料夾")
+                            .description("存檔時預設的資料夾，留空時為家目錄")
+                            .child(Self::render_folder_input(
+                                "choose-output-folder",
+                                &self.output_folder_input,
+                                "選擇輸出資料夾",
+                            )),
+                    )
+                    .child(
+                        form_field()
+                            .label("封存資料夾")
+                            .description("設定後訂購單不再詢問，直接依封存結構存入，留空時每次詢問")
+                            .child(Self::render_folder_input(
+                                "choose-archive-root",
+                                &self.archive_root_input,
+                                "選擇封存資料夾",
+                            )),
+                    )
+                    .child(
+                        form_field()
+                            .label("訂購單檔名格式")
+                            .required(true)
+                            .col_span(2)
+                            .description(
+                                "{freebie} 為贈品、{order_number} 為訂單編號、\
+                                 {notification_date} 為通知日期，例如 1141006、\
+                                 {roc_year} 為通知日期的民國年",
+                            )
+                            .child(TextInput::new(&self.order_file_name_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("封存結構")
+                            .required(true)
+                            .col_span(2)
+                            .description("以 / 分隔資料夾，可用的代號同檔名格式")
+                            .child(TextInput::new(&self.archive_layout_input)),
+                    )
+                    .child(
+                        form_field()
+                            .label("語言")
+                            .description("日期選擇器等元件的語言")
+                            .child(Dropdown::new(&self.locale_dropdown)),
+                    )
+                    .child(
+                        form_field().label("主題").child(
+                            TabBar::new("settings-theme-tab-bar")
+                                .segmented()
+                                .small()
+                                .selected_index(
+                                    Theme::all()
+                                        .iter()
+                                        .position(|theme| *theme == self.theme)
+                                        .unwrap_or_default(),
+                                )
+                                .on_click(cx.listener(|this, index: &usize, _, cx| {
+                                    this.theme = Theme::all()[*index];
+                                    cx.notify();
+                                }))
+                                .children(Theme::all().iter().map(|theme| Tab::new(theme.label()))),
+                        ),
+                    )
+                    .child(
+                        form_field()
+                            .no_label_indent()
+                            .col_span(2)
+                            .when(!self.description.is_empty(), |this| {
+                                this.description(SharedString::from(&self.description))
+                            })
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("save-settings")
+                                            .primary()
+                                            .label("儲存")
+                                            .on_click(cx.listener(|this, _, window, cx| {
+                                                this.save(window, cx)
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new("reset-settings").label("還原預設").on_click(
+                                            cx.listener(|this, _, window, cx| {
+                                                this.fill(&Settings::default(), window, cx)
+                                            }),
+                                        ),
+                                    )
+                                    .when_some(settings_file_path(), |this, path| {
+                                        this.child(
+                                            Button::new("reveal-settings-file")
+                                                .ghost()
+                                                .label("開啟設定檔位置")
+                                                .on_click(move |_, _, cx| cx.reveal_path(&path)),
+                                        )
+                                    }),
+                            ),
+                    ),
+            )
+    }
+}
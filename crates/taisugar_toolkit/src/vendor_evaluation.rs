@@ -0,0 +1,412 @@
+use crate::app_settings::output_folder;
+use crate::calendar::{WorkingDays, to_jiff_date, uncovered_year_description};
+use crate::ledger::OrderLedger;
+use anyhow::anyhow;
+use chrono::Local;
+use gpui::prelude::*;
+use gpui::{
+    AnyWindowHandle, App, AsyncApp, Entity, PathPromptOptions, SharedString, WeakEntity, Window,
+    div,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::form::{form_field, v_form};
+use gpui_component::input::{InputState, TextInput};
+use gpui_component::notification::{Notification, NotificationType};
+use gpui_component::{ActiveTheme, ContextModal, h_flex, v_flex};
+use log::{error, info};
+use order_ledger::{DeliveryQuery, OrderQuery, OrderStatus};
+use roc_calendar::{RocDate, WorkingDayCalendar};
+use std::path::PathBuf;
+use std::sync::Arc;
+use umya_spreadsheet::writer;
+use vendor_performance::{DeliveryLine, Period, VendorPerformance};
+
+pub struct VendorEvaluationView {
+    roc_year_input: Entity<InputState>,
+    roc_year_description: String,
+    /// The deliveries imported from the monthly delivery statistics workbooks.
+    imported_lines: Vec<DeliveryLine>,
+    imported_paths: Vec<PathBuf>,
+    /// The yearly evaluations of the vendors, shown after evaluating.
+    performances: Vec<VendorPerformance>,
+    button_loading: bool,
+}
+
+impl VendorEvaluationView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let roc_year = to_jiff_date(&Local::now().naive_local().date())
+            .and_then(|date| RocDate::try_from(date).ok())
+            .map(|date| date.roc_year())
+            .unwrap_or_default();
+        let roc_year_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .validate(|value, _| value.is_empty() || value.parse::<u16>().is_ok())
+                .default_value(roc_year.to_string())
+        });
+
+        VendorEvaluationView {
+            roc_year_input,
+            roc_year_description: String::new(),
+            imported_lines: vec![],
+            imported_paths: vec![],
+            performances: vec![],
+            button_loading: false,
+        }
+    }
+
+    fn get_roc_year(&mut self, cx: &mut Context<Self>) -> Option<i16> {
+        match self.roc_year_input.read(cx).value().trim().parse() {
+            Ok(roc_year) => {
+                self.roc_year_description = String::new();
+                Some(roc_year)
+            }
+            Err(_) => {
+                self.roc_year_description = "請輸入評鑑年度，例如 114".to_string();
+                cx.notify();
+                None
+            }
+        }
+    }
+
+    /// The lines of the orders in the ledger which have received deliveries, or are past
+    /// delivered, followed by the imported ones.
+    fn collect_lines(&self, cx: &App) -> anyhow::Result<Vec<DeliveryLine>> {
+        let ledger = &cx.global::<OrderLedger>().0;
+        let calendar = &cx.global::<WorkingDays>().0;
+        let deliveries = ledger.find_deliveries(&DeliveryQuery::default())?;
+        let mut lines = vec![];
+        for order in ledger.find_orders(&OrderQuery::default())? {
+            if order.status >= OrderStatus::Delivered
+                || deliveries
+                    .iter()
+                    .any(|delivery| delivery.order_id == order.id)
+            {
+                lines.extend(DeliveryLine::from_order(&order, &deliveries, calendar));
+            }
+        }
+        lines.extend(self.imported_lines.iter().cloned());
+        Ok(lines)
+    }
+
+    /// Evaluates the vendors over the ROC year and returns the evaluations of its months and of
+    /// the year itself.
+    fn evaluate(&mut self, cx: &mut Context<Self>) -> Option<Vec<VendorPerformance>> {
+        let roc_year = self.get_roc_year(cx)?;
+        let result = self.collect_lines(cx).and_then(|lines| {
+            let lines = lines
+                .into_iter()
+                .filter(|line| {
+                    RocDate::try_from(line.date).is_ok_and(|date| date.roc_year() == roc_year)
+                })
+                .collect::<Vec<_>>();
+            Ok(vendor_performance::evaluate_vendors(&lines)?)
+        });
+        match result {
+            Ok(performances) => {
+                if let Some(new_year) = roc_year
+                    .checked_add(1911)
+                    .and_then(|year| jiff::civil::Date::new(year, 1, 1).ok())
+                    && !cx.global::<WorkingDays>().0.covers(&new_year)
+                {
+                    self.roc_year_description = uncovered_year_description(&new_year, "延遲天數");
+                }
+                self.performances = performances
+                    .iter()
+                    .filter(|performance| matches!(performance.period, Period::Year(_)))
+                    .cloned()
+                    .collect();
+                cx.notify();
+                Some(performances)
+            }
+            Err(error) => {
+                self.roc_year_description = format!("無法評鑑廠商\n{}", error);
+                cx.notify();
+                None
+            }
+        }
+    }
+
+    fn import_workbooks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let window_handle = window.window_handle();
+        let calendar = cx.global::<WorkingDays>().0.clone();
+        let paths_receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+            prompt: Some("選擇交貨統計表".into()),
+        });
+        self.button_loading = true;
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let paths = cx
+                .background_spawn(paths_receiver)
+                .await
+                .ok()
+                .and_then(|paths| paths.ok().flatten())
+                .unwrap_or_default();
+            let moved_paths = paths.clone();
+            let result = cx
+                .background_spawn(async move { Self::read_workbooks(&moved_paths, &calendar) })
+                .await;
+            let _ = cx.update_window(window_handle, |_, window, cx| {
+                let _ = this.update(cx, |this, cx| {
+                    match result {
+                        Ok(lines) if !paths.is_empty() => {
+                            window.push_notification(
+                                (
+                                    NotificationType::Success,
+                                    SharedString::from(format!(
+                                        "已從 {} 個活頁簿匯入 {} 筆交貨",
+                                        paths.len(),
+                                        lines.len()
+                                    )),
+                                ),
+                                cx,
+                            );
+                            this.imported_lines.extend(lines);
+                            this.imported_paths.extend(paths);
+                        }
+                        Ok(_) => {}
+                        Err(error) => window.push_notification(
+                            (
+                                NotificationType::Error,
+                                SharedString::from(format!("無法匯入交貨統計表\n{}", error)),
+                            ),
+                            cx,
+                        ),
+                    }
+                    this.button_loading = false;
+                    cx.notify();
+                });
+            });
+        })
+        .detach();
+    }
+
+    fn read_workbooks(
+        paths: &[PathBuf],
+        calendar: &WorkingDayCalendar,
+    ) -> anyhow::Result<Vec<DeliveryLine>> {
+        let mut lines = vec![];
+        for path in paths {
+            let import = legacy_import::import_workbook(path)
+                .map_err(|error| anyhow!("{}: {}", path.display(), error))?;
+            lines.extend(
+                import
+                    .deliveries
+                    .iter()
+                    .filter_map(|delivery| DeliveryLine::from_legacy(delivery, calendar)),
+            );
+        }
+        Ok(lines)
+    }
+
+    fn export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(performances) = self.evaluate(cx) else {
+            return;
+        };
+        let window_handle = window.window_handle();
+        let file_name = format!(
+            "{}年廠商評鑑.xlsx",
+            self.roc_year_input.read(cx).value().trim()
+        );
+        let paths_receiver = cx.prompt_for_new_path(&output_folder(cx), Some(&file_name));
+        self.button_loading = true;
+        cx.notify();
+
+        let performances = Arc::new(performances);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            if let Ok(Ok(Some(path))) = cx.background_spawn(paths_receiver).await {
+                let path_string = path.to_string_lossy().to_string();
+                let write_result = cx
+                    .background_spawn(async move {
+                        let spreadsheet =
+                            vendor_performance::generate_vendor_performance_report(&performances);
+                        writer::xlsx::write(&spreadsheet, &path)
+                    })
+                    .await;
+                Self::notify_export(write_result.is_ok(), path_string, window_handle, cx);
+            }
+            let _ = this.update(cx, |this, cx| {
+                this.button_loading = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn notify_export(
+        is_written: bool,
+        path_string: String,
+        window_handle: AnyWindowHandle,
+        cx: &mut AsyncApp,
+    ) {
+        if is_written {
+            info!(path = path_string.as_str(); "wrote vendor evaluation");
+        } else {
+            error!(path = path_string.as_str(); "unable to write vendor evaluation");
+        }
+        let _ = cx.update_window(window_handle, |_, window, cx| {
+            let notification = if is_written {
+                Notification::new()
+                    .with_type(NotificationType::Success)
+                    .message(format!("已將廠商評鑑儲存到 {}", path_string))
+            } else {
+                Notification::new()
+                    .with_type(NotificationType::Error)
+                    .message(format!("無法將廠商評鑑儲存到 {}", path_string))
+            };
+            window.push_notification(notification, cx);
+        });
+    }
+
+    fn format_rate(rate: Option<f64>) -> String {
+        rate.map(|rate| format!("{:.1}%", rate * 100.))
+            .unwrap_or_default()
+    }
+
+    fn render_performances(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .text_color(cx.theme().muted_foreground)
+            .child(div().w_24().child("廠商"))
+            .child(div().w_20().child("交貨站次"))
+            .child(div().w_20().child("準時率"))
+            .child(div().w_24().child("平均延遲"))
+            .child(div().w_24().child("最大延遲"))
+            .child(div().w_20().child("短少數量"))
+            .child(div().flex_1().child("不合格率"));
+        let rows = self.performances.iter().map(|performance| {
+            h_flex()
+                .gap_2()
+                .py_1()
+                .child(div().w_24().child(performance.vendor))
+                .child(div().w_20().child(performance.delivered_lines.to_string()))
+                .child(
+                    div()
+                        .w_20()
+                        .child(Self::format_rate(performance.on_time_rate())),
+                )
+                .child(
+                    div().w_24().child(
+                        performance
+                            .mean_delay_days()
+                            .map(|days| format!("{:.1} 工作天", days))
+                            .unwrap_or_default(),
+                    ),
+                )
+                .child(
+                    div()
+                        .w_24()
+                        .child(format!("{} 工作天", performance.max_delay_days)),
+                )
+                .child(div().w_20().child(performance.shortfall.to_string()))
+                .child(
+                    div().flex_1().child(
+                        performance
+                            .defects
+                            .iter()
+                            .map(|(kind, _)| {
+                                format!(
+                                    "{} {}",
+                                    kind.label(),
+                                    Self::format_rate(performance.defect_rate(*kind))
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join("、"),
+                    ),
+                )
+        });
+
+        v_flex().child(header).children(rows)
+    }
+}
+
+impl Render for VendorEvaluationView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(div().text_2xl().child("廠商評鑑"))
+            .child(
+                v_form()
+                    .child(
+                        form_field()
+                            .label("評鑑年度")
+                            .required(true)
+                            .when(!self.roc_year_description.is_empty(), |this| {
+                                this.description(SharedString::from(&self.roc_year_description))
+                            })
+                            .child(TextInput::new(&self.roc_year_input).suffix("年")),
+                    )
+                    .child(form_field().label("交貨統計表").child(
+                        div().text_color(cx.theme().muted_foreground).child(
+                            if self.imported_paths.is_empty() {
+                                "只計算本程式登錄的交貨，可再匯入每月交貨統計表".to_string()
+                            } else {
+                                format!(
+                                    "已匯入 {} 個活頁簿的 {} 筆交貨，請勿與本程式登錄的月份重複",
+                                    self.imported_paths.len(),
+                                    self.imported_lines.len()
+                                )
+                            },
+                        ),
+                    ))
+                    .child(
+                        form_field().no_label_indent().child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("evaluate-vendors")
+                                        .primary()
+                                        .label("評鑑")
+                                        .loading(self.button_loading)
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.evaluate(cx);
+                                        })),
+                                )
+                                .child(
+                                    Button::new("export-vendor-evaluation")
+                                        .label("匯出評鑑表")
+                                        .loading(self.button_loading)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.export(window, cx)
+                                        })),
+                                )
+                                .child(
+                                    Button::new("import-delivery-statistics")
+                                        .label("匯入交貨統計表")
+                                        .loading(self.button_loading)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.import_workbooks(window, cx)
+                                        })),
+                                )
+                                .when(!self.imported_paths.is_empty(), |this| {
+                                    this.child(
+                                        Button::new("clear-delivery-statistics")
+                                            .ghost()
+                                            .label("清除匯入")
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.imported_lines.clear();
+                                                this.imported_paths.clear();
+                                                cx.notify();
+                                            })),
+                                    )
+                                }),
+                        ),
+                    ),
+            )
+            .when(!self.performances.is_empty(), |this| {
+                this.child(self.render_performances(cx))
+            })
+    }
+}
@@ -1,14 +1,34 @@
+use crate::dashboard::DashboardView;
+use crate::delivery_entry::DeliveryEntryView;
 use crate::delivery_record::DeliveryRecordView;
+use crate::inspector::InspectorView;
+use crate::log_panel::LogPanel;
+use crate::monthly_report::MonthlyReportView;
+use crate::order_status::OrderStatusView;
 use crate::purchase_order::PurchaseOrderView;
+use crate::settings::SettingsView;
+use crate::vendor_evaluation::VendorEvaluationView;
 use gpui::prelude::*;
-use gpui::{App, ClickEvent, Entity, Window, div, img, relative};
-use gpui_component::sidebar::{Sidebar, SidebarGroup, SidebarHeader, SidebarMenu, SidebarMenuItem};
-use gpui_component::{ActiveTheme, Root, Side, h_flex, v_flex};
+use gpui::{App, ClickEvent, Entity, MouseButton, Subscription, Window, div, img, relative};
+use gpui_component::sidebar::{
+    Sidebar, SidebarFooter, SidebarGroup, SidebarHeader, SidebarMenu, SidebarMenuItem,
+};
+use gpui_component::{ActiveTheme, Icon, IconName, Root, Selectable, Side, h_flex, v_flex};
 
 pub struct ToolkitView {
     active_item: MenuItem,
+    dashboard_view: Entity<DashboardView>,
     purchase_order_view: Entity<PurchaseOrderView>,
     delivery_record_view: Entity<DeliveryRecordView>,
+    order_status_view: Entity<OrderStatusView>,
+    delivery_entry_view: Entity<DeliveryEntryView>,
+    vendor_evaluation_view: Entity<VendorEvaluationView>,
+    monthly_report_view: Entity<MonthlyReportView>,
+    inspector_view: Entity<InspectorView>,
+    settings_view: Entity<SettingsView>,
+    log_panel: Entity<LogPanel>,
+    is_log_panel_open: bool,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl ToolkitView {
@@ -17,13 +37,34 @@ impl ToolkitView {
     }
 
     fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let dashboard_view = DashboardView::view(window, cx);
         let purchase_order_view = PurchaseOrderView::view(window, cx);
         let delivery_record_view = DeliveryRecordView::view(window, cx);
+        let order_status_view = OrderStatusView::view(window, cx);
+        let delivery_entry_view = DeliveryEntryView::view(window, cx);
+        let vendor_evaluation_view = VendorEvaluationView::view(window, cx);
+        let monthly_report_view = MonthlyReportView::view(window, cx);
+        let inspector_view = InspectorView::view(window, cx);
+        let settings_view = SettingsView::view(window, cx);
+        let log_panel = LogPanel::view(window, cx);
+
+        // Update the error count of the sidebar as events are logged
+        let subscriptions = vec![cx.observe(&log_panel, |_, _, cx| cx.notify())];
 
         ToolkitView {
-            active_item: MenuItem::PurchaseOrderNotice,
+            active_item: MenuItem::Dashboard,
+            dashboard_view,
             purchase_order_view,
             delivery_record_view,
+            order_status_view,
+            delivery_entry_view,
+            vendor_evaluation_view,
+            monthly_report_view,
+            inspector_view,
+            settings_view,
+            log_panel,
+            is_log_panel_open: false,
+            _subscriptions: subscriptions,
         }
     }
 
@@ -33,30 +74,89 @@ impl ToolkitView {
         _cx: &mut Context<Self>,
     ) -> impl IntoElement {
         v_flex()
+            .when(self.active_item == MenuItem::Dashboard, |this| {
+                this.child(self.dashboard_view.clone())
+            })
             .when(self.active_item == MenuItem::PurchaseOrderNotice, |this| {
                 this.child(self.purchase_order_view.clone())
             })
             .when(self.active_item == MenuItem::DeliveryRecordSheet, |this| {
                 this.child(self.delivery_record_view.clone())
             })
+            .when(self.active_item == MenuItem::OrderStatus, |this| {
+                this.child(self.order_status_view.clone())
+            })
+            .when(self.active_item == MenuItem::DeliveryEntry, |this| {
+                this.child(self.delivery_entry_view.clone())
+            })
+            .when(self.active_item == MenuItem::VendorEvaluation, |this| {
+                this.child(self.vendor_evaluation_view.clone())
+            })
+            .when(self.active_item == MenuItem::MonthlyReport, |this| {
+                this.child(self.monthly_report_view.clone())
+            })
+            .when(self.active_item == MenuItem::Inspector, |this| {
+                this.child(self.inspector_view.clone())
+            })
+            .when(self.active_item == MenuItem::Settings, |this| {
+                this.child(self.settings_view.clone())
+            })
     }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone)]
 enum MenuItem {
+    Dashboard,
     PurchaseOrderNotice,
     DeliveryRecordSheet,
+    OrderStatus,
+    DeliveryEntry,
+    VendorEvaluation,
+    MonthlyReport,
+    Inspector,
+    Settings,
 }
 
 impl MenuItem {
-    fn all() -> [Self; 2] {
-        [MenuItem::PurchaseOrderNotice, MenuItem::DeliveryRecordSheet]
+    fn all() -> [Self; 9] {
+        [
+            MenuItem::Dashboard,
+            MenuItem::PurchaseOrderNotice,
+            MenuItem::DeliveryRecordSheet,
+            MenuItem::OrderStatus,
+            MenuItem::DeliveryEntry,
+            MenuItem::VendorEvaluation,
+            MenuItem::MonthlyReport,
+            MenuItem::Inspector,
+            MenuItem::Settings,
+        ]
     }
 
     fn label(&self) -> &'static str {
         match self {
+            MenuItem::Dashboard => "總覽",
             MenuItem::PurchaseOrderNotice => "訂貨通知單",
             MenuItem::DeliveryRecordSheet => "交貨統計表",
+            MenuItem::OrderStatus => "訂單追蹤",
+            MenuItem::DeliveryEntry => "交貨登錄",
+            MenuItem::VendorEvaluation => "廠商評鑑",
+            MenuItem::MonthlyReport => "月結報表",
+            MenuItem::Inspector => "原始資料",
+            MenuItem::Settings => "設定",
+        }
+    }
+
+    fn icon(&self) -> IconName {
+        match self {
+            MenuItem::Dashboard => IconName::LayoutDashboard,
+            MenuItem::PurchaseOrderNotice => IconName::File,
+            MenuItem::DeliveryRecordSheet => IconName::BookOpen,
+            MenuItem::OrderStatus => IconName::Inbox,
+            MenuItem::DeliveryEntry => IconName::CircleCheck,
+            MenuItem::VendorEvaluation => IconName::ChartPie,
+            MenuItem::MonthlyReport => IconName::Calendar,
+            MenuItem::Inspector => IconName::Inspector,
+            MenuItem::Settings => IconName::Settings,
         }
     }
 
@@ -67,6 +167,16 @@ impl MenuItem {
         let item = *self;
         move |this, _, _, cx| {
             this.active_item = item;
+            // Show the orders saved or sent since the view was last opened
+            match item {
+                MenuItem::OrderStatus => this
+                    .order_status_view
+                    .update(cx, |view, cx| view.refresh(cx)),
+                MenuItem::DeliveryEntry => this
+                    .delivery_entry_view
+                    .update(cx, |view, cx| view.refresh(cx)),
+                _ => {}
+            }
             cx.notify();
         }
     }
@@ -75,6 +185,7 @@ impl MenuItem {
 impl Render for ToolkitView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let notification_layer = Root::render_notification_layer(window, cx);
+        let error_count = self.log_panel.read(cx).error_count();
 
         div().size_full().children(notification_layer).child(
             h_flex()
@@ -103,17 +214,55 @@ impl Render for ToolkitView {
                         .child(SidebarGroup::new("贈品").child(SidebarMenu::new().children(
                             MenuItem::all().iter().map(|item| {
                                 SidebarMenuItem::new(item.label())
+                                    .icon(item.icon())
                                     .active(item == &self.active_item)
                                     .on_click(cx.listener(item.handler()))
                             }),
-                        ))),
+                        )))
+                        .footer(
+                            SidebarFooter::new()
+                                .selected(self.is_log_panel_open)
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|this, _, _, cx| {
+                                        this.is_log_panel_open = !this.is_log_panel_open;
+                                        cx.notify();
+                                    }),
+                                )
+                                .child(Icon::new(IconName::SquareTerminal))
+                                .child(div().flex_1().child("活動記錄"))
+                                .when(error_count > 0, |this| {
+                                    this.child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(cx.theme().danger)
+                                            .child(format!("{} 個錯誤", error_count)),
+                                    )
+                                }),
+                        ),
                 )
                 .child(
                     v_flex()
                         .size_full()
-                        .gap_4()
-                        .p_4()
-                        .child(self.render_content(window, cx)),
+                        .child(
+                            v_flex()
+                                .id("toolkit-content")
+                                .flex_1()
+                                .overflow_y_scroll()
+                                .gap_4()
+                                .p_4()
+                                .child(self.render_content(window, cx)),
+                        )
+                        .when(self.is_log_panel_open, |this| {
+                            this.child(
+                                div()
+                                    .h_64()
+                                    .flex_none()
+                                    .border_t_1()
+                                    .border_color(cx.theme().border)
+                                    .child(self.log_panel.clone()),
+                            )
+                        }),
                 ),
         )
     }
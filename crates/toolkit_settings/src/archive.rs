@@ -0,0 +1,132 @@
+use crate::Error;
+use crate::settings::fill_placeholders;
+use freebie::{Freebie, OrderNumber};
+use jiff::civil::Date;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const DEFAULT_ARCHIVE_LAYOUT: &str =
+    "{roc_year}/{freebie}/{order_number}_{notification_date}";
+
+/// The characters which cannot be in the name of a file or folder on Windows.
+const INVALID_CHARACTERS: [char; 7] = [':', '*', '?', '"', '<', '>', '|'];
+
+/// A folder where weekly order forms are filed by a layout such as
+/// `{roc_year}/{freebie}/{order_number}_{notification_date}`, with `/` separating the folders.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Archive {
+    root: PathBuf,
+    layout: String,
+}
+
+impl Archive {
+    pub fn new(root: impl Into<PathBuf>, layout: impl Into<String>) -> Self {
+        Archive {
+            root: root.into(),
+            layout: layout.into(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The path of the weekly order form of the freebie with the extension, such as
+    /// `114/60抽面紙/10-3_1141006.xlsx` within the root. The folders never leave the root.
+    pub fn order_path(
+        &self,
+        freebie: &Freebie,
+        order_number: &OrderNumber,
+        notification_date: &Date,
+        extension: &str,
+    ) -> PathBuf {
+        let split_layout = |layout: &str| {
+            fill_placeholders(layout, freebie, order_number, notification_date)
+                .split(['/', '\\'])
+                .map(|component| component.trim().replace(INVALID_CHARACTERS, "-"))
+                .filter(|component| !matches!(component.as_str(), "" | "." | ".."))
+                .collect::<Vec<_>>()
+        };
+        let mut components = split_layout(&self.layout);
+        if components.is_empty() {
+            components = split_layout(DEFAULT_ARCHIVE_LAYOUT);
+        }
+
+        // Append the extension rather than replace what follows a dot in the file name
+        if let Some(file_name) = components.last_mut() {
+            file_name.push('.');
+            file_name.push_str(extension);
+        }
+        let mut path = self.root.clone();
+        path.extend(&components);
+        path
+    }
+
+    /// Creates the folders of a new file at the path, refusing to overwrite the file if it
+    /// exists.
+    pub fn prepare(path: &Path) -> Result<(), Error> {
+        if path.exists() {
+            return Err(Error::FileExists(path.to_path_buf()));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_path() {
+        let order_number = "10-3".parse().unwrap();
+        let notification_date = Date::new(2025, 10, 6).unwrap();
+        let archive = Archive::new("archive", DEFAULT_ARCHIVE_LAYOUT);
+        assert_eq!(
+            archive.order_path(
+                &Freebie::Tissue60,
+                &order_number,
+                &notification_date,
+                "xlsx"
+            ),
+            Path::new("archive/114/60抽面紙/10-3_1141006.xlsx")
+        );
+
+        // Keep the files within the root
+        let archive = Archive::new("archive", "../{freebie}/./{order_number}: {roc_year}");
+        assert_eq!(
+            archive.order_path(
+                &Freebie::MineralWater,
+                &order_number,
+                &notification_date,
+                "pdf"
+            ),
+            Path::new("archive/礦泉水/10-3- 114.pdf")
+        );
+        let archive = Archive::new("archive", " / ");
+        assert_eq!(
+            archive.order_path(
+                &Freebie::Tissue110,
+                &order_number,
+                &notification_date,
+                "xlsx"
+            ),
+            Path::new("archive/114/110抽面紙/10-3_1141006.xlsx")
+        );
+    }
+
+    #[test]
+    fn test_prepare() {
+        let root = std::env::temp_dir().join(format!("toolkit-archive-{}", std::process::id()));
+        let path = root.join("114").join("60抽面紙").join("10-3_1141006.xlsx");
+        Archive::prepare(&path).unwrap();
+        assert!(path.parent().unwrap().is_dir());
+
+        // Never overwrite a filed order form
+        fs::write(&path, "").unwrap();
+        assert!(matches!(Archive::prepare(&path), Err(Error::FileExists(_))));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
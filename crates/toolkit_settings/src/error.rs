@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    TomlDeError(#[from] toml::de::Error),
+
+    #[error("file already exists: {}", .0.display())]
+    FileExists(PathBuf),
+
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
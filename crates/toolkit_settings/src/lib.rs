@@ -0,0 +1,10 @@
+mod archive;
+mod error;
+mod settings;
+
+pub use crate::archive::Archive;
+pub use crate::error::Error;
+pub use crate::settings::{
+    AppearanceSettings, ArchiveSettings, HttpSettings, OutputSettings, ReportSettings, Settings,
+    Theme, TscredSettings,
+};
@@ -0,0 +1,305 @@
+use crate::archive::DEFAULT_ARCHIVE_LAYOUT;
+use crate::{Archive, Error};
+use freebie::{Freebie, OrderNumber};
+use jiff::civil::Date;
+use roc_calendar::{RocDate, RocDateFormat};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const DEFAULT_ORDER_FILE_NAME: &str = "{freebie}每週訂購單{order_number}";
+
+/// The settings of the toolkit, saved as TOML.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub tscred: TscredSettings,
+    pub http: HttpSettings,
+    pub report: ReportSettings,
+    pub output: OutputSettings,
+    pub archive: ArchiveSettings,
+    pub appearance: AppearanceSettings,
+}
+
+impl FromStr for Settings {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+impl Display for Settings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = toml::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TscredSettings {
+    /// The root of the TSCRED site, such as `http://192.168.41.30/TSCRED`.
+    pub base_url: String,
+    /// The department whose item needs are fetched, `2` being 油品事業部.
+    pub department_id: String,
+    /// The ids of the operation centers whose item needs are fetched, or empty for all of them.
+    pub operation_centers: Vec<String>,
+}
+
+impl Default for TscredSettings {
+    fn default() -> Self {
+        TscredSettings {
+            base_url: tscred::DEFAULT_BASE_URL.to_string(),
+            department_id: "2".to_string(),
+            operation_centers: vec![],
+        }
+    }
+}
+
+impl TscredSettings {
+    /// Whether the item needs of the operation center are fetched.
+    pub fn includes_operation_center(&self, operation_center_id: &str) -> bool {
+        self.operation_centers.is_empty()
+            || self
+                .operation_centers
+                .iter()
+                .any(|id| id == operation_center_id)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// The time a request may take in all before it is given up.
+    pub timeout_secs: u64,
+    /// The `User-Agent` header sent with the requests, or empty to send none.
+    pub user_agent: String,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            timeout_secs: 5,
+            user_agent: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSettings {
+    /// The number of days up to today selected for a report by default.
+    pub default_days: u64,
+}
+
+impl Default for ReportSettings {
+    fn default() -> Self {
+        ReportSettings { default_days: 7 }
+    }
+}
+
+/// Where files are suggested to be saved.
+///
+/// The order file name may contain `{freebie}`, `{order_number}`, `{notification_date}` and
+/// `{roc_year}`, which are replaced by the short name of the freebie, the order number, the compact
+/// ROC notification date such as `1140930` and the ROC year of the notification date.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputSettings {
+    /// The folder suggested for saving files, or empty for the home folder.
+    pub folder: String,
+    /// The name suggested for a weekly order form, without its extension.
+    pub order_file_name: String,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        OutputSettings {
+            folder: String::new(),
+            order_file_name: DEFAULT_ORDER_FILE_NAME.to_string(),
+        }
+    }
+}
+
+impl OutputSettings {
+    /// The folder suggested for saving files, or `None` for the home folder.
+    pub fn folder(&self) -> Option<PathBuf> {
+        let folder = self.folder.trim();
+        (!folder.is_empty()).then(|| PathBuf::from(folder))
+    }
+
+    /// The name suggested for the weekly order form of the freebie with the extension, such as
+    /// `60抽面紙每週訂購單10-3.xlsx`.
+    pub fn order_file_name(
+        &self,
+        freebie: &Freebie,
+        order_number: &OrderNumber,
+        notification_date: &Date,
+        extension: &str,
+    ) -> String {
+        let file_name = fill_placeholders(
+            &self.order_file_name,
+            freebie,
+            order_number,
+            notification_date,
+        )
+        // Keep the file within the folder it is saved in
+        .replace(['/', '\\'], "-");
+        format!("{}.{}", file_name.trim(), extension)
+    }
+}
+
+/// Where saved weekly order forms are filed without asking, laid out by the placeholders of the
+/// order file name with `/` separating the folders.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    /// The root folder of the archive, or empty to ask where to save every order form.
+    pub root: String,
+    /// The path of an order form within the root, without its extension.
+    pub layout: String,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        ArchiveSettings {
+            root: String::new(),
+            layout: DEFAULT_ARCHIVE_LAYOUT.to_string(),
+        }
+    }
+}
+
+impl ArchiveSettings {
+    /// The archive to file order forms in, or `None` if no root folder is set.
+    pub fn archive(&self) -> Option<Archive> {
+        let root = self.root.trim();
+        (!root.is_empty()).then(|| Archive::new(root, self.layout.trim()))
+    }
+}
+
+/// Replaces the placeholders of a file name or archive layout with those of the weekly order.
+pub(crate) fn fill_placeholders(
+    pattern: &str,
+    freebie: &Freebie,
+    order_number: &OrderNumber,
+    notification_date: &Date,
+) -> String {
+    let roc_date = RocDate::try_from(*notification_date).ok();
+    let compact_date = roc_date
+        .map(|date| date.format(RocDateFormat::Compact).to_string())
+        .unwrap_or_else(|| notification_date.strftime("%Y%m%d").to_string());
+    let roc_year = roc_date
+        .map(|date| date.roc_year().to_string())
+        .unwrap_or_else(|| notification_date.year().to_string());
+    pattern
+        .replace("{freebie}", freebie.short_name())
+        .replace("{order_number}", &order_number.to_string())
+        .replace("{notification_date}", &compact_date)
+        .replace("{roc_year}", &roc_year)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    /// The locale of the components such as the date pickers, one of `zh-HK`, `zh-CN` and `en`.
+    pub locale: String,
+    pub theme: Theme,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        AppearanceSettings {
+            locale: "zh-HK".to_string(),
+            theme: Theme::Light,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Follows the appearance of the system.
+    System,
+}
+
+impl Theme {
+    pub fn all() -> [Theme; 3] {
+        [Theme::Light, Theme::Dark, Theme::System]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Light => "淺色",
+            Theme::Dark => "深色",
+            Theme::System => "跟隨系統",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings: Settings = r#"
+[tscred]
+base_url = "http://localhost:8080/TSCRED"
+operation_centers = ["1", "3"]
+
+[http]
+timeout_secs = 30
+
+[appearance]
+theme = "dark"
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(settings.tscred.base_url, "http://localhost:8080/TSCRED");
+        assert_eq!(settings.tscred.department_id, "2");
+        assert!(settings.tscred.includes_operation_center("3"));
+        assert!(!settings.tscred.includes_operation_center("2"));
+        assert_eq!(settings.http.timeout_secs, 30);
+        assert_eq!(settings.http.user_agent, "");
+        assert_eq!(settings.report.default_days, 7);
+        assert_eq!(settings.appearance.locale, "zh-HK");
+        assert_eq!(settings.appearance.theme, Theme::Dark);
+
+        // What is saved is read back the same
+        assert_eq!(settings.to_string().parse::<Settings>().unwrap(), settings);
+        assert!(Settings::default().tscred.includes_operation_center("2"));
+    }
+
+    #[test]
+    fn test_order_file_name() {
+        let mut output = OutputSettings::default();
+        let order_number = "10-3".parse().unwrap();
+        let notification_date = Date::new(2025, 10, 6).unwrap();
+        assert_eq!(
+            output.order_file_name(
+                &Freebie::Tissue60,
+                &order_number,
+                &notification_date,
+                "xlsx"
+            ),
+            "60抽面紙每週訂購單10-3.xlsx"
+        );
+
+        output.order_file_name = "{notification_date}/{freebie}_{order_number}".to_string();
+        assert_eq!(
+            output.order_file_name(
+                &Freebie::MineralWater,
+                &order_number,
+                &notification_date,
+                "pdf"
+            ),
+            "1141006-礦泉水_10-3.pdf"
+        );
+        assert_eq!(output.folder(), None);
+        assert_eq!(ArchiveSettings::default().archive(), None);
+    }
+}
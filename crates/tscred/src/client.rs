@@ -1,41 +1,152 @@
 use crate::error::Error;
 use crate::{GetItemNeedsOptions, ItemNeeds, OperationCenter};
-use ureq::Agent;
+use chunked_fetch::{ChunkOptions, Progress, fetch_chunks, split_date_range};
+use log::{debug, info, warn};
+use std::time::Instant;
+use ureq::typestate::WithoutBody;
+use ureq::{Agent, Body, RequestBuilder};
 
-const GET_OPERATION_CENTERS_URL: &str =
-    "http://192.168.41.30/TSCRED/BulkPeriodSheet/CennoDropdownList";
-const GET_ITEM_NEEDS_URL: &str = "http://192.168.41.30/TSCRED/ItemNeedCount/GetItemNeedCount";
+/// The root of the TSCRED site on the intranet.
+pub const DEFAULT_BASE_URL: &str = "http://192.168.41.30/TSCRED";
+const GET_OPERATION_CENTERS_PATH: &str = "BulkPeriodSheet/CennoDropdownList";
+const GET_ITEM_NEEDS_PATH: &str = "ItemNeedCount/GetItemNeedCount";
 
 pub struct Client {
     agent: Agent,
+    base_url: String,
 }
 
 impl Client {
     pub fn new(agent: Agent) -> Self {
-        Self { agent }
+        Self::with_base_url(agent, DEFAULT_BASE_URL)
+    }
+
+    /// Creates a client of the TSCRED site at the base URL, such as a test server.
+    pub fn with_base_url(agent: Agent, base_url: impl Into<String>) -> Self {
+        Self {
+            agent,
+            base_url: base_url.into(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
     }
 
     pub fn get_operation_centers(&self) -> Result<Vec<OperationCenter>, Error> {
-        Ok(self
-            .agent
-            .get(GET_OPERATION_CENTERS_URL)
-            .call()?
-            .body_mut()
-            .read_json()?)
+        fetch(
+            self.agent.get(self.url(GET_OPERATION_CENTERS_PATH)),
+            |body| body.read_json::<Vec<OperationCenter>>(),
+            |centers| Some(centers.len()),
+        )
     }
 
     pub fn get_item_needs(&self, options: GetItemNeedsOptions<'_>) -> Result<ItemNeeds, Error> {
-        Ok(self
-            .agent
-            .get(GET_ITEM_NEEDS_URL)
+        fetch(
+            self.item_needs_request(options),
+            |body| body.read_json::<ItemNeeds>(),
+            |item_needs| Some(item_needs.row_count()),
+        )
+    }
+
+    /// Gets the item needs like [`Client::get_item_needs`], but returns the body of the response
+    /// as it is, to be inspected or parsed with [`ItemNeeds::from_json`].
+    pub fn get_item_needs_raw(&self, options: GetItemNeedsOptions<'_>) -> Result<String, Error> {
+        fetch(
+            self.item_needs_request(options),
+            |body| body.read_to_string(),
+            |_| None,
+        )
+    }
+
+    fn item_needs_request(&self, options: GetItemNeedsOptions<'_>) -> RequestBuilder<WithoutBody> {
+        debug!(
+            operation_center = options.operation_center_id,
+            start_date:% = options.start_date,
+            end_date:% = options.end_date,
+            display_mode:% = options.display_mode;
+            "requesting item needs"
+        );
+        self.agent
+            .get(self.url(GET_ITEM_NEEDS_PATH))
             .query("CLANA", "")
             .query("CLANA2", options.operation_center_id)
             .query("CLANO", options.start_date.strftime("%Y/%m/%d").to_string())
             .query("CLANO2", options.end_date.strftime("%Y/%m/%d").to_string())
             .query("DSP_SEL", options.display_mode.to_string())
             .query("HOST", options.department_id)
-            .call()?
-            .body_mut()
-            .read_json()?)
+    }
+
+    /// Gets the item needs like [`Client::get_item_needs`], but splits the date range into chunks
+    /// which are fetched concurrently and merged into one [`ItemNeeds`].
+    pub fn get_item_needs_chunked<P: Fn(Progress) + Sync>(
+        &self,
+        options: GetItemNeedsOptions<'_>,
+        chunk_options: &ChunkOptions,
+        on_progress: P,
+    ) -> Result<ItemNeeds, Error> {
+        let chunks = split_date_range(
+            options.start_date,
+            options.end_date,
+            chunk_options.chunk_size,
+        );
+        let item_needs = fetch_chunks(
+            &chunks,
+            chunk_options.max_concurrency,
+            |start_date, end_date| {
+                self.get_item_needs(GetItemNeedsOptions {
+                    start_date,
+                    end_date,
+                    ..options
+                })
+            },
+            on_progress,
+        )?;
+        Ok(ItemNeeds::merge(item_needs))
+    }
+}
+
+/// Sends the request and reads the body, logging the status, the duration and the number of rows
+/// received if it can be told, or the error.
+fn fetch<T>(
+    request: RequestBuilder<WithoutBody>,
+    read: impl FnOnce(&mut Body) -> Result<T, ureq::Error>,
+    rows: impl FnOnce(&T) -> Option<usize>,
+) -> Result<T, Error> {
+    let url = request
+        .uri_ref()
+        .map(|uri| uri.to_string())
+        .unwrap_or_default();
+    let started = Instant::now();
+    let result = request.call().and_then(|mut response| {
+        let status = response.status().as_u16();
+        read(response.body_mut()).map(|value| (status, value))
+    });
+    let duration_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok((status, value)) => {
+            info!(
+                url = url.as_str(),
+                status,
+                duration_ms,
+                rows:? = rows(&value);
+                "received response"
+            );
+            Ok(value)
+        }
+        Err(error) => {
+            let status = match error {
+                ureq::Error::StatusCode(status) => Some(status),
+                _ => None,
+            };
+            warn!(
+                url = url.as_str(),
+                status:?,
+                duration_ms,
+                error:%;
+                "request failed"
+            );
+            Err(error.into())
+        }
     }
 }
@@ -5,8 +5,11 @@ pub enum Error {
     #[error(transparent)]
     JiffError(#[from] jiff::Error),
 
-    #[error("date parse error: {0}")]
-    ParseDateError(String),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    RocDateError(#[from] roc_calendar::Error),
 
     #[error(transparent)]
     UreqError(#[from] ureq::Error),
@@ -1,6 +1,7 @@
-use crate::date::parse_date_from_roc_calendar;
+use crate::Error;
 use jiff::civil::Date;
-use serde::Deserialize;
+use roc_calendar::RocDate;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
 
@@ -12,12 +13,32 @@ pub struct GetItemNeedsOptions<'a> {
     pub department_id: &'a str,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum DisplayMode {
     ByStation,
     ByDate,
     Details,
 }
 
+impl DisplayMode {
+    pub fn all() -> [DisplayMode; 3] {
+        [
+            DisplayMode::ByStation,
+            DisplayMode::ByDate,
+            DisplayMode::Details,
+        ]
+    }
+
+    /// The name of the display mode on the TSCRED page.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::ByStation => "依油站",
+            DisplayMode::ByDate => "依日期",
+            DisplayMode::Details => "明細",
+        }
+    }
+}
+
 impl Display for DisplayMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self {
@@ -29,7 +50,7 @@ impl Display for DisplayMode {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 struct DynamicColumn {
     field: String,
     title: String,
@@ -38,24 +59,33 @@ struct DynamicColumn {
     width: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 struct DataKV {
     key: String,
     value: Value,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 #[serde(untagged)]
 enum Value {
     String(String),
     Number(u64),
 }
 
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 const STATION_NAME_KEY: &str = "NAME";
 const ORDER_DATE_KEY: &str = "ORDNO";
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ItemNeeds {
     dynamic_columns: Vec<DynamicColumn>,
@@ -63,8 +93,40 @@ pub struct ItemNeeds {
 }
 
 impl ItemNeeds {
+    pub fn from_json(json: &str) -> Result<ItemNeeds, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Merges the item needs of several queries into one, keeping the first occurrence of every
+    /// column. The rows are only concatenated, so item needs grouped by station or by date and
+    /// fetched in chunks hold a row per chunk for the same station or date, which must be summed
+    /// before being shown as they are.
+    pub fn merge<I: IntoIterator<Item = ItemNeeds>>(item_needs_iter: I) -> ItemNeeds {
+        let mut merged = ItemNeeds {
+            dynamic_columns: vec![],
+            data: vec![],
+        };
+        for item_needs in item_needs_iter {
+            for column in item_needs.dynamic_columns {
+                if !merged
+                    .dynamic_columns
+                    .iter()
+                    .any(|merged_column| merged_column.field == column.field)
+                {
+                    merged.dynamic_columns.push(column);
+                }
+            }
+            merged.data.extend(item_needs.data);
+        }
+        merged
+    }
+
     pub fn get_all_items(&self) -> Vec<Item> {
-        let mut items = Vec::with_capacity(self.dynamic_columns.len() - 3);
+        let mut items = Vec::with_capacity(self.dynamic_columns.len().saturating_sub(3));
         for column in self.dynamic_columns.iter() {
             // Find all columns whose field starts with "A_", which are columns for items
             if column.field.starts_with("A_") {
@@ -77,6 +139,55 @@ impl ItemNeeds {
         items
     }
 
+    /// The number of rows, one per station and date in the details.
+    pub fn row_count(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The columns in the order TSCRED shows them, including the station and date columns.
+    pub fn columns(&self) -> Vec<Column> {
+        self.dynamic_columns
+            .iter()
+            .map(|column| Column {
+                field: column.field.clone(),
+                title: column.title.clone(),
+            })
+            .collect()
+    }
+
+    /// The values of each row by the field of their column, as text. Unlike [`ItemNeeds::iter`],
+    /// this reads the rows of every display mode.
+    pub fn rows(&self) -> Vec<HashMap<&str, String>> {
+        self.data
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|kv| (kv.key.as_str(), kv.value.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Writes the rows as CSV with the column titles as the header.
+    pub fn to_csv(&self) -> String {
+        let columns = self.columns();
+        let mut csv = columns
+            .iter()
+            .map(|column| escape_csv(&column.title))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str("\r\n");
+        for row in self.rows() {
+            let values = columns
+                .iter()
+                .map(|column| escape_csv(row.get(column.field.as_str()).map_or("", |value| value)))
+                .collect::<Vec<_>>();
+            csv.push_str(&values.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
     pub fn iter(&self) -> Iter<'_> {
         Iter {
             target: self,
@@ -85,6 +196,21 @@ impl ItemNeeds {
     }
 }
 
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A column of the item needs as returned by TSCRED.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub field: String,
+    pub title: String,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Item {
     pub id: String,
@@ -117,8 +243,10 @@ impl<'a> Iterator for Iter<'a> {
                 Value::String(value) => match kv.key.as_str() {
                     STATION_NAME_KEY => station_name = value,
                     ORDER_DATE_KEY => {
-                        order_date = parse_date_from_roc_calendar(value)
+                        order_date = value
+                            .parse::<RocDate>()
                             .expect("unable to parse date from roc calendar")
+                            .date()
                     }
                     _ => {}
                 },
@@ -213,6 +341,63 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_rows() {
+        let json = include_str!("../../../testdata/GetItemNeedCount.json");
+        let item_needs = ItemNeeds::from_json(json).unwrap();
+        let columns = item_needs.columns();
+        assert_eq!(columns.len(), 16);
+        assert_eq!(
+            columns[1],
+            Column {
+                field: "NAME".to_string(),
+                title: "油站名稱".to_string(),
+            }
+        );
+
+        let rows = item_needs.rows();
+        assert_eq!(rows.len(), 45);
+        assert_eq!(rows[0].get("NAME").unwrap(), "成功嶺站");
+        assert_eq!(rows[0].get("ORDNO").unwrap(), "114-09-30");
+        assert_eq!(rows[0].get("A_G002").unwrap(), "60");
+
+        let item_needs = ItemNeeds::from_json(&item_needs.to_json().unwrap()).unwrap();
+        assert_eq!(item_needs.columns(), columns);
+        assert_eq!(item_needs.rows(), rows);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let item_needs = ItemNeeds::from_json(
+            r#"{
+                "dynamicColumns": [
+                    {"field": "NAME", "title": "油站名稱", "width": "180px"},
+                    {"field": "A_G001", "title": "面紙, \"60抽\"", "width": "200px"}
+                ],
+                "data": [
+                    [{"Key": "NAME", "Value": "七星站"}, {"Key": "A_G001", "Value": 30}],
+                    [{"Key": "NAME", "Value": "新進站"}]
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            item_needs.to_csv(),
+            "油站名稱,\"面紙, \"\"60抽\"\"\"\r\n七星站,30\r\n新進站,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let item_needs = ItemNeeds::merge([deserialize_item_needs(), deserialize_item_needs()]);
+        assert_eq!(item_needs.get_all_items().len(), 13);
+        assert_eq!(item_needs.iter().count(), 90);
+
+        let item_needs = ItemNeeds::merge([]);
+        assert!(item_needs.get_all_items().is_empty());
+        assert_eq!(item_needs.iter().count(), 0);
+    }
+
     #[test]
     fn test_items_need_iter() {
         let item_needs = deserialize_item_needs();
@@ -1,10 +1,10 @@
 mod client;
-mod date;
 mod error;
 mod item_needs;
 mod operation_center;
 
-pub use crate::client::Client;
+pub use crate::client::{Client, DEFAULT_BASE_URL};
 pub use crate::error::Error;
-pub use crate::item_needs::{DisplayMode, GetItemNeedsOptions, Item, ItemNeeds};
+pub use crate::item_needs::{Column, DisplayMode, GetItemNeedsOptions, Item, ItemNeeds};
 pub use crate::operation_center::OperationCenter;
+pub use chunked_fetch::{ChunkOptions, ChunkSize, Progress};
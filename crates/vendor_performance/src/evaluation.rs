@@ -0,0 +1,216 @@
+use crate::{DeliveryLine, Error};
+use freebie::DefectKind;
+use roc_calendar::RocYearMonth;
+use std::fmt::{Display, Formatter};
+
+/// The period a vendor is evaluated over.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Period {
+    Month(RocYearMonth),
+    /// A whole ROC year.
+    Year(i16),
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Period::Month(month) => write!(f, "{}", month),
+            Period::Year(roc_year) => write!(f, "{}年", roc_year),
+        }
+    }
+}
+
+/// How well a vendor delivered over a period.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VendorPerformance {
+    pub vendor: &'static str,
+    pub period: Period,
+    /// The station lines ordered, whether delivered or not.
+    pub lines: u64,
+    pub delivered_lines: u64,
+    pub on_time_lines: u64,
+    /// The working days of delay summed over the delivered lines.
+    pub total_delay_days: u64,
+    pub max_delay_days: u64,
+    pub ordered: u64,
+    pub received: u64,
+    pub shortfall: u64,
+    /// The defective boxes of each kind the freebies of the vendor are inspected for.
+    pub defects: Vec<(DefectKind, u64)>,
+}
+
+impl VendorPerformance {
+    fn new(vendor: &'static str, period: Period) -> Self {
+        VendorPerformance {
+            vendor,
+            period,
+            lines: 0,
+            delivered_lines: 0,
+            on_time_lines: 0,
+            total_delay_days: 0,
+            max_delay_days: 0,
+            ordered: 0,
+            received: 0,
+            shortfall: 0,
+            defects: vec![],
+        }
+    }
+
+    fn add(&mut self, line: &DeliveryLine) {
+        self.lines += 1;
+        if let Some(delay_days) = line.delay_days {
+            self.delivered_lines += 1;
+            self.total_delay_days += delay_days;
+            self.max_delay_days = self.max_delay_days.max(delay_days);
+        }
+        if line.is_on_time() {
+            self.on_time_lines += 1;
+        }
+        self.ordered += line.ordered;
+        self.received += line.received;
+        self.shortfall += line.shortfall();
+        for kind in line.freebie.defect_kinds() {
+            let quantity = line
+                .defects
+                .iter()
+                .filter(|(k, _)| k == kind)
+                .map(|(_, quantity)| quantity)
+                .sum::<u64>();
+            match self.defects.iter_mut().find(|(k, _)| k == kind) {
+                Some((_, total)) => *total += quantity,
+                None => self.defects.push((*kind, quantity)),
+            }
+        }
+    }
+
+    /// The share of the delivered lines which came by their due date, or `None` if nothing was
+    /// delivered.
+    pub fn on_time_rate(&self) -> Option<f64> {
+        (self.delivered_lines > 0).then(|| self.on_time_lines as f64 / self.delivered_lines as f64)
+    }
+
+    /// The mean working days of delay of the delivered lines, on time ones included.
+    pub fn mean_delay_days(&self) -> Option<f64> {
+        (self.delivered_lines > 0)
+            .then(|| self.total_delay_days as f64 / self.delivered_lines as f64)
+    }
+
+    /// The share of the received boxes rejected for the kind of defect, or `None` if the vendor is
+    /// not inspected for it or delivered nothing.
+    pub fn defect_rate(&self, kind: DefectKind) -> Option<f64> {
+        let (_, quantity) = self.defects.iter().find(|(k, _)| *k == kind)?;
+        (self.received > 0).then(|| *quantity as f64 / self.received as f64)
+    }
+}
+
+/// Evaluates each vendor over every month it has lines in, followed by the whole year of those
+/// months. The evaluations are ordered by vendor, then by period with the months of a year before
+/// the year itself.
+pub fn evaluate_vendors(lines: &[DeliveryLine]) -> Result<Vec<VendorPerformance>, Error> {
+    let mut performances: Vec<VendorPerformance> = vec![];
+    for line in lines.iter() {
+        let vendor = line.freebie.vendor().name;
+        let month = RocYearMonth::try_from(line.date)?;
+        for period in [Period::Month(month), Period::Year(month.roc_year())] {
+            match performances
+                .iter_mut()
+                .find(|performance| performance.vendor == vendor && performance.period == period)
+            {
+                Some(performance) => performance.add(line),
+                None => {
+                    let mut performance = VendorPerformance::new(vendor, period);
+                    performance.add(line);
+                    performances.push(performance);
+                }
+            }
+        }
+    }
+
+    performances.sort_by_key(|performance| {
+        let (roc_year, month) = match performance.period {
+            Period::Month(month) => (month.roc_year(), month.month()),
+            Period::Year(roc_year) => (roc_year, i8::MAX),
+        };
+        (performance.vendor, roc_year, month)
+    });
+
+    Ok(performances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freebie::Freebie;
+    use jiff::civil::Date;
+
+    fn line(freebie: Freebie, date: Date, received: u64, delay_days: Option<u64>) -> DeliveryLine {
+        DeliveryLine {
+            freebie,
+            station_name: "新進站".to_string(),
+            date,
+            ordered: 30,
+            received,
+            delay_days,
+            defects: vec![],
+        }
+    }
+
+    #[test]
+    fn test_evaluate_vendors() {
+        let mut damp = line(
+            Freebie::Tissue60,
+            Date::new(2025, 10, 17).unwrap(),
+            30,
+            Some(2),
+        );
+        damp.defects = vec![(DefectKind::Damp, 3)];
+        let lines = [
+            line(
+                Freebie::MineralWater,
+                Date::new(2025, 9, 4).unwrap(),
+                30,
+                Some(0),
+            ),
+            damp,
+            line(Freebie::Tissue60, Date::new(2025, 10, 15).unwrap(), 0, None),
+            line(
+                Freebie::Tissue60,
+                Date::new(2025, 9, 12).unwrap(),
+                30,
+                Some(0),
+            ),
+        ];
+        let performances = evaluate_vendors(&lines).unwrap();
+        assert_eq!(
+            performances
+                .iter()
+                .map(|performance| format!("{}{}", performance.vendor, performance.period))
+                .collect::<Vec<_>>(),
+            vec![
+                "台糖公司114年09月",
+                "台糖公司114年",
+                "正隆公司114年09月",
+                "正隆公司114年10月",
+                "正隆公司114年",
+            ]
+        );
+
+        let october = &performances[3];
+        assert_eq!(october.lines, 2);
+        assert_eq!(october.delivered_lines, 1);
+        assert_eq!(october.on_time_rate(), Some(0.));
+        assert_eq!(october.mean_delay_days(), Some(2.));
+        assert_eq!(october.shortfall, 30);
+        assert_eq!(october.defect_rate(DefectKind::Damp), Some(0.1));
+        assert_eq!(october.defect_rate(DefectKind::Damaged), Some(0.));
+        assert_eq!(october.defect_rate(DefectKind::BrokenBottle), None);
+
+        let year = &performances[4];
+        assert_eq!(year.lines, 3);
+        assert_eq!(year.on_time_rate(), Some(0.5));
+        assert_eq!(year.mean_delay_days(), Some(1.));
+        assert_eq!(year.max_delay_days, 2);
+        assert_eq!(year.ordered, 90);
+        assert_eq!(year.received, 60);
+    }
+}
@@ -0,0 +1,9 @@
+mod error;
+mod evaluation;
+mod line;
+mod report;
+
+pub use crate::error::Error;
+pub use crate::evaluation::{Period, VendorPerformance, evaluate_vendors};
+pub use crate::line::DeliveryLine;
+pub use crate::report::generate_vendor_performance_report;
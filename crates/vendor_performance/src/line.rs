@@ -0,0 +1,245 @@
+use freebie::{DefectKind, Freebie};
+use jiff::civil::Date;
+use legacy_import::LegacyDelivery;
+use order_ledger::{Delivery, Order};
+use roc_calendar::WorkingDayCalendar;
+
+/// What a station received for one order, the unit the vendors are evaluated on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeliveryLine {
+    pub freebie: Freebie,
+    pub station_name: String,
+    /// The date the line counts towards, which is the last delivery date, or the due date if
+    /// nothing was delivered.
+    pub date: Date,
+    pub ordered: u64,
+    pub received: u64,
+    /// The working days the last delivery came after the due date, or `None` if nothing was
+    /// delivered.
+    pub delay_days: Option<u64>,
+    pub defects: Vec<(DefectKind, u64)>,
+}
+
+impl DeliveryLine {
+    /// Matches the deliveries recorded in the app to the stations of the order, due on their
+    /// proposed delivery date (擬交貨日期).
+    pub fn from_order(
+        order: &Order,
+        deliveries: &[Delivery],
+        calendar: &WorkingDayCalendar,
+    ) -> Vec<DeliveryLine> {
+        let vendor = order.freebie.vendor();
+        order
+            .stations
+            .iter()
+            .filter(|station| station.quantity > 0)
+            .map(|station| {
+                let due_date = vendor.proposed_delivery_date(
+                    calendar,
+                    &order.notification_date,
+                    &station.order_date,
+                );
+                let deliveries = deliveries
+                    .iter()
+                    .filter(|delivery| {
+                        delivery.order_id == order.id
+                            && delivery.station_name == station.station_name
+                    })
+                    .collect::<Vec<_>>();
+                let delivery_date = deliveries
+                    .iter()
+                    .map(|delivery| delivery.delivery_date)
+                    .max();
+                let mut defects: Vec<(DefectKind, u64)> = vec![];
+                for defect in deliveries
+                    .iter()
+                    .flat_map(|delivery| delivery.defects.iter())
+                {
+                    add_defect(&mut defects, defect.kind, defect.quantity);
+                }
+
+                DeliveryLine {
+                    freebie: order.freebie,
+                    station_name: station.station_name.clone(),
+                    date: delivery_date.unwrap_or(due_date),
+                    ordered: station.quantity,
+                    received: deliveries
+                        .iter()
+                        .map(|delivery| delivery.quantity_received)
+                        .sum(),
+                    delay_days: delivery_date
+                        .map(|date| calendar.working_days_between(&due_date, &date).max(0) as u64),
+                    defects,
+                }
+            })
+            .collect()
+    }
+
+    /// Reads a delivery imported from a monthly delivery statistics workbook, whose ordered
+    /// quantity is taken to be the quantity delivered. Its delay is the 延遲交貨天數 of the sheet,
+    /// or counted from the proposed delivery date of the order date if the sheet has none.
+    ///
+    /// Returns `None` for the products other than the freebies and the deliveries whose delay cannot
+    /// be told. Defects of kinds the freebie is not inspected for are left out.
+    pub fn from_legacy(
+        delivery: &LegacyDelivery,
+        calendar: &WorkingDayCalendar,
+    ) -> Option<DeliveryLine> {
+        let freebie = delivery.freebie?;
+        let delivery_date = delivery.delivery_date?;
+        let delay_days = match delivery.delay_days {
+            Some(delay_days) => delay_days,
+            None => {
+                let order_date = delivery.order_date?;
+                let due_date =
+                    freebie
+                        .vendor()
+                        .proposed_delivery_date(calendar, &order_date, &order_date);
+                calendar
+                    .working_days_between(&due_date, &delivery_date)
+                    .max(0) as u64
+            }
+        };
+        let mut defects = vec![];
+        for defect in delivery.defects.iter() {
+            if let Some(kind) = DefectKind::from_label(&defect.kind)
+                && freebie.defect_kinds().contains(&kind)
+            {
+                add_defect(&mut defects, kind, defect.quantity);
+            }
+        }
+
+        Some(DeliveryLine {
+            freebie,
+            station_name: delivery.station_name.clone(),
+            date: delivery_date,
+            ordered: delivery.quantity,
+            received: delivery.quantity,
+            delay_days: Some(delay_days),
+            defects,
+        })
+    }
+
+    pub fn is_delivered(&self) -> bool {
+        self.delay_days.is_some()
+    }
+
+    pub fn is_on_time(&self) -> bool {
+        self.delay_days == Some(0)
+    }
+
+    /// The boxes ordered but not received.
+    pub fn shortfall(&self) -> u64 {
+        self.ordered.saturating_sub(self.received)
+    }
+}
+
+fn add_defect(defects: &mut Vec<(DefectKind, u64)>, kind: DefectKind, quantity: u64) {
+    match defects.iter_mut().find(|(k, _)| *k == kind) {
+        Some((_, total)) => *total += quantity,
+        None => defects.push((kind, quantity)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freebie::StationQuantity;
+    use order_ledger::{Defect, OrderStatus};
+    use std::path::PathBuf;
+
+    fn order() -> Order {
+        let station = |station_name: &str, quantity| StationQuantity {
+            station_name: station_name.to_string(),
+            order_date: Date::new(2025, 9, 30).unwrap(),
+            quantity,
+            adjustment: None,
+        };
+        Order {
+            id: 1,
+            freebie: Freebie::Tissue60,
+            order_number: "10-1".parse().unwrap(),
+            notification_date: Date::new(2025, 10, 7).unwrap(),
+            report_start_date: Date::new(2025, 9, 30).unwrap(),
+            report_end_date: Date::new(2025, 10, 6).unwrap(),
+            stations: vec![station("新進站", 30), station("七星站", 20)],
+            output_path: PathBuf::from("訂購單.xlsx"),
+            status: OrderStatus::Delivered,
+            sent_date: Date::new(2025, 10, 7).ok(),
+            confirmation: None,
+        }
+    }
+
+    fn deliveries() -> Vec<Delivery> {
+        let delivery = |id, day, quantity_received, defects| Delivery {
+            id,
+            order_id: 1,
+            freebie: Freebie::Tissue60,
+            station_name: "新進站".to_string(),
+            delivery_date: Date::new(2025, 10, day).unwrap(),
+            quantity_received,
+            defects,
+        };
+        vec![
+            delivery(
+                1,
+                14,
+                20,
+                vec![Defect {
+                    kind: DefectKind::Damp,
+                    quantity: 1,
+                }],
+            ),
+            delivery(2, 17, 10, vec![]),
+        ]
+    }
+
+    #[test]
+    fn test_from_order() {
+        let calendar = WorkingDayCalendar::bundled();
+        let lines = DeliveryLine::from_order(&order(), &deliveries(), &calendar);
+
+        // Due on 10/15, the rest of 新進站 came two working days late
+        assert_eq!(
+            lines[0],
+            DeliveryLine {
+                freebie: Freebie::Tissue60,
+                station_name: "新進站".to_string(),
+                date: Date::new(2025, 10, 17).unwrap(),
+                ordered: 30,
+                received: 30,
+                delay_days: Some(2),
+                defects: vec![(DefectKind::Damp, 1)],
+            }
+        );
+
+        // Nothing came for 七星站
+        assert_eq!(lines[1].date, Date::new(2025, 10, 15).unwrap());
+        assert!(!lines[1].is_delivered());
+        assert_eq!(lines[1].shortfall(), 20);
+    }
+
+    #[test]
+    fn test_from_legacy() {
+        let calendar = WorkingDayCalendar::bundled();
+        let import =
+            legacy_import::import_workbook("../../reports/面紙交貨統計表110抽.xls").unwrap();
+        let lines = import
+            .deliveries
+            .iter()
+            .filter_map(|delivery| DeliveryLine::from_legacy(delivery, &calendar))
+            .collect::<Vec<_>>();
+        assert!(lines.iter().all(|line| line.freebie == Freebie::Tissue110));
+        assert_eq!(lines.iter().filter(|line| !line.is_on_time()).count(), 45);
+
+        let line = lines
+            .iter()
+            .find(|line| {
+                line.station_name == "七星站" && line.date == Date::new(2025, 9, 12).unwrap()
+            })
+            .unwrap();
+        assert_eq!(line.ordered, 30);
+        assert_eq!(line.shortfall(), 0);
+        assert!(line.is_on_time());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Period, VendorPerformance};
+use freebie::DefectKind;
+use umya_spreadsheet::{NumberingFormat, Spreadsheet, Worksheet};
+
+/// Writes the evaluations into a workbook for the annual vendor evaluation, with a sheet of the
+/// months and one of the years. Rates which cannot be told, such as the defect rates of kinds a
+/// vendor is not inspected for, are left blank.
+pub fn generate_vendor_performance_report(performances: &[VendorPerformance]) -> Spreadsheet {
+    let mut spreadsheet = umya_spreadsheet::new_file_empty_worksheet();
+    for (sheet_name, is_yearly) in [("月別", false), ("年度", true)] {
+        let worksheet = spreadsheet
+            .new_sheet(sheet_name)
+            .expect("unable to add sheet");
+        set_headers(worksheet);
+        for (row, performance) in (2..).zip(
+            performances
+                .iter()
+                .filter(|performance| matches!(performance.period, Period::Year(_)) == is_yearly),
+        ) {
+            set_performance(worksheet, row, performance);
+        }
+        worksheet.get_column_dimension_mut("A").set_width(12.);
+        worksheet.get_column_dimension_mut("B").set_width(12.);
+    }
+
+    spreadsheet
+}
+
+fn set_headers(worksheet: &mut Worksheet) {
+    let headers = [
+        "廠商",
+        "期間",
+        "訂購站次",
+        "交貨站次",
+        "準時站次",
+        "準時率",
+        "平均延遲工作天",
+        "最大延遲工作天",
+        "訂購數量",
+        "實收數量",
+        "短少數量",
+    ]
+    .into_iter()
+    .map(|header| header.to_string())
+    .chain(
+        DefectKind::all()
+            .into_iter()
+            .map(|kind| format!("{}率", kind.label())),
+    );
+    for (column, header) in headers.enumerate() {
+        worksheet
+            .get_cell_mut((column as u32 + 1, 1))
+            .set_value(header)
+            .get_style_mut()
+            .get_font_mut()
+            .set_bold(true);
+    }
+}
+
+fn set_performance(worksheet: &mut Worksheet, row: u32, performance: &VendorPerformance) {
+    worksheet
+        .get_cell_mut((1, row))
+        .set_value(performance.vendor);
+    worksheet
+        .get_cell_mut((2, row))
+        .set_value(performance.period.to_string());
+    for (column, value) in [
+        (3, performance.lines),
+        (4, performance.delivered_lines),
+        (5, performance.on_time_lines),
+        (8, performance.max_delay_days),
+        (9, performance.ordered),
+        (10, performance.received),
+        (11, performance.shortfall),
+    ] {
+        worksheet
+            .get_cell_mut((column, row))
+            .set_value_number(value as f64);
+    }
+    if let Some(on_time_rate) = performance.on_time_rate() {
+        set_rate(worksheet, 6, row, on_time_rate);
+    }
+    if let Some(mean_delay_days) = performance.mean_delay_days() {
+        worksheet
+            .get_cell_mut((7, row))
+            .set_value_number(mean_delay_days)
+            .get_style_mut()
+            .get_number_format_mut()
+            .set_format_code("0.0");
+    }
+    for (i, kind) in DefectKind::all().into_iter().enumerate() {
+        if let Some(defect_rate) = performance.defect_rate(kind) {
+            set_rate(worksheet, 12 + i as u32, row, defect_rate);
+        }
+    }
+}
+
+fn set_rate(worksheet: &mut Worksheet, column: u32, row: u32, rate: f64) {
+    worksheet
+        .get_cell_mut((column, row))
+        .set_value_number(rate)
+        .get_style_mut()
+        .get_number_format_mut()
+        .set_format_code(NumberingFormat::FORMAT_PERCENTAGE_00);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeliveryLine, evaluate_vendors};
+    use freebie::Freebie;
+    use jiff::civil::Date;
+
+    #[test]
+    fn test_generate_vendor_performance_report() {
+        let lines = [
+            DeliveryLine {
+                freebie: Freebie::Tissue110,
+                station_name: "七星站".to_string(),
+                date: Date::new(2025, 9, 12).unwrap(),
+                ordered: 30,
+                received: 25,
+                delay_days: Some(1),
+                defects: vec![(DefectKind::Damaged, 5)],
+            },
+            DeliveryLine {
+                freebie: Freebie::Tissue110,
+                station_name: "月眉站".to_string(),
+                date: Date::new(2025, 10, 3).unwrap(),
+                ordered: 50,
+                received: 50,
+                delay_days: Some(0),
+                defects: vec![],
+            },
+        ];
+        let spreadsheet = generate_vendor_performance_report(&evaluate_vendors(&lines).unwrap());
+
+        let worksheet = spreadsheet.get_sheet_by_name("月別").unwrap();
+        assert_eq!(worksheet.get_value("A1"), "廠商");
+        assert_eq!(worksheet.get_value("L1"), "破損率");
+        assert_eq!(worksheet.get_value("A2"), "欣達紙業");
+        assert_eq!(worksheet.get_value("B2"), "114年09月");
+        assert_eq!(worksheet.get_value("F2"), "0");
+        assert_eq!(worksheet.get_value("K2"), "5");
+        assert_eq!(worksheet.get_value("L2"), "0.2");
+        assert_eq!(worksheet.get_value("O2"), "");
+        assert_eq!(worksheet.get_value("B3"), "114年10月");
+
+        let worksheet = spreadsheet.get_sheet_by_name("年度").unwrap();
+        assert_eq!(worksheet.get_value("B2"), "114年");
+        assert_eq!(worksheet.get_value("F2"), "0.5");
+        assert_eq!(worksheet.get_value("G2"), "0.5");
+        assert_eq!(worksheet.get_value("H2"), "1");
+        assert_eq!(worksheet.get_value("B3"), "");
+    }
+}